@@ -14,6 +14,7 @@
 use proc_macro2::TokenStream;
 use quote::quote;
 
+use crate::constraints;
 use crate::context::Context;
 use crate::types::AliasDefinition;
 
@@ -104,6 +105,36 @@ pub fn generate(ctx: &Context, def: &AliasDefinition) -> TokenStream {
         None => quote!(),
     };
 
+    let validate = if ctx.validation() {
+        let ok = ctx.ok_ident(def.type_name());
+
+        let mut checks = vec![];
+        if ctx.needs_validation(def.alias()) {
+            checks.push(quote!(conjure_object::Validate::validate(&self.0)?;));
+        }
+        if let Some(constraint) = ctx.type_constraint(def.type_name()) {
+            checks.push(constraints::generate_checks(
+                ctx,
+                def.type_name(),
+                quote!(&self.0),
+                def.alias(),
+                constraint,
+                None,
+            ));
+        }
+
+        quote! {
+            impl conjure_object::Validate for #name {
+                fn validate(&self) -> #result<(), conjure_object::ValidationError> {
+                    #(#checks)*
+                    #ok(())
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
     let dealiased_type = ctx.rust_type(def.type_name(), ctx.dealiased_type(def.alias()));
 
     quote! {
@@ -119,6 +150,8 @@ pub fn generate(ctx: &Context, def: &AliasDefinition) -> TokenStream {
 
         #from_iterator
 
+        #validate
+
         impl std::convert::From<#dealiased_type> for #name {
             #[inline]
             fn from(v: #dealiased_type) -> Self {
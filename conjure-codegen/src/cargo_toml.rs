@@ -6,7 +6,34 @@ use std::collections::BTreeMap;
 pub struct Manifest<'a> {
     pub package: Package<'a>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
-    pub dependencies: BTreeMap<&'a str, &'a str>,
+    pub dependencies: BTreeMap<&'a str, Dependency<'a>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<&'a str, Vec<&'a str>>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum Dependency<'a> {
+    Simple(&'a str),
+    Detailed(DetailedDependency<'a>),
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct DetailedDependency<'a> {
+    pub version: &'a str,
+    #[serde(skip_serializing_if = "is_true")]
+    pub default_features: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pub optional: bool,
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 #[derive(Serialize)]
@@ -26,9 +26,9 @@ enum Style {
     Sync,
 }
 
-pub fn generate(ctx: &Context, def: &ServiceDefinition) -> TokenStream {
-    let async_ = generate_inner(ctx, def, Style::Async);
-    let sync = generate_inner(ctx, def, Style::Sync);
+pub fn generate(ctx: &Context, def: &ServiceDefinition, smile_requests: bool) -> TokenStream {
+    let async_ = generate_inner(ctx, def, Style::Async, smile_requests);
+    let sync = generate_inner(ctx, def, Style::Sync, smile_requests);
 
     quote! {
         #async_
@@ -37,7 +37,12 @@ pub fn generate(ctx: &Context, def: &ServiceDefinition) -> TokenStream {
     }
 }
 
-fn generate_inner(ctx: &Context, def: &ServiceDefinition, style: Style) -> TokenStream {
+fn generate_inner(
+    ctx: &Context,
+    def: &ServiceDefinition,
+    style: Style,
+    smile_requests: bool,
+) -> TokenStream {
     let docs = ctx.docs(def.docs());
     let suffix = match style {
         Style::Async => "AsyncClient",
@@ -58,7 +63,7 @@ fn generate_inner(ctx: &Context, def: &ServiceDefinition, style: Style) -> Token
     let endpoints = def
         .endpoints()
         .iter()
-        .map(|e| generate_endpoint(ctx, def, style, e));
+        .map(|e| generate_endpoint(ctx, def, style, smile_requests, e));
 
     quote! {
         #docs
@@ -87,6 +92,7 @@ fn generate_endpoint(
     ctx: &Context,
     def: &ServiceDefinition,
     style: Style,
+    smile_requests: bool,
     endpoint: &EndpointDefinition,
 ) -> TokenStream {
     let docs = ctx.docs(endpoint.docs());
@@ -124,7 +130,7 @@ fn generate_endpoint(
     let where_ = where_(ctx, style, body_arg);
 
     let request = quote!(request_);
-    let setup_request = setup_request(ctx, body_arg, style, &request);
+    let setup_request = setup_request(ctx, body_arg, style, smile_requests, &request);
 
     let method = endpoint
         .http_method()
@@ -238,6 +244,7 @@ fn setup_request(
     ctx: &Context,
     body_arg: Option<&ArgumentDefinition>,
     style: Style,
+    smile_requests: bool,
     request: &TokenStream,
 ) -> TokenStream {
     match body_arg {
@@ -253,9 +260,11 @@ fn setup_request(
                     },
                 }
             } else {
-                let function = match style {
-                    Style::Sync => quote!(encode_serializable_request),
-                    Style::Async => quote!(async_encode_serializable_request),
+                let function = match (style, smile_requests) {
+                    (Style::Sync, false) => quote!(encode_serializable_request),
+                    (Style::Async, false) => quote!(async_encode_serializable_request),
+                    (Style::Sync, true) => quote!(encode_smile_request),
+                    (Style::Async, true) => quote!(async_encode_smile_request),
                 };
                 quote! {
                     let mut #request = conjure_http::private::#function(&#name);
@@ -0,0 +1,257 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Constraints on values declared via the `constraints` IR extension.
+//!
+//! The extension maps the qualified name of an alias (e.g. `com.palantir.product.Name`), of an object field (e.g.
+//! `com.palantir.product.Object.name`), or of a type used as an endpoint argument marker (e.g.
+//! `com.palantir.product.NonEmpty`) to the constraints on its value:
+//!
+//! ```json
+//! "constraints": {
+//!   "com.palantir.product.Object.name": { "minLength": 1, "maxLength": 64 },
+//!   "com.palantir.product.Object.items": { "maxSize": 10 },
+//!   "com.palantir.product.Percentage": { "min": 0, "max": 100 },
+//!   "com.palantir.product.NonEmpty": { "minLength": 1 }
+//! }
+//! ```
+//!
+//! Constraints on optional values apply only when the value is present.
+//!
+//! A marker's constraints apply to the values of every argument carrying it, in addition to any constraints of the
+//! argument's type.
+
+use anyhow::{Context as _, Error};
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::context::Context;
+use crate::types::{ArgumentDefinition, ConjureDefinition, PrimitiveType, Type, TypeName};
+
+const EXTENSION: &str = "constraints";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Constraint {
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    min_size: Option<usize>,
+    max_size: Option<usize>,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+/// Parses the constraints declared in the IR, keyed by qualified name.
+pub fn parse_constraints(defs: &ConjureDefinition) -> Result<HashMap<String, Constraint>, Error> {
+    match defs.extensions().get(EXTENSION) {
+        Some(constraints) => constraints
+            .clone()
+            .deserialize_into()
+            .with_context(|| format!("invalid `{EXTENSION}` extension")),
+        None => Ok(HashMap::new()),
+    }
+}
+
+/// Returns the name of the type referenced by a marker.
+pub fn marker_name(marker: &Type) -> Option<&TypeName> {
+    match marker {
+        Type::Reference(name) => Some(name),
+        Type::External(def) => Some(def.external_reference()),
+        _ => None,
+    }
+}
+
+/// Returns the constraints declared via the markers of an argument.
+pub fn marker_constraints<'a>(ctx: &'a Context, arg: &ArgumentDefinition) -> Vec<&'a Constraint> {
+    arg.markers()
+        .iter()
+        .filter_map(marker_name)
+        .filter_map(|name| ctx.type_constraint(name))
+        .collect()
+}
+
+#[derive(Copy, Clone)]
+enum ValueKind {
+    String,
+    Collection,
+    Integer,
+    Double,
+    SafeLong,
+}
+
+fn value_kind(ctx: &Context, def: &Type) -> Option<ValueKind> {
+    match def {
+        Type::Primitive(PrimitiveType::String) => Some(ValueKind::String),
+        Type::Primitive(PrimitiveType::Integer) => Some(ValueKind::Integer),
+        Type::Primitive(PrimitiveType::Double) => Some(ValueKind::Double),
+        Type::Primitive(PrimitiveType::Safelong) => Some(ValueKind::SafeLong),
+        Type::List(_) | Type::Set(_) | Type::Map(_) => Some(ValueKind::Collection),
+        Type::External(def) if !ctx.is_mapped_external(def) => value_kind(ctx, def.fallback()),
+        _ => None,
+    }
+}
+
+fn unwrap_optional(def: &Type) -> (&Type, bool) {
+    match def {
+        Type::Optional(def) => (def.item_type(), true),
+        def => (def, false),
+    }
+}
+
+/// Returns the reasons the constraint can't be applied to a value of the specified type.
+pub fn constraint_problems(ctx: &Context, def: &Type, constraint: &Constraint) -> Vec<String> {
+    let (def, _) = unwrap_optional(def);
+    let kind = value_kind(ctx, def);
+    let mut problems = vec![];
+
+    let mut check = |name: &str, set: bool, applies: bool| {
+        if set && !applies {
+            problems.push(format!("`{name}` does not apply to values of this type"));
+        }
+    };
+    let is_string = matches!(kind, Some(ValueKind::String));
+    let is_collection = matches!(kind, Some(ValueKind::Collection));
+    let is_number = matches!(
+        kind,
+        Some(ValueKind::Integer | ValueKind::Double | ValueKind::SafeLong)
+    );
+    check("minLength", constraint.min_length.is_some(), is_string);
+    check("maxLength", constraint.max_length.is_some(), is_string);
+    check("minSize", constraint.min_size.is_some(), is_collection);
+    check("maxSize", constraint.max_size.is_some(), is_collection);
+    check("min", constraint.min.is_some(), is_number);
+    check("max", constraint.max.is_some(), is_number);
+
+    let bounds = [
+        (
+            "minLength",
+            "maxLength",
+            constraint.min_length,
+            constraint.max_length,
+        ),
+        (
+            "minSize",
+            "maxSize",
+            constraint.min_size,
+            constraint.max_size,
+        ),
+    ];
+    for (min_name, max_name, min, max) in bounds {
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                problems.push(format!("`{min_name}` is greater than `{max_name}`"));
+            }
+        }
+    }
+    if let (Some(min), Some(max)) = (constraint.min, constraint.max) {
+        if min > max {
+            problems.push("`min` is greater than `max`".to_string());
+        }
+    }
+
+    let range = match kind {
+        Some(ValueKind::Integer) => Some((i32::MIN as f64, i32::MAX as f64)),
+        Some(ValueKind::SafeLong) => Some((-(2f64.powi(53) - 1.), 2f64.powi(53) - 1.)),
+        _ => None,
+    };
+    if let Some((lower, upper)) = range {
+        for (name, value) in [("min", constraint.min), ("max", constraint.max)] {
+            if let Some(value) = value {
+                if value.fract() != 0. || value < lower || value > upper {
+                    problems.push(format!("`{name}` is not a valid value of this type"));
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+/// Generates statements checking the constraint against `value`, an expression of type `&T` for the Rust type of
+/// `def`.
+pub fn generate_checks(
+    ctx: &Context,
+    this_type: &TypeName,
+    value: TokenStream,
+    def: &Type,
+    constraint: &Constraint,
+    field: Option<&str>,
+) -> TokenStream {
+    let (item_type, optional) = unwrap_optional(def);
+    let Some(kind) = value_kind(ctx, item_type) else {
+        return quote!();
+    };
+
+    let map_err = match field {
+        Some(field) => quote!(.map_err(|e| e.with_field(#field))),
+        None => quote!(),
+    };
+
+    let mut checks = vec![];
+    for (check, bound) in [
+        (quote!(check_min_length), constraint.min_length),
+        (quote!(check_max_length), constraint.max_length),
+    ] {
+        if let Some(bound) = bound {
+            let bound = Literal::usize_unsuffixed(bound);
+            checks.push(quote!(conjure_object::private::#check(value, #bound)#map_err?;));
+        }
+    }
+    for (check, bound) in [
+        (quote!(check_min_size), constraint.min_size),
+        (quote!(check_max_size), constraint.max_size),
+    ] {
+        if let Some(bound) = bound {
+            let bound = Literal::usize_unsuffixed(bound);
+            checks.push(quote!(conjure_object::private::#check(value.len(), #bound)#map_err?;));
+        }
+    }
+    for (check, bound) in [
+        (quote!(check_min), constraint.min),
+        (quote!(check_max), constraint.max),
+    ] {
+        let Some(bound) = bound else {
+            continue;
+        };
+        let (value, bound) = match kind {
+            ValueKind::Integer => (quote!(*value), Literal::i32_unsuffixed(bound as i32)),
+            ValueKind::Double => (quote!(*value), Literal::f64_unsuffixed(bound)),
+            ValueKind::SafeLong => (quote!(**value), Literal::i64_unsuffixed(bound as i64)),
+            ValueKind::String | ValueKind::Collection => continue,
+        };
+        checks.push(quote!(conjure_object::private::#check(#value, #bound)#map_err?;));
+    }
+
+    if checks.is_empty() {
+        return quote!();
+    }
+
+    if optional {
+        let some = ctx.some_ident(this_type);
+        quote! {
+            if let #some(value) = #value {
+                #(#checks)*
+            }
+        }
+    } else {
+        quote! {
+            {
+                let value = #value;
+                #(#checks)*
+            }
+        }
+    }
+}
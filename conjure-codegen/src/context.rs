@@ -19,10 +19,11 @@ use quote::quote;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 
+use crate::constraints::Constraint;
 use crate::errors::error_object_definition;
 use crate::types::{
-    ArgumentDefinition, ConjureDefinition, Documentation, LogSafety, PrimitiveType, Type,
-    TypeDefinition, TypeName,
+    ArgumentDefinition, ConjureDefinition, Documentation, ExternalReference, LogSafety,
+    PrimitiveType, Type, TypeDefinition, TypeName,
 };
 
 enum CachedLogSafety {
@@ -37,11 +38,21 @@ struct TypeContext {
     log_safety: RefCell<CachedLogSafety>,
 }
 
+/// Absolute paths of Conjure packages and types provided by other crates.
+#[derive(Default)]
+pub struct ExternPaths {
+    pub packages: HashMap<String, TokenStream>,
+    pub types: HashMap<String, TokenStream>,
+}
+
 pub struct Context {
     types: HashMap<TypeName, TypeContext>,
     exhaustive: bool,
     strip_prefix: Vec<String>,
     version: Option<String>,
+    external_types: HashMap<String, TokenStream>,
+    extern_paths: ExternPaths,
+    constraints: Option<HashMap<String, Constraint>>,
 }
 
 impl Context {
@@ -50,12 +61,18 @@ impl Context {
         exhaustive: bool,
         strip_prefix: Option<&str>,
         version: Option<&str>,
+        external_types: HashMap<String, TokenStream>,
+        extern_paths: ExternPaths,
+        constraints: Option<HashMap<String, Constraint>>,
     ) -> Context {
         let mut context = Context {
             types: HashMap::new(),
             exhaustive,
             strip_prefix: vec![],
             version: version.map(str::to_owned),
+            external_types,
+            extern_paths,
+            constraints,
         };
 
         if let Some(strip_prefix) = strip_prefix {
@@ -63,12 +80,7 @@ impl Context {
         }
 
         for def in defs.types() {
-            let name = match &def {
-                TypeDefinition::Alias(def) => def.type_name().clone(),
-                TypeDefinition::Enum(def) => def.type_name().clone(),
-                TypeDefinition::Object(def) => def.type_name().clone(),
-                TypeDefinition::Union(def) => def.type_name().clone(),
-            };
+            let name = def.type_name().clone();
 
             context.types.insert(
                 name,
@@ -100,13 +112,92 @@ impl Context {
         self.exhaustive
     }
 
+    pub fn is_extern(&self, name: &TypeName) -> bool {
+        self.extern_type_path(name).is_some()
+            || self.extern_paths.packages.contains_key(name.package())
+    }
+
+    fn extern_type_path(&self, name: &TypeName) -> Option<&TokenStream> {
+        self.extern_paths.types.get(&name.qualified_name())
+    }
+
+    /// Returns true if generated types should implement `conjure_object::Validate`.
+    pub fn validation(&self) -> bool {
+        self.constraints.is_some()
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = (&str, &Constraint)> {
+        self.constraints
+            .iter()
+            .flatten()
+            .map(|(name, constraint)| (&**name, constraint))
+    }
+
+    pub fn type_constraint(&self, name: &TypeName) -> Option<&Constraint> {
+        self.constraints.as_ref()?.get(&name.qualified_name())
+    }
+
+    pub fn field_constraint(&self, name: &TypeName, field: &str) -> Option<&Constraint> {
+        self.constraints
+            .as_ref()?
+            .get(&format!("{}.{}", name.qualified_name(), field))
+    }
+
+    /// Returns true if values of the type contain generated types which need to be validated.
+    pub fn needs_validation(&self, def: &Type) -> bool {
+        match def {
+            Type::Primitive(_) => false,
+            Type::Optional(def) => self.needs_validation(def.item_type()),
+            Type::List(def) => self.needs_validation(def.item_type()),
+            Type::Set(def) => self.needs_validation(def.item_type()),
+            Type::Map(def) => {
+                (self.needs_validation(def.key_type()) || self.needs_validation(def.value_type()))
+                    && self.is_validate(def.key_type())
+                    && self.is_validate(def.value_type())
+            }
+            Type::Reference(name) => !self.is_extern(name),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.needs_validation(def.fallback())
+            }
+        }
+    }
+
+    // Types from other crates may not have been generated with validation enabled.
+    fn is_validate(&self, def: &Type) -> bool {
+        match def {
+            Type::Primitive(_) => true,
+            Type::Optional(def) => self.is_validate(def.item_type()),
+            Type::List(def) => self.is_validate(def.item_type()),
+            Type::Set(def) => self.is_validate(def.item_type()),
+            Type::Map(def) => {
+                self.is_validate(def.key_type()) && self.is_validate(def.value_type())
+            }
+            Type::Reference(name) => !self.is_extern(name),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_validate(def.fallback())
+            }
+        }
+    }
+
+    pub fn is_mapped_external(&self, def: &ExternalReference) -> bool {
+        self.mapped_external_type(def).is_some()
+    }
+
+    // Mapped external types are treated as opaque, like a reference to an object type.
+    fn mapped_external_type(&self, def: &ExternalReference) -> Option<&TokenStream> {
+        let name = def.external_reference();
+        self.external_types.get(&name.qualified_name())
+    }
+
     fn needs_box(&self, def: &Type) -> bool {
         match def {
             Type::Primitive(_) => false,
             Type::Optional(def) => self.needs_box(def.item_type()),
             Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_needs_box(def),
-            Type::External(def) => self.needs_box(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.needs_box(def.fallback())
+            }
         }
     }
 
@@ -172,7 +263,9 @@ impl Context {
             Type::Optional(def) => self.is_copy(def.item_type()),
             Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_copy(def),
-            Type::External(def) => self.is_copy(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_copy(def.fallback())
+            }
         }
     }
 
@@ -197,7 +290,9 @@ impl Context {
             Type::Primitive(_) => true,
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_required(def),
-            Type::External(def) => self.is_required(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_some() || self.is_required(def.fallback())
+            }
         }
     }
 
@@ -227,7 +322,9 @@ impl Context {
             },
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => true,
             Type::Reference(def) => self.ref_is_default(def),
-            Type::External(def) => self.is_default(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_default(def.fallback())
+            }
         }
     }
 
@@ -255,7 +352,9 @@ impl Context {
             },
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.ref_is_display(def),
-            Type::External(def) => self.is_display(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_display(def.fallback())
+            }
         }
     }
 
@@ -278,7 +377,10 @@ impl Context {
             Type::List(def) => Some(self.rust_type(this_type, def.item_type())),
             Type::Set(def) => Some(self.rust_type_inner(this_type, def.item_type(), true)),
             Type::Reference(def) => self.ref_is_from_iter(this_type, def),
-            Type::External(def) => self.is_from_iter(this_type, def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(_) => None,
+                None => self.is_from_iter(this_type, def.fallback()),
+            },
         }
     }
 
@@ -302,7 +404,10 @@ impl Context {
                 }
                 TypeDefinition::Alias(def) => self.dealiased_type(def.alias()),
             },
-            Type::External(def) => self.dealiased_type(def.fallback()),
+            Type::External(external) => match self.mapped_external_type(external) {
+                Some(_) => def,
+                None => self.dealiased_type(external.fallback()),
+            },
         }
     }
 
@@ -351,7 +456,10 @@ impl Context {
                 quote!(std::collections::BTreeMap<#key, #value>)
             }
             Type::Reference(def) => self.type_path(this_type, def),
-            Type::External(def) => self.rust_type_inner(this_type, def.fallback(), key),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(type_) => type_.clone(),
+                None => self.rust_type_inner(this_type, def.fallback(), key),
+            },
         }
     }
 
@@ -363,7 +471,10 @@ impl Context {
                 quote!(#option<#item>)
             }
             Type::Reference(def) => self.ref_boxed_rust_type(this_type, def),
-            Type::External(def) => self.boxed_rust_type(this_type, def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(type_) => type_.clone(),
+                None => self.boxed_rust_type(this_type, def.fallback()),
+            },
             def => self.rust_type(this_type, def),
         }
     }
@@ -424,7 +535,10 @@ impl Context {
                 quote!(&std::collections::BTreeMap<#key, #value>)
             }
             Type::Reference(def) => self.borrowed_rust_type_ref(this_type, def),
-            Type::External(def) => self.borrowed_rust_type(this_type, def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(type_) => quote!(&#type_),
+                None => self.borrowed_rust_type(this_type, def.fallback()),
+            },
         }
     }
 
@@ -470,7 +584,10 @@ impl Context {
             Type::List(_) => quote!(&*#value),
             Type::Set(_) | Type::Map(_) => quote!(&#value),
             Type::Reference(def) => self.borrow_rust_type_ref(value, def),
-            Type::External(def) => self.borrow_rust_type(value, def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(_) => quote!(&#value),
+                None => self.borrow_rust_type(value, def.fallback()),
+            },
         }
     }
 
@@ -539,7 +656,10 @@ impl Context {
                     BuilderConfig::Normal
                 }
             }
-            Type::External(def) => self.builder_config(this_type, def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(_) => BuilderConfig::Normal,
+                None => self.builder_config(this_type, def.fallback()),
+            },
         }
     }
 
@@ -602,7 +722,12 @@ impl Context {
             Type::Reference(def) => BuilderItemConfig::Normal {
                 type_: self.type_path(this_type, def),
             },
-            Type::External(def) => self.builder_item_config(this_type, def.fallback(), key),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(type_) => BuilderItemConfig::Normal {
+                    type_: type_.clone(),
+                },
+                None => self.builder_item_config(this_type, def.fallback(), key),
+            },
         }
     }
 
@@ -612,7 +737,10 @@ impl Context {
             Type::Optional(_) => Some(quote!(is_none)),
             Type::List(_) | Type::Set(_) | Type::Map(_) => Some(quote!(is_empty)),
             Type::Reference(def) => self.is_empty_method_ref(def),
-            Type::External(def) => self.is_empty_method(def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(_) => None,
+                None => self.is_empty_method(def.fallback()),
+            },
         }
     }
 
@@ -634,7 +762,9 @@ impl Context {
             | Type::Set(_)
             | Type::Map(_) => false,
             Type::Reference(def) => self.is_binary_ref(def),
-            Type::External(def) => self.is_binary(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_binary(def.fallback())
+            }
         }
     }
 
@@ -664,7 +794,9 @@ impl Context {
             },
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_plain_ref(def),
-            Type::External(def) => self.is_plain(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_plain(def.fallback())
+            }
         }
     }
 
@@ -683,7 +815,9 @@ impl Context {
             Type::Primitive(_) => false,
             Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => true,
             Type::Reference(def) => self.is_iterable_ref(def),
-            Type::External(def) => self.is_iterable(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_iterable(def.fallback())
+            }
         }
     }
 
@@ -701,7 +835,10 @@ impl Context {
             Type::Primitive(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => None,
             Type::Optional(def) => Some(def.item_type()),
             Type::Reference(def) => self.is_optional_ref(def),
-            Type::External(def) => self.is_optional(def.fallback()),
+            Type::External(def) => match self.mapped_external_type(def) {
+                Some(_) => None,
+                None => self.is_optional(def.fallback()),
+            },
         }
     }
 
@@ -719,7 +856,9 @@ impl Context {
             Type::List(_) => true,
             Type::Primitive(_) | Type::Optional(_) | Type::Set(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_list_ref(def),
-            Type::External(def) => self.is_list(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_list(def.fallback())
+            }
         }
     }
 
@@ -737,7 +876,9 @@ impl Context {
             Type::Set(_) => true,
             Type::Primitive(_) | Type::Optional(_) | Type::List(_) | Type::Map(_) => false,
             Type::Reference(def) => self.is_set_ref(def),
-            Type::External(def) => self.is_set(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_set(def.fallback())
+            }
         }
     }
 
@@ -758,7 +899,9 @@ impl Context {
             Type::List(def) => self.is_double(def.item_type()),
             Type::Map(def) => self.is_double(def.value_type()),
             Type::Primitive(_) | Type::Set(_) | Type::Reference(_) => false,
-            Type::External(def) => self.is_double(def.fallback()),
+            Type::External(def) => {
+                self.mapped_external_type(def).is_none() && self.is_double(def.fallback())
+            }
         }
     }
 
@@ -915,6 +1058,16 @@ impl Context {
     }
 
     fn type_path(&self, this_type: &TypeName, other_type: &TypeName) -> TokenStream {
+        let other_type_name = self.type_name(other_type.name());
+
+        // types from another crate are referenced by their absolute path
+        if let Some(path) = self.extern_type_path(other_type) {
+            return path.clone();
+        }
+        if let Some(path) = self.extern_paths.packages.get(other_type.package()) {
+            return quote!(#path::#other_type_name);
+        }
+
         let this_module_path = self.module_path(this_type);
         let other_module_path = self.module_path(other_type);
 
@@ -937,8 +1090,6 @@ impl Context {
             components.push(component.parse().unwrap());
         }
 
-        let other_type_name = self.type_name(other_type.name());
-
         quote!(#(#components::)* #other_type_name)
     }
 
@@ -0,0 +1,591 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Wire compatibility checks between versions of a Conjure definition.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! let changes = conjure_codegen::diff::diff_files("old.conjure.json", "new.conjure.json").unwrap();
+//! for change in &changes {
+//!     println!("{change}");
+//! }
+//! assert!(!changes.iter().any(|c| c.is_breaking()));
+//! ```
+
+use anyhow::Error;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+
+use crate::types::{
+    ArgumentDefinition, ConjureDefinition, EndpointDefinition, ErrorDefinition, FieldDefinition,
+    ParameterType, ServiceDefinition, Type, TypeDefinition, TypeName,
+};
+
+/// A change between two versions of a Conjure definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    breaking: bool,
+    location: String,
+    message: String,
+}
+
+impl Change {
+    /// Returns true if the change is not wire compatible with the old version of the definition.
+    ///
+    /// Changes which are not breaking are additive.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking
+    }
+
+    /// Returns the definition which changed, e.g. `type com.palantir.product.MyObject`.
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Returns a description of the change.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.breaking {
+            "breaking"
+        } else {
+            "additive"
+        };
+        write!(fmt, "{kind}: {}: {}", self.location, self.message)
+    }
+}
+
+/// Compares two JSON-encoded Conjure IR files, returning the changes made by the new version.
+pub fn diff_files<P, Q>(old_ir_file: P, new_ir_file: Q) -> Result<Vec<Change>, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let old = crate::parse_ir(old_ir_file.as_ref())?;
+    let new = crate::parse_ir(new_ir_file.as_ref())?;
+    Ok(diff(&old, &new))
+}
+
+fn diff(old: &ConjureDefinition, new: &ConjureDefinition) -> Vec<Change> {
+    let mut differ = Differ {
+        new_types: new.types().iter().map(|d| (d.type_name(), d)).collect(),
+        changes: vec![],
+    };
+
+    let old_types = by_name(old.types(), TypeDefinition::type_name);
+    let new_types = by_name(new.types(), TypeDefinition::type_name);
+    differ.diff_definitions("type", &old_types, &new_types, Differ::diff_type);
+
+    let old_errors = by_name(old.errors(), |d| d.error_name());
+    let new_errors = by_name(new.errors(), |d| d.error_name());
+    differ.diff_definitions("error", &old_errors, &new_errors, Differ::diff_error);
+
+    let old_services = by_name(old.services(), |d| d.service_name());
+    let new_services = by_name(new.services(), |d| d.service_name());
+    differ.diff_definitions(
+        "service",
+        &old_services,
+        &new_services,
+        Differ::diff_service,
+    );
+
+    differ.changes
+}
+
+fn by_name<'a, T, F>(defs: &'a [T], name: F) -> BTreeMap<String, &'a T>
+where
+    F: Fn(&'a T) -> &'a TypeName,
+{
+    defs.iter().map(|d| (name(d).qualified_name(), d)).collect()
+}
+
+struct Differ<'a> {
+    new_types: HashMap<&'a TypeName, &'a TypeDefinition>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Differ<'a> {
+    fn breaking(&mut self, location: &str, message: String) {
+        self.changes.push(Change {
+            breaking: true,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn additive(&mut self, location: &str, message: String) {
+        self.changes.push(Change {
+            breaking: false,
+            location: location.to_string(),
+            message,
+        });
+    }
+
+    fn diff_definitions<T, F>(
+        &mut self,
+        kind: &str,
+        old: &BTreeMap<String, &T>,
+        new: &BTreeMap<String, &'a T>,
+        diff: F,
+    ) where
+        F: Fn(&mut Self, &str, &T, &'a T),
+    {
+        for (name, old_def) in old {
+            let location = format!("{kind} {name}");
+            match new.get(name) {
+                Some(new_def) => diff(self, &location, old_def, new_def),
+                None => self.breaking(&location, "removed".to_string()),
+            }
+        }
+
+        for name in new.keys().filter(|name| !old.contains_key(*name)) {
+            self.additive(&format!("{kind} {name}"), "added".to_string());
+        }
+    }
+
+    fn diff_type(&mut self, location: &str, old: &TypeDefinition, new: &'a TypeDefinition) {
+        match (old, new) {
+            (TypeDefinition::Alias(old), TypeDefinition::Alias(new)) => {
+                self.diff_type_ref(location, "aliased type", old.alias(), new.alias());
+            }
+            (TypeDefinition::Enum(old), TypeDefinition::Enum(new)) => {
+                for value in old.values() {
+                    if !new.values().iter().any(|v| v.value() == value.value()) {
+                        self.breaking(location, format!("value `{}` removed", value.value()));
+                    }
+                }
+                for value in new.values() {
+                    if !old.values().iter().any(|v| v.value() == value.value()) {
+                        self.additive(location, format!("value `{}` added", value.value()));
+                    }
+                }
+            }
+            (TypeDefinition::Object(old), TypeDefinition::Object(new)) => {
+                self.diff_fields(location, "field", old.fields(), new.fields());
+            }
+            (TypeDefinition::Union(old), TypeDefinition::Union(new)) => {
+                for variant in old.union_() {
+                    let name = &**variant.field_name();
+                    match new
+                        .union_()
+                        .iter()
+                        .find(|v| v.field_name() == variant.field_name())
+                    {
+                        Some(new) => {
+                            let what = format!("variant `{name}`");
+                            self.diff_type_ref(location, &what, variant.type_(), new.type_());
+                        }
+                        None => self.breaking(location, format!("variant `{name}` removed")),
+                    }
+                }
+                for variant in new.union_() {
+                    if !old
+                        .union_()
+                        .iter()
+                        .any(|v| v.field_name() == variant.field_name())
+                    {
+                        // like enums, generated unions capture unknown variants unless exhaustive
+                        let name = &**variant.field_name();
+                        self.additive(location, format!("variant `{name}` added"));
+                    }
+                }
+            }
+            (old, new) => self.breaking(
+                location,
+                format!("changed from {} to {}", type_kind(old), type_kind(new)),
+            ),
+        }
+    }
+
+    fn diff_fields(
+        &mut self,
+        location: &str,
+        kind: &str,
+        old: &[FieldDefinition],
+        new: &'a [FieldDefinition],
+    ) {
+        for field in old {
+            let name = &**field.field_name();
+            match new.iter().find(|f| f.field_name() == field.field_name()) {
+                Some(new) => {
+                    let what = format!("{kind} `{name}`");
+                    self.diff_type_ref(location, &what, field.type_(), new.type_());
+                }
+                None => self.breaking(location, format!("{kind} `{name}` removed")),
+            }
+        }
+
+        for field in new {
+            if old.iter().any(|f| f.field_name() == field.field_name()) {
+                continue;
+            }
+
+            let name = &**field.field_name();
+            if self.is_required(field.type_()) {
+                self.breaking(location, format!("required {kind} `{name}` added"));
+            } else {
+                self.additive(location, format!("optional {kind} `{name}` added"));
+            }
+        }
+    }
+
+    fn diff_error(&mut self, location: &str, old: &ErrorDefinition, new: &'a ErrorDefinition) {
+        if old.namespace() != new.namespace() {
+            self.breaking(
+                location,
+                format!(
+                    "namespace changed from {} to {}",
+                    &**old.namespace(),
+                    &**new.namespace()
+                ),
+            );
+        }
+        if old.code() != new.code() {
+            self.breaking(
+                location,
+                format!("code changed from {} to {}", old.code(), new.code()),
+            );
+        }
+
+        self.diff_fields(location, "safe parameter", old.safe_args(), new.safe_args());
+        self.diff_fields(
+            location,
+            "unsafe parameter",
+            old.unsafe_args(),
+            new.unsafe_args(),
+        );
+    }
+
+    fn diff_service(
+        &mut self,
+        location: &str,
+        old: &ServiceDefinition,
+        new: &'a ServiceDefinition,
+    ) {
+        for endpoint in old.endpoints() {
+            let name = &**endpoint.endpoint_name();
+            let endpoint_location = format!("{location} endpoint `{name}`");
+            match new
+                .endpoints()
+                .iter()
+                .find(|e| e.endpoint_name() == endpoint.endpoint_name())
+            {
+                Some(new) => self.diff_endpoint(&endpoint_location, endpoint, new),
+                None => self.breaking(location, format!("endpoint `{name}` removed")),
+            }
+        }
+
+        for endpoint in new.endpoints() {
+            if !old
+                .endpoints()
+                .iter()
+                .any(|e| e.endpoint_name() == endpoint.endpoint_name())
+            {
+                let name = &**endpoint.endpoint_name();
+                self.additive(location, format!("endpoint `{name}` added"));
+            }
+        }
+    }
+
+    fn diff_endpoint(
+        &mut self,
+        location: &str,
+        old: &EndpointDefinition,
+        new: &'a EndpointDefinition,
+    ) {
+        if old.http_method() != new.http_method() {
+            self.breaking(
+                location,
+                format!(
+                    "HTTP method changed from {} to {}",
+                    old.http_method(),
+                    new.http_method()
+                ),
+            );
+        }
+        if old.http_path() != new.http_path() {
+            self.breaking(
+                location,
+                format!(
+                    "HTTP path changed from {} to {}",
+                    &**old.http_path(),
+                    &**new.http_path()
+                ),
+            );
+        }
+        if old.auth() != new.auth() {
+            self.breaking(location, "authentication changed".to_string());
+        }
+
+        for arg in old.args() {
+            let name = &**arg.arg_name();
+            match new.args().iter().find(|a| a.arg_name() == arg.arg_name()) {
+                Some(new) => self.diff_arg(location, arg, new),
+                None => self.breaking(location, format!("argument `{name}` removed")),
+            }
+        }
+
+        for arg in new.args() {
+            if old.args().iter().any(|a| a.arg_name() == arg.arg_name()) {
+                continue;
+            }
+
+            let name = &**arg.arg_name();
+            let optional = match arg.param_type() {
+                ParameterType::Query(_) | ParameterType::Header(_) | ParameterType::Body(_) => {
+                    !self.is_required(arg.type_())
+                }
+                ParameterType::Path(_) => false,
+            };
+            if optional {
+                self.additive(location, format!("optional argument `{name}` added"));
+            } else {
+                self.breaking(location, format!("required argument `{name}` added"));
+            }
+        }
+
+        match (old.returns(), new.returns()) {
+            (Some(old), Some(new)) => self.diff_type_ref(location, "return type", old, new),
+            (None, None) => {}
+            (old, new) => self.breaking(
+                location,
+                format!(
+                    "return type changed from {} to {}",
+                    old.map_or_else(|| "none".to_string(), describe),
+                    new.map_or_else(|| "none".to_string(), describe),
+                ),
+            ),
+        }
+    }
+
+    fn diff_arg(&mut self, location: &str, old: &ArgumentDefinition, new: &'a ArgumentDefinition) {
+        let name = &**old.arg_name();
+        if old.param_type() != new.param_type() {
+            self.breaking(
+                location,
+                format!(
+                    "argument `{name}` changed from {} to {}",
+                    param_kind(old.param_type()),
+                    param_kind(new.param_type())
+                ),
+            );
+        }
+        self.diff_type_ref(
+            location,
+            &format!("argument `{name}`"),
+            old.type_(),
+            new.type_(),
+        );
+    }
+
+    fn diff_type_ref(&mut self, location: &str, what: &str, old: &Type, new: &Type) {
+        if old == new {
+            return;
+        }
+
+        let message = match (old, new) {
+            (Type::Optional(old), new) if old.item_type() == new => {
+                format!("{what} changed from optional to required")
+            }
+            (old, Type::Optional(new)) if old == new.item_type() => {
+                format!("{what} changed from required to optional")
+            }
+            _ => format!("{what} changed from {} to {}", describe(old), describe(new)),
+        };
+        self.breaking(location, message);
+    }
+
+    fn is_required(&self, def: &Type) -> bool {
+        match def {
+            Type::Primitive(_) => true,
+            Type::Optional(_) | Type::List(_) | Type::Set(_) | Type::Map(_) => false,
+            Type::Reference(name) => match self.new_types.get(name) {
+                Some(TypeDefinition::Alias(def)) => self.is_required(def.alias()),
+                _ => true,
+            },
+            Type::External(def) => self.is_required(def.fallback()),
+        }
+    }
+}
+
+fn type_kind(def: &TypeDefinition) -> &'static str {
+    match def {
+        TypeDefinition::Alias(_) => "alias",
+        TypeDefinition::Enum(_) => "enum",
+        TypeDefinition::Object(_) => "object",
+        TypeDefinition::Union(_) => "union",
+    }
+}
+
+fn param_kind(def: &ParameterType) -> String {
+    match def {
+        ParameterType::Body(_) => "body".to_string(),
+        ParameterType::Header(def) => format!("header `{}`", &**def.param_id()),
+        ParameterType::Path(_) => "path".to_string(),
+        ParameterType::Query(def) => format!("query `{}`", &**def.param_id()),
+    }
+}
+
+fn describe(def: &Type) -> String {
+    match def {
+        Type::Primitive(def) => def.as_str().to_lowercase(),
+        Type::Optional(def) => format!("optional<{}>", describe(def.item_type())),
+        Type::List(def) => format!("list<{}>", describe(def.item_type())),
+        Type::Set(def) => format!("set<{}>", describe(def.item_type())),
+        Type::Map(def) => format!(
+            "map<{}, {}>",
+            describe(def.key_type()),
+            describe(def.value_type())
+        ),
+        Type::Reference(name) => name.qualified_name(),
+        Type::External(def) => def.external_reference().qualified_name(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::{
+        EndpointName, EnumDefinition, EnumValueDefinition, FieldName, HttpMethod, HttpPath,
+        ObjectDefinition, OptionalType, PrimitiveType, UnionDefinition,
+    };
+
+    fn object(fields: Vec<FieldDefinition>) -> TypeDefinition {
+        TypeDefinition::Object(
+            ObjectDefinition::builder()
+                .type_name(TypeName::new("Object", "com.palantir.test"))
+                .fields(fields)
+                .build(),
+        )
+    }
+
+    fn field(name: &str, type_: Type) -> FieldDefinition {
+        FieldDefinition::new(FieldName(name.to_string()), type_)
+    }
+
+    fn enum_(values: &[&str]) -> TypeDefinition {
+        TypeDefinition::Enum(
+            EnumDefinition::builder()
+                .type_name(TypeName::new("Enum", "com.palantir.test"))
+                .values(values.iter().map(|v| EnumValueDefinition::new(*v)))
+                .build(),
+        )
+    }
+
+    fn union_(variants: Vec<FieldDefinition>) -> TypeDefinition {
+        TypeDefinition::Union(
+            UnionDefinition::builder()
+                .type_name(TypeName::new("Union", "com.palantir.test"))
+                .union_(variants)
+                .build(),
+        )
+    }
+
+    fn service(path: &str) -> ServiceDefinition {
+        ServiceDefinition::builder()
+            .service_name(TypeName::new("Service", "com.palantir.test"))
+            .endpoints([EndpointDefinition::builder()
+                .endpoint_name(EndpointName("get".to_string()))
+                .http_method(HttpMethod::Get)
+                .http_path(HttpPath(path.to_string()))
+                .build()])
+            .build()
+    }
+
+    fn messages(changes: &[Change]) -> Vec<String> {
+        changes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn compatible() {
+        let string = Type::Primitive(PrimitiveType::String);
+        let old = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                object(vec![field("a", string.clone())]),
+                enum_(&["A"]),
+                union_(vec![field("a", string.clone())]),
+            ])
+            .services([service("/get")])
+            .build();
+        let new = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                object(vec![
+                    field("a", string.clone()),
+                    field("b", Type::Optional(OptionalType::new(string.clone()))),
+                ]),
+                enum_(&["A", "B"]),
+                union_(vec![field("a", string.clone()), field("b", string)]),
+            ])
+            .services([service("/get")])
+            .build();
+
+        let changes = diff(&old, &new);
+        assert_eq!(
+            messages(&changes),
+            [
+                "additive: type com.palantir.test.Enum: value `B` added",
+                "additive: type com.palantir.test.Object: optional field `b` added",
+                "additive: type com.palantir.test.Union: variant `b` added",
+            ],
+        );
+        assert!(!changes.iter().any(Change::is_breaking));
+    }
+
+    #[test]
+    fn breaking() {
+        let string = Type::Primitive(PrimitiveType::String);
+        let old = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                object(vec![field("a", string.clone()), field("b", string.clone())]),
+                enum_(&["A", "B"]),
+                union_(vec![field("a", string.clone()), field("b", string.clone())]),
+            ])
+            .services([service("/get")])
+            .build();
+        let new = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                object(vec![
+                    field("a", Type::Optional(OptionalType::new(string.clone()))),
+                    field("c", string.clone()),
+                ]),
+                enum_(&["A"]),
+                union_(vec![field("a", string.clone()), field("c", string)]),
+            ])
+            .services([service("/get/v2")])
+            .build();
+
+        assert_eq!(
+            messages(&diff(&old, &new)),
+            [
+                "breaking: type com.palantir.test.Enum: value `B` removed",
+                "breaking: type com.palantir.test.Object: field `a` changed from required to optional",
+                "breaking: type com.palantir.test.Object: field `b` removed",
+                "breaking: type com.palantir.test.Object: required field `c` added",
+                "breaking: type com.palantir.test.Union: variant `b` removed",
+                "additive: type com.palantir.test.Union: variant `c` added",
+                "breaking: service com.palantir.test.Service endpoint `get`: HTTP path changed from /get to /get/v2",
+            ],
+        );
+    }
+}
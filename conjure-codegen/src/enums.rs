@@ -20,6 +20,7 @@ use crate::types::{EnumDefinition, EnumValueDefinition};
 pub fn generate(ctx: &Context, def: &EnumDefinition) -> TokenStream {
     let enum_ = generate_enum(ctx, def);
     let unknown = generate_unknown(ctx, def);
+    let validate = generate_validate(ctx, def);
 
     quote! {
         use conjure_object::serde::{ser, de};
@@ -28,6 +29,7 @@ pub fn generate(ctx: &Context, def: &EnumDefinition) -> TokenStream {
 
         #enum_
         #unknown
+        #validate
     }
 }
 
@@ -238,3 +240,33 @@ fn generate_unknown(ctx: &Context, def: &EnumDefinition) -> TokenStream {
         }
     }
 }
+
+fn generate_validate(ctx: &Context, def: &EnumDefinition) -> TokenStream {
+    if !ctx.validation() {
+        return quote!();
+    }
+
+    let name = ctx.type_name(def.type_name().name());
+    let result = ctx.result_ident(def.type_name());
+    let ok = ctx.ok_ident(def.type_name());
+    let err = ctx.err_ident(def.type_name());
+
+    let check_unknown = if ctx.exhaustive() {
+        quote!()
+    } else {
+        quote! {
+            if let #name::Unknown(v) = self {
+                return #err(conjure_object::ValidationError::new(format!("unknown variant `{}`", &**v)));
+            }
+        }
+    };
+
+    quote! {
+        impl conjure_object::Validate for #name {
+            fn validate(&self) -> #result<(), conjure_object::ValidationError> {
+                #check_unknown
+                #ok(())
+            }
+        }
+    }
+}
@@ -1188,7 +1188,8 @@ pub trait TestService<#[request_body] I, #[response_writer] O> {
         method = GET,
         path = "/catalog/fileSystems",
         name = "getFileSystems",
-        produces = conjure_http::server::conjure::CollectionResponseSerializer
+        produces = conjure_http::server::conjure::CollectionResponseSerializer,
+        markers = ["javax.annotation.Nonnull"]
     )]
     fn get_file_systems(
         &self,
@@ -1573,7 +1574,8 @@ pub trait AsyncTestService<#[request_body] I, #[response_writer] O> {
         method = GET,
         path = "/catalog/fileSystems",
         name = "getFileSystems",
-        produces = conjure_http::server::conjure::CollectionResponseSerializer
+        produces = conjure_http::server::conjure::CollectionResponseSerializer,
+        markers = ["javax.annotation.Nonnull"]
     )]
     async fn get_file_systems(
         &self,
@@ -0,0 +1,297 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cargo features gating the top-level modules of a generated crate.
+
+use anyhow::{bail, Error};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::context::Context;
+use crate::types::{ConjureDefinition, Type, TypeDefinition, TypeName};
+
+/// The feature gating generated clients.
+pub const CLIENTS: &str = "clients";
+/// The feature gating generated servers.
+pub const SERVERS: &str = "servers";
+
+pub struct PackageFeatures {
+    /// The top-level modules gated behind a feature of the same name.
+    pub gated_modules: BTreeSet<String>,
+    /// The features enabled by each gated module's feature.
+    pub dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+pub fn package_features(ctx: &Context, defs: &ConjureDefinition) -> Result<PackageFeatures, Error> {
+    // top-level module (or `None` for the crate root) -> top-level modules it references
+    let mut references = BTreeMap::<Option<String>, BTreeSet<String>>::new();
+
+    for def in defs.types() {
+        let (name, types) = match def {
+            TypeDefinition::Alias(def) => (def.type_name(), vec![def.alias()]),
+            TypeDefinition::Enum(def) => (def.type_name(), vec![]),
+            TypeDefinition::Object(def) => (
+                def.type_name(),
+                def.fields().iter().map(|f| f.type_()).collect(),
+            ),
+            TypeDefinition::Union(def) => (
+                def.type_name(),
+                def.union_().iter().map(|f| f.type_()).collect(),
+            ),
+        };
+        add_references(ctx, &mut references, name, types);
+    }
+
+    for def in defs.errors() {
+        let types = def
+            .safe_args()
+            .iter()
+            .chain(def.unsafe_args())
+            .map(|f| f.type_())
+            .collect();
+        add_references(ctx, &mut references, def.error_name(), types);
+    }
+
+    for def in defs.services() {
+        let types = def
+            .endpoints()
+            .iter()
+            .flat_map(|e| e.args().iter().map(|a| a.type_()).chain(e.returns()))
+            .collect();
+        add_references(ctx, &mut references, def.service_name(), types);
+    }
+
+    // modules referenced by the crate root are always needed, so they can't be gated
+    let mut ungated = BTreeSet::new();
+    let mut pending = references.get(&None).cloned().unwrap_or_default();
+    while let Some(module) = pending.pop_first() {
+        if ungated.insert(module.clone()) {
+            pending.extend(references.get(&Some(module)).into_iter().flatten().cloned());
+        }
+    }
+
+    let mut dependencies = BTreeMap::new();
+    for (module, referenced) in references {
+        let Some(module) = module else {
+            continue;
+        };
+        if ungated.contains(&module) {
+            continue;
+        }
+
+        let referenced = referenced
+            .into_iter()
+            .filter(|m| *m != module && !ungated.contains(m))
+            .collect();
+        dependencies.insert(module, referenced);
+    }
+
+    if let Some(module) = dependencies.keys().find(|m| *m == CLIENTS || *m == SERVERS) {
+        bail!("the package feature for module `{module}` conflicts with the `{module}` bindings feature");
+    }
+
+    Ok(PackageFeatures {
+        gated_modules: dependencies.keys().cloned().collect(),
+        dependencies,
+    })
+}
+
+fn add_references(
+    ctx: &Context,
+    references: &mut BTreeMap<Option<String>, BTreeSet<String>>,
+    name: &TypeName,
+    types: Vec<&Type>,
+) {
+    if ctx.is_extern(name) {
+        return;
+    }
+
+    let referenced = references.entry(top_level_module(ctx, name)).or_default();
+    for type_ in types {
+        type_references(ctx, type_, referenced);
+    }
+}
+
+fn type_references(ctx: &Context, def: &Type, referenced: &mut BTreeSet<String>) {
+    match def {
+        Type::Primitive(_) => {}
+        Type::Optional(def) => type_references(ctx, def.item_type(), referenced),
+        Type::List(def) => type_references(ctx, def.item_type(), referenced),
+        Type::Set(def) => type_references(ctx, def.item_type(), referenced),
+        Type::Map(def) => {
+            type_references(ctx, def.key_type(), referenced);
+            type_references(ctx, def.value_type(), referenced);
+        }
+        Type::Reference(name) => {
+            if !ctx.is_extern(name) {
+                referenced.extend(top_level_module(ctx, name));
+            }
+        }
+        Type::External(def) => type_references(ctx, def.fallback(), referenced),
+    }
+}
+
+fn top_level_module(ctx: &Context, name: &TypeName) -> Option<String> {
+    ctx.module_path(name).into_iter().next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::ExternPaths;
+    use crate::fixtures::{alias, primitive, reference};
+    use crate::types::{PrimitiveType, ServiceDefinition};
+    use crate::Config;
+    use std::collections::HashMap;
+
+    #[test]
+    fn transitive_dependencies() {
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                alias("com.palantir.a", "A", reference("com.palantir.b", "B")),
+                alias("com.palantir.b", "B", primitive(PrimitiveType::String)),
+                alias("com.palantir.c", "C", primitive(PrimitiveType::String)),
+                alias("com.palantir", "Root", reference("com.palantir.c", "C")),
+            ])
+            .build();
+        let ctx = Context::new(
+            &defs,
+            false,
+            Some("com.palantir"),
+            None,
+            HashMap::new(),
+            ExternPaths::default(),
+            None,
+        );
+
+        let features = package_features(&ctx, &defs).unwrap();
+        assert_eq!(
+            features.gated_modules,
+            BTreeSet::from(["a".to_string(), "b".to_string()]),
+        );
+        assert_eq!(
+            features.dependencies,
+            BTreeMap::from([
+                ("a".to_string(), BTreeSet::from(["b".to_string()])),
+                ("b".to_string(), BTreeSet::new()),
+            ]),
+        );
+    }
+
+    #[test]
+    fn bindings_feature_conflict() {
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([alias(
+                "com.palantir.clients",
+                "A",
+                primitive(PrimitiveType::String),
+            )])
+            .build();
+        let ctx = Context::new(
+            &defs,
+            false,
+            Some("com.palantir"),
+            None,
+            HashMap::new(),
+            ExternPaths::default(),
+            None,
+        );
+
+        assert!(package_features(&ctx, &defs).is_err());
+    }
+
+    // Returns the generated `lib.rs` and `Cargo.toml`.
+    fn generate_crate(defs: &ConjureDefinition) -> (String, String) {
+        let mut config = Config::new();
+        config
+            .strip_prefix("com.palantir".to_string())
+            .build_crate("test-api", "1.0.0")
+            .package_features(true);
+
+        let modules = config
+            .create_modules(defs, HashMap::new(), ExternPaths::default())
+            .unwrap();
+        let lib = prettyplease::unparse(&syn::parse2(modules.create_root_module(true)).unwrap());
+        let manifest =
+            config.render_cargo_toml(config.build_crate.as_ref().unwrap(), defs, &modules);
+
+        (lib, manifest)
+    }
+
+    #[test]
+    fn generated_crate() {
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                alias("com.palantir.a", "A", reference("com.palantir.b", "B")),
+                alias("com.palantir.b", "B", primitive(PrimitiveType::String)),
+            ])
+            .services([ServiceDefinition::new(TypeName::new(
+                "RootService",
+                "com.palantir",
+            ))])
+            .build();
+
+        let (lib, manifest) = generate_crate(&defs);
+        assert_eq!(
+            lib,
+            r#"#![allow(warnings)]
+#[cfg(feature = "clients")]
+#[doc(inline)]
+pub use self::root_service::{RootServiceClient, RootServiceAsyncClient};
+#[cfg(feature = "servers")]
+#[doc(inline)]
+pub use self::root_service::{
+    RootService, AsyncRootService, RootServiceEndpoints, AsyncRootServiceEndpoints,
+};
+pub mod root_service;
+#[cfg(feature = "a")]
+pub mod a;
+#[cfg(feature = "b")]
+pub mod b;
+"#,
+        );
+        assert_eq!(
+            manifest,
+            format!(
+                r#"[package]
+name = "test-api"
+version = "1.0.0"
+edition = "2018"
+
+[dependencies]
+conjure-object = "{version}"
+
+[dependencies.conjure-http]
+version = "{version}"
+optional = true
+
+[features]
+a = ["b"]
+b = []
+clients = ["dep:conjure-http"]
+default = [
+    "a",
+    "b",
+    "clients",
+    "servers",
+]
+servers = ["dep:conjure-http"]
+"#,
+                version = env!("CARGO_PKG_VERSION"),
+            ),
+        );
+    }
+}
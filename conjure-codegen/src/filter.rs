@@ -0,0 +1,267 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filtering of definitions by name.
+
+use anyhow::{bail, Error};
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{ConjureDefinition, Type, TypeDefinition, TypeName};
+
+pub enum Pattern {
+    Name(String),
+    Package(String),
+    PackageTree(String),
+}
+
+impl Pattern {
+    pub fn parse(pattern: &str) -> Pattern {
+        if let Some(package) = pattern.strip_suffix(".**") {
+            Pattern::PackageTree(package.to_string())
+        } else if let Some(package) = pattern.strip_suffix(".*") {
+            Pattern::Package(package.to_string())
+        } else {
+            Pattern::Name(pattern.to_string())
+        }
+    }
+
+    fn matches(&self, name: &TypeName) -> bool {
+        match self {
+            Pattern::Name(pattern) => *pattern == name.qualified_name(),
+            Pattern::Package(package) => name.package() == package,
+            Pattern::PackageTree(package) => {
+                name.package() == package
+                    || name
+                        .package()
+                        .strip_prefix(&**package)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            }
+        }
+    }
+}
+
+/// Returns the subset of definitions selected by the patterns, along with all of the types they reference.
+///
+/// If there are no include patterns, every definition not matching an exclude pattern is selected. Errors are only
+/// selected by the patterns, since the IR has no way for types or services to reference them.
+pub fn filter(
+    defs: &ConjureDefinition,
+    includes: &[Pattern],
+    excludes: &[Pattern],
+) -> Result<ConjureDefinition, Error> {
+    let selected = |name: &TypeName| {
+        (includes.is_empty() || includes.iter().any(|p| p.matches(name)))
+            && !excludes.iter().any(|p| p.matches(name))
+    };
+
+    let types = defs
+        .types()
+        .iter()
+        .map(|def| (def.type_name(), def))
+        .collect::<HashMap<_, _>>();
+
+    // (type, the definition requiring it)
+    let mut pending = vec![];
+
+    for def in defs.types() {
+        if selected(def.type_name()) {
+            pending.push((def.type_name(), None));
+        }
+    }
+
+    let errors = defs
+        .errors()
+        .iter()
+        .filter(|def| selected(def.error_name()))
+        .collect::<Vec<_>>();
+    for def in &errors {
+        for arg in def.safe_args().iter().chain(def.unsafe_args()) {
+            references(arg.type_(), def.error_name(), &mut pending);
+        }
+    }
+
+    let services = defs
+        .services()
+        .iter()
+        .filter(|def| selected(def.service_name()))
+        .collect::<Vec<_>>();
+    for def in &services {
+        for endpoint in def.endpoints() {
+            for arg in endpoint.args() {
+                references(arg.type_(), def.service_name(), &mut pending);
+            }
+            if let Some(returns) = endpoint.returns() {
+                references(returns, def.service_name(), &mut pending);
+            }
+        }
+    }
+
+    let mut required = HashSet::new();
+    while let Some((name, required_by)) = pending.pop() {
+        if let Some(required_by) = required_by {
+            if excludes.iter().any(|p| p.matches(name)) {
+                bail!(
+                    "type {}.{} is excluded but is required by {}.{}",
+                    name.package(),
+                    name.name(),
+                    required_by.package(),
+                    required_by.name(),
+                );
+            }
+        }
+
+        if !required.insert(name) {
+            continue;
+        }
+
+        let fields = match types.get(name) {
+            Some(TypeDefinition::Alias(def)) => vec![def.alias()],
+            Some(TypeDefinition::Object(def)) => def.fields().iter().map(|f| f.type_()).collect(),
+            Some(TypeDefinition::Union(def)) => def.union_().iter().map(|f| f.type_()).collect(),
+            Some(TypeDefinition::Enum(_)) | None => vec![],
+        };
+        for field in fields {
+            references(field, name, &mut pending);
+        }
+    }
+
+    Ok(ConjureDefinition::builder()
+        .version(defs.version())
+        .errors(errors.into_iter().cloned())
+        .types(
+            defs.types()
+                .iter()
+                .filter(|def| required.contains(def.type_name()))
+                .cloned(),
+        )
+        .services(services.into_iter().cloned())
+        .extensions(defs.extensions().clone())
+        .build())
+}
+
+fn references<'a>(
+    def: &'a Type,
+    required_by: &'a TypeName,
+    pending: &mut Vec<(&'a TypeName, Option<&'a TypeName>)>,
+) {
+    match def {
+        Type::Primitive(_) => {}
+        Type::Optional(def) => references(def.item_type(), required_by, pending),
+        Type::List(def) => references(def.item_type(), required_by, pending),
+        Type::Set(def) => references(def.item_type(), required_by, pending),
+        Type::Map(def) => {
+            references(def.key_type(), required_by, pending);
+            references(def.value_type(), required_by, pending);
+        }
+        Type::Reference(name) => pending.push((name, Some(required_by))),
+        Type::External(def) => references(def.fallback(), required_by, pending),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{alias, primitive, reference};
+    use crate::types::{
+        EndpointDefinition, EndpointName, ErrorCode, ErrorDefinition, ErrorNamespace,
+        FieldDefinition, FieldName, HttpMethod, HttpPath, PrimitiveType, ServiceDefinition,
+    };
+
+    fn defs() -> ConjureDefinition {
+        ConjureDefinition::builder()
+            .version(1)
+            .types([
+                alias("com.palantir.a", "A", reference("com.palantir.b", "B")),
+                alias("com.palantir.b", "B", primitive(PrimitiveType::String)),
+                alias("com.palantir.b.c", "C", primitive(PrimitiveType::String)),
+            ])
+            .build()
+    }
+
+    fn names(defs: &ConjureDefinition) -> Vec<&str> {
+        defs.types()
+            .iter()
+            .map(|def| def.type_name().name())
+            .collect()
+    }
+
+    #[test]
+    fn include_closure() {
+        let defs = defs();
+
+        let filtered = filter(&defs, &[Pattern::parse("com.palantir.a.A")], &[]).unwrap();
+        assert_eq!(names(&filtered), ["A", "B"]);
+
+        let filtered = filter(&defs, &[Pattern::parse("com.palantir.b.*")], &[]).unwrap();
+        assert_eq!(names(&filtered), ["B"]);
+
+        let filtered = filter(&defs, &[Pattern::parse("com.palantir.b.**")], &[]).unwrap();
+        assert_eq!(names(&filtered), ["B", "C"]);
+    }
+
+    #[test]
+    fn exclude() {
+        let defs = defs();
+
+        let filtered = filter(&defs, &[], &[Pattern::parse("com.palantir.b.c.*")]).unwrap();
+        assert_eq!(names(&filtered), ["A", "B"]);
+
+        let error = filter(&defs, &[], &[Pattern::parse("com.palantir.b.B")])
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "type com.palantir.b.B is excluded but is required by com.palantir.a.A",
+        );
+    }
+
+    #[test]
+    fn errors_selected_by_pattern() {
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                alias("com.palantir.a", "A", primitive(PrimitiveType::String)),
+                alias("com.palantir.b", "B", primitive(PrimitiveType::String)),
+            ])
+            .errors([ErrorDefinition::builder()
+                .error_name(TypeName::new("Error", "com.palantir.e"))
+                .namespace(ErrorNamespace("Test".to_string()))
+                .code(ErrorCode::InvalidArgument)
+                .safe_args([FieldDefinition::new(
+                    FieldName("b".to_string()),
+                    reference("com.palantir.b", "B"),
+                )])
+                .build()])
+            .services([ServiceDefinition::builder()
+                .service_name(TypeName::new("Service", "com.palantir.s"))
+                .endpoints([EndpointDefinition::builder()
+                    .endpoint_name(EndpointName("get".to_string()))
+                    .http_method(HttpMethod::Get)
+                    .http_path(HttpPath("/get".to_string()))
+                    .returns(reference("com.palantir.a", "A"))
+                    .build()])
+                .build()])
+            .build();
+
+        // endpoints don't declare the errors they throw, so selecting a service doesn't select any errors
+        let filtered = filter(&defs, &[Pattern::parse("com.palantir.s.*")], &[]).unwrap();
+        assert_eq!(names(&filtered), ["A"]);
+        assert!(filtered.errors().is_empty());
+
+        let filtered = filter(&defs, &[Pattern::parse("com.palantir.e.*")], &[]).unwrap();
+        assert_eq!(names(&filtered), ["B"]);
+        assert_eq!(filtered.errors().len(), 1);
+        assert!(filtered.services().is_empty());
+    }
+}
@@ -0,0 +1,34 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! IR definitions shared by the unit tests of the codegen passes.
+
+use crate::types::{AliasDefinition, PrimitiveType, Type, TypeDefinition, TypeName};
+
+pub fn alias(package: &str, name: &str, alias: Type) -> TypeDefinition {
+    TypeDefinition::Alias(
+        AliasDefinition::builder()
+            .type_name(TypeName::new(name, package))
+            .alias(alias)
+            .build(),
+    )
+}
+
+pub fn reference(package: &str, name: &str) -> Type {
+    Type::Reference(TypeName::new(name, package))
+}
+
+pub fn primitive(primitive: PrimitiveType) -> Type {
+    Type::Primitive(primitive)
+}
@@ -283,31 +283,41 @@
 #![allow(clippy::needless_doctest_main)]
 #![recursion_limit = "256"]
 
-use crate::context::Context;
-use crate::types::{ConjureDefinition, TypeDefinition};
+use crate::context::{Context, ExternPaths};
+use crate::types::{ConjureDefinition, TypeDefinition, TypeName};
 use anyhow::{bail, Context as _, Error};
 use proc_macro2::TokenStream;
 use quote::quote;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::env;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 mod aliases;
 mod cargo_toml;
 mod clients;
+mod constraints;
 mod context;
+pub mod diff;
 mod enums;
 mod errors;
+mod features;
+mod filter;
+#[cfg(test)]
+mod fixtures;
 mod http_paths;
+mod merge;
 mod objects;
+mod output;
 mod servers;
 #[allow(dead_code, clippy::all)]
 #[rustfmt::skip]
 mod types;
 mod human_size;
+mod type_names;
 mod unions;
+mod validate;
 
 /// Examples of generated Conjure code.
 ///
@@ -322,12 +332,44 @@ struct CrateInfo {
     version: String,
 }
 
+enum ServiceFilter {
+    All,
+    Only(BTreeSet<String>),
+}
+
+impl ServiceFilter {
+    fn matches(&self, name: &TypeName) -> bool {
+        match self {
+            ServiceFilter::All => true,
+            ServiceFilter::Only(services) => {
+                services.contains(name.name()) || services.contains(&name.qualified_name())
+            }
+        }
+    }
+}
+
+struct ExternCrate {
+    version: String,
+    packages: BTreeMap<String, String>,
+    types: BTreeMap<String, String>,
+}
+
 /// Codegen configuration.
 pub struct Config {
     exhaustive: bool,
     strip_prefix: Option<String>,
     version: Option<String>,
     build_crate: Option<CrateInfo>,
+    smile_request_services: BTreeSet<String>,
+    external_types: BTreeMap<String, String>,
+    extern_crates: BTreeMap<String, ExternCrate>,
+    clients: ServiceFilter,
+    servers: ServiceFilter,
+    package_features: bool,
+    includes: Vec<String>,
+    excludes: Vec<String>,
+    validation: bool,
+    check: bool,
 }
 
 impl Default for Config {
@@ -344,6 +386,16 @@ impl Config {
             strip_prefix: None,
             version: None,
             build_crate: None,
+            smile_request_services: BTreeSet::new(),
+            external_types: BTreeMap::new(),
+            extern_crates: BTreeMap::new(),
+            clients: ServiceFilter::All,
+            servers: ServiceFilter::All,
+            package_features: false,
+            includes: vec![],
+            excludes: vec![],
+            validation: false,
+            check: false,
         }
     }
 
@@ -406,50 +458,347 @@ impl Config {
         self
     }
 
+    /// Generates clients for the specified services which encode request bodies as Smile rather than JSON.
+    ///
+    /// Smile is typically more compact than JSON, but the servers implementing these services must support Smile
+    /// request bodies. Services can be identified by either their name (e.g. `MyService`) or fully qualified name
+    /// (e.g. `com.palantir.product.MyService`).
+    ///
+    /// Defaults to no services.
+    pub fn smile_request_services<I, T>(&mut self, services: I) -> &mut Config
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.smile_request_services = services.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Maps a Conjure external import to a Rust type.
+    ///
+    /// The `name` is the fully qualified name of the import's external reference (e.g. `com.palantir.logsafe.Safe`),
+    /// and the `rust_type` should be an absolute type path (e.g. `my_crate::Safe`). The Rust type is used in place of
+    /// the import's fallback type, and must implement `Serialize`, `Deserialize`, and any traits derived by the
+    /// generated types referencing it.
+    ///
+    /// Imports without a mapping use their fallback type.
+    pub fn external_type<T, U>(&mut self, name: T, rust_type: U) -> &mut Config
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        self.external_types.insert(name.into(), rust_type.into());
+        self
+    }
+
+    /// Reuses the types of Conjure packages already generated in another crate.
+    ///
+    /// `packages` maps each Conjure package provided by the crate to the path of its module relative to the crate root
+    /// (e.g. `product` for the package `com.palantir.product` in a crate generated with a `strip_prefix` of
+    /// `com.palantir`). Definitions in those packages will not be generated, and references to their types will point
+    /// into the crate instead. When generating a full crate, it will depend on the crate at the specified version.
+    pub fn extern_crate<I, K, V>(&mut self, name: &str, version: &str, packages: I) -> &mut Config
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.extern_crate_entry(name, version)
+            .packages
+            .extend(packages.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Reuses individual Conjure types already generated in another crate.
+    ///
+    /// `types` maps the fully qualified name of each Conjure type provided by the crate (e.g.
+    /// `com.palantir.product.MyObject`) to the path of the type relative to the crate root (e.g. `product::MyObject`).
+    /// This behaves like [`Config::extern_crate`], but applies only to the listed types rather than to entire packages.
+    pub fn extern_crate_types<I, K, V>(
+        &mut self,
+        name: &str,
+        version: &str,
+        types: I,
+    ) -> &mut Config
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.extern_crate_entry(name, version)
+            .types
+            .extend(types.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    fn extern_crate_entry(&mut self, name: &str, version: &str) -> &mut ExternCrate {
+        let info = self
+            .extern_crates
+            .entry(name.to_string())
+            .or_insert_with(|| ExternCrate {
+                version: String::new(),
+                packages: BTreeMap::new(),
+                types: BTreeMap::new(),
+            });
+        info.version = version.to_string();
+        info
+    }
+
+    /// Controls generation of client bindings for services.
+    ///
+    /// Defaults to `true`.
+    pub fn generate_clients(&mut self, generate_clients: bool) -> &mut Config {
+        self.clients = if generate_clients {
+            ServiceFilter::All
+        } else {
+            ServiceFilter::Only(BTreeSet::new())
+        };
+        self
+    }
+
+    /// Limits generation of client bindings to the specified services.
+    ///
+    /// Services can be identified by either their name (e.g. `MyService`) or fully qualified name (e.g.
+    /// `com.palantir.product.MyService`). This overrides [`Self::generate_clients`].
+    pub fn client_services<I, T>(&mut self, services: I) -> &mut Config
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.clients = ServiceFilter::Only(services.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Controls generation of server bindings for services.
+    ///
+    /// Defaults to `true`.
+    pub fn generate_servers(&mut self, generate_servers: bool) -> &mut Config {
+        self.servers = if generate_servers {
+            ServiceFilter::All
+        } else {
+            ServiceFilter::Only(BTreeSet::new())
+        };
+        self
+    }
+
+    /// Limits generation of server bindings to the specified services.
+    ///
+    /// Services are identified in the same way as in [`Self::client_services`]. This overrides
+    /// [`Self::generate_servers`].
+    pub fn server_services<I, T>(&mut self, services: I) -> &mut Config
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<String>,
+    {
+        self.servers = ServiceFilter::Only(services.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Gates the modules of the generated crate behind Cargo features.
+    ///
+    /// Each top-level package module is gated behind a feature of the same name, which enables the features of the
+    /// other packages it references. Client and server bindings are additionally gated behind `clients` and `servers`
+    /// features, so generation fails if a gated package module is itself named `clients` or `servers`. The
+    /// `conjure-http` dependency is only enabled by those two features, and the `default` feature enables every
+    /// feature of the crate. Only applies when generating a full crate via [`Self::build_crate`].
+    ///
+    /// Defaults to `false`.
+    pub fn package_features(&mut self, package_features: bool) -> &mut Config {
+        self.package_features = package_features;
+        self
+    }
+
+    /// Limits generation to definitions matching a pattern, along with the types they reference.
+    ///
+    /// Patterns match the fully qualified names of types, errors, and services. A pattern is either a fully qualified
+    /// name (e.g. `com.palantir.product.MyService`), a package followed by `.*` to match the definitions in that
+    /// package (e.g. `com.palantir.product.*`), or a package followed by `.**` to also match the definitions in its
+    /// subpackages.
+    ///
+    /// Errors are only generated if they match a pattern themselves, since types and services do not reference the
+    /// errors they may produce.
+    ///
+    /// Defaults to generating all definitions.
+    pub fn include<T>(&mut self, pattern: T) -> &mut Config
+    where
+        T: Into<String>,
+    {
+        self.includes.push(pattern.into());
+        self
+    }
+
+    /// Excludes definitions matching a pattern from generation.
+    ///
+    /// Patterns have the same format as in [`Self::include`]. Generation will fail if an excluded type is referenced by
+    /// a definition being generated.
+    pub fn exclude<T>(&mut self, pattern: T) -> &mut Config
+    where
+        T: Into<String>,
+    {
+        self.excludes.push(pattern.into());
+        self
+    }
+
+    /// Controls generation of `conjure_object::Validate` implementations for types.
+    ///
+    /// Validation rejects unknown variants of non-exhaustive enums and unions, and checks constraints declared in the
+    /// IR's `constraints` extension. The extension maps the qualified names of aliases and object fields (e.g.
+    /// `com.palantir.product.MyObject.name`) to objects with any of the `minLength`, `maxLength`, `minSize`, `maxSize`,
+    /// `min`, and `max` properties. Generated servers validate request bodies after deserializing them.
+    ///
+    /// Constraints keyed by the qualified name of a type used as an endpoint argument marker apply to the values of all
+    /// arguments with that marker. Generated servers check them after decoding the arguments.
+    ///
+    /// Defaults to `false`.
+    pub fn validation(&mut self, validation: bool) -> &mut Config {
+        self.validation = validation;
+        self
+    }
+
+    /// Controls whether generation only checks that the output directory is up to date rather than writing to it.
+    ///
+    /// In check mode, generation returns an error describing every generated file which is missing, differs from the
+    /// file on disk, or is stale. This is useful to detect drift of checked-in generated code in CI.
+    ///
+    /// Defaults to `false`.
+    pub fn check(&mut self, check: bool) -> &mut Config {
+        self.check = check;
+        self
+    }
+
     /// Generates Rust source files from a JSON-encoded Conjure IR file.
+    ///
+    /// Files are only rewritten if their contents change, and files generated by a previous run which are no longer
+    /// generated are removed. The generated files are tracked in a `.conjure-manifest` file in the output directory.
     pub fn generate_files<P, Q>(&self, ir_file: P, out_dir: Q) -> Result<(), Error>
     where
         P: AsRef<Path>,
         Q: AsRef<Path>,
     {
-        self.generate_files_inner(ir_file.as_ref(), out_dir.as_ref())
+        self.generate_files_inner(&[ir_file.as_ref()], out_dir.as_ref())
     }
 
-    fn generate_files_inner(&self, ir_file: &Path, out_dir: &Path) -> Result<(), Error> {
-        let defs = self.parse_ir(ir_file)?;
+    /// Generates Rust source files from multiple JSON-encoded Conjure IR files.
+    ///
+    /// The definitions of all of the files are merged into a single set of modules. Definitions may appear in multiple
+    /// files as long as they are identical, but an error is returned if they differ.
+    pub fn generate_merged_files<I, P, Q>(&self, ir_files: I, out_dir: Q) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+    {
+        let ir_files = ir_files.into_iter().collect::<Vec<_>>();
+        let ir_files = ir_files.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+        self.generate_files_inner(&ir_files, out_dir.as_ref())
+    }
 
-        if defs.version() != 1 {
-            bail!("unsupported IR version {}", defs.version());
+    fn generate_files_inner(&self, ir_files: &[&Path], out_dir: &Path) -> Result<(), Error> {
+        let mut all_defs = vec![];
+        for &ir_file in ir_files {
+            all_defs.push((ir_file, parse_ir(ir_file)?));
         }
 
-        let modules = self.create_modules(&defs);
+        let defs = match &*all_defs {
+            [] => bail!("no Conjure IR files provided"),
+            [(_, defs)] => defs.clone(),
+            all_defs => merge::merge(all_defs)?,
+        };
+
+        let defs = if self.includes.is_empty() && self.excludes.is_empty() {
+            defs
+        } else {
+            let includes = self
+                .includes
+                .iter()
+                .map(|p| filter::Pattern::parse(p))
+                .collect::<Vec<_>>();
+            let excludes = self
+                .excludes
+                .iter()
+                .map(|p| filter::Pattern::parse(p))
+                .collect::<Vec<_>>();
+            filter::filter(&defs, &includes, &excludes)?
+        };
+
+        let external_types = self.parse_external_types()?;
+        let extern_paths = self.parse_extern_paths()?;
+        let modules = self.create_modules(&defs, external_types, extern_paths)?;
         let (src_dir, lib_root) = if self.build_crate.is_some() {
-            (out_dir.join("src"), true)
+            (PathBuf::from("src"), true)
         } else {
-            (out_dir.to_path_buf(), false)
+            (PathBuf::new(), false)
         };
 
+        let mut output = output::Output::new();
+
         if let Some(info) = &self.build_crate {
-            self.write_cargo_toml(out_dir, info, &defs)?;
-            self.write_rustfmt_toml(out_dir)?;
+            output.insert(
+                PathBuf::from("Cargo.toml"),
+                self.render_cargo_toml(info, &defs, &modules),
+            );
+            output.insert(PathBuf::from("rustfmt.toml"), self.render_rustfmt_toml());
         }
 
-        modules.render(&src_dir, lib_root)?;
+        modules.render(&src_dir, lib_root, &mut output)?;
 
-        Ok(())
+        if self.check {
+            output.check(out_dir)
+        } else {
+            output.write(out_dir)
+        }
     }
 
-    fn parse_ir(&self, ir_file: &Path) -> Result<ConjureDefinition, Error> {
-        let ir = fs::read_to_string(ir_file)
-            .with_context(|| format!("error reading file {}", ir_file.display()))?;
+    fn is_smile_request_service(&self, name: &TypeName) -> bool {
+        self.smile_request_services.contains(name.name())
+            || self.smile_request_services.contains(&name.qualified_name())
+    }
 
-        let defs = conjure_serde::json::client_from_str(&ir)
-            .with_context(|| format!("error parsing Conjure IR file {}", ir_file.display()))?;
+    fn parse_external_types(&self) -> Result<HashMap<String, TokenStream>, Error> {
+        self.external_types
+            .iter()
+            .map(|(name, rust_type)| {
+                let type_ = syn::parse_str::<syn::Type>(rust_type).with_context(|| {
+                    format!("invalid Rust type `{rust_type}` for external type {name}")
+                })?;
+                Ok((name.clone(), quote!(#type_)))
+            })
+            .collect()
+    }
+
+    fn parse_extern_paths(&self) -> Result<ExternPaths, Error> {
+        let mut paths = ExternPaths::default();
+
+        for (name, info) in &self.extern_crates {
+            for (package, module) in &info.packages {
+                let path = extern_path(name, module).with_context(|| {
+                    format!("invalid module path `{module}` for package {package} in crate {name}")
+                })?;
+                paths.packages.insert(package.clone(), path);
+            }
+            for (type_, rust_type) in &info.types {
+                let path = extern_path(name, rust_type).with_context(|| {
+                    format!("invalid type path `{rust_type}` for type {type_} in crate {name}")
+                })?;
+                paths.types.insert(type_.clone(), path);
+            }
+        }
 
-        Ok(defs)
+        Ok(paths)
     }
 
-    fn create_modules(&self, defs: &ConjureDefinition) -> ModuleTrie {
+    fn create_modules(
+        &self,
+        defs: &ConjureDefinition,
+        external_types: HashMap<String, TokenStream>,
+        extern_paths: ExternPaths,
+    ) -> Result<ModuleTrie, Error> {
+        let constraints = if self.validation {
+            Some(constraints::parse_constraints(defs)?)
+        } else {
+            None
+        };
+
         let context = Context::new(
             defs,
             self.exhaustive,
@@ -457,70 +806,133 @@ impl Config {
             self.version
                 .as_deref()
                 .or_else(|| self.build_crate.as_ref().map(|v| &*v.version)),
+            external_types,
+            extern_paths,
+            constraints,
         );
 
+        validate::validate(&context, defs)?;
+
         let mut root = ModuleTrie::new();
+        let package_features = self.package_features && self.build_crate.is_some();
+
+        if package_features {
+            let features = features::package_features(&context, defs)?;
+            root.gated_modules = features.gated_modules;
+            for (module, dependencies) in features.dependencies {
+                root.features
+                    .insert(module, dependencies.into_iter().collect());
+            }
+        }
 
         for def in defs.types() {
-            let (type_name, contents) = match def {
-                TypeDefinition::Enum(def) => (def.type_name(), enums::generate(&context, def)),
-                TypeDefinition::Alias(def) => (def.type_name(), aliases::generate(&context, def)),
-                TypeDefinition::Union(def) => (def.type_name(), unions::generate(&context, def)),
-                TypeDefinition::Object(def) => (def.type_name(), objects::generate(&context, def)),
+            let type_name = def.type_name();
+            if context.is_extern(type_name) {
+                continue;
+            }
+
+            let contents = match def {
+                TypeDefinition::Enum(def) => enums::generate(&context, def),
+                TypeDefinition::Alias(def) => aliases::generate(&context, def),
+                TypeDefinition::Union(def) => unions::generate(&context, def),
+                TypeDefinition::Object(def) => objects::generate(&context, def),
             };
 
             let type_ = Type {
                 module_name: context.module_name(type_name),
                 type_names: vec![context.type_name(type_name.name()).to_string()],
+                gated_type_names: vec![],
                 contents,
             };
             root.insert(&context.module_path(type_name), type_);
         }
 
         for def in defs.errors() {
+            if context.is_extern(def.error_name()) {
+                continue;
+            }
+
             let type_ = Type {
                 module_name: context.module_name(def.error_name()),
                 type_names: vec![context.type_name(def.error_name().name()).to_string()],
+                gated_type_names: vec![],
                 contents: errors::generate(&context, def),
             };
             root.insert(&context.module_path(def.error_name()), type_);
         }
 
         for def in defs.services() {
-            let client = clients::generate(&context, def);
-            let server = servers::generate(&context, def);
+            if context.is_extern(def.service_name()) {
+                continue;
+            }
 
-            let contents = quote! {
-                #client
-                #server
-            };
-            let type_ = Type {
-                module_name: context.module_name(def.service_name()),
-                type_names: vec![
+            let generate_client = self.clients.matches(def.service_name());
+            let generate_server = self.servers.matches(def.service_name());
+            if !generate_client && !generate_server {
+                continue;
+            }
+
+            let mut contents = quote!();
+            let mut type_names = vec![];
+            let mut gated_type_names = vec![];
+
+            if generate_client {
+                let client_names = vec![
                     format!("{}Client", def.service_name().name()),
                     format!("{}AsyncClient", def.service_name().name()),
+                ];
+                let smile_requests = self.is_smile_request_service(def.service_name());
+                let client = clients::generate(&context, def, smile_requests);
+                if package_features {
+                    contents.extend(gate_items(client, features::CLIENTS));
+                    gated_type_names.push((features::CLIENTS, client_names));
+                    root.features
+                        .entry(features::CLIENTS.to_string())
+                        .or_default();
+                } else {
+                    contents.extend(client);
+                    type_names.extend(client_names);
+                }
+            }
+
+            if generate_server {
+                let server_names = vec![
                     context.type_name(def.service_name().name()).to_string(),
                     format!("Async{}", def.service_name().name()),
                     format!("{}Endpoints", def.service_name().name()),
                     format!("Async{}Endpoints", def.service_name().name()),
-                ],
+                ];
+                let server = servers::generate(&context, def);
+                if package_features {
+                    contents.extend(gate_items(server, features::SERVERS));
+                    gated_type_names.push((features::SERVERS, server_names));
+                    root.features
+                        .entry(features::SERVERS.to_string())
+                        .or_default();
+                } else {
+                    contents.extend(server);
+                    type_names.extend(server_names);
+                }
+            }
+
+            let type_ = Type {
+                module_name: context.module_name(def.service_name()),
+                type_names,
+                gated_type_names,
                 contents,
             };
             root.insert(&context.module_path(def.service_name()), type_);
         }
 
-        root
+        Ok(root)
     }
 
-    fn write_cargo_toml(
+    fn render_cargo_toml(
         &self,
-        dir: &Path,
         info: &CrateInfo,
         def: &ConjureDefinition,
-    ) -> Result<(), Error> {
-        fs::create_dir_all(dir)
-            .with_context(|| format!("error creating directory {}", dir.display()))?;
-
+        modules: &ModuleTrie,
+    ) -> String {
         let metadata = def
             .extensions()
             .get("recommended-product-dependencies")
@@ -532,7 +944,8 @@ impl Config {
 
         let mut needs_object = false;
         let mut needs_error = false;
-        let mut needs_http = false;
+        let mut needs_clients = false;
+        let mut needs_servers = false;
 
         if !def.types().is_empty() {
             needs_object = true;
@@ -543,21 +956,51 @@ impl Config {
             needs_error = true;
         }
 
-        if !def.services().is_empty() {
-            needs_http = true;
-            needs_object = true;
+        for service in def.services() {
+            let needs_client = self.clients.matches(service.service_name());
+            let needs_server = self.servers.matches(service.service_name());
+            if needs_client || needs_server {
+                needs_object = true;
+            }
+            needs_clients |= needs_client;
+            needs_servers |= needs_server;
         }
 
         let conjure_version = env!("CARGO_PKG_VERSION");
         let mut dependencies = BTreeMap::new();
         if needs_object {
-            dependencies.insert("conjure-object", conjure_version);
+            dependencies.insert(
+                "conjure-object",
+                cargo_toml::Dependency::Simple(conjure_version),
+            );
         }
         if needs_error {
-            dependencies.insert("conjure-error", conjure_version);
+            dependencies.insert(
+                "conjure-error",
+                cargo_toml::Dependency::Simple(conjure_version),
+            );
         }
-        if needs_http {
-            dependencies.insert("conjure-http", conjure_version);
+        // with package features, only the bindings features need conjure-http
+        let optional_http = modules.features.contains_key(features::CLIENTS)
+            || modules.features.contains_key(features::SERVERS);
+        if needs_servers && !optional_http {
+            dependencies.insert(
+                "conjure-http",
+                cargo_toml::Dependency::Simple(conjure_version),
+            );
+        } else if needs_clients || needs_servers {
+            dependencies.insert(
+                "conjure-http",
+                cargo_toml::Dependency::Detailed(cargo_toml::DetailedDependency {
+                    version: conjure_version,
+                    // the endpoint macros are only used by server bindings
+                    default_features: needs_servers,
+                    optional: optional_http,
+                }),
+            );
+        }
+        for (name, info) in &self.extern_crates {
+            dependencies.insert(name, cargo_toml::Dependency::Simple(&info.version));
         }
 
         let manifest = cargo_toml::Manifest {
@@ -568,40 +1011,95 @@ impl Config {
                 metadata,
             },
             dependencies,
+            features: self.render_features(modules),
         };
 
-        let manifest = toml::to_string_pretty(&manifest).unwrap();
-
-        let file = dir.join("Cargo.toml");
+        toml::to_string_pretty(&manifest).unwrap()
+    }
 
-        fs::write(&file, manifest)
-            .with_context(|| format!("error writing manifest file {}", file.display()))?;
+    fn render_features<'a>(&self, modules: &'a ModuleTrie) -> BTreeMap<&'a str, Vec<&'a str>> {
+        let mut features = modules
+            .features
+            .iter()
+            .map(|(feature, dependencies)| {
+                let dependencies = if feature == features::CLIENTS || feature == features::SERVERS {
+                    vec!["dep:conjure-http"]
+                } else {
+                    dependencies.iter().map(|d| &**d).collect()
+                };
+                (&**feature, dependencies)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        if !features.is_empty() {
+            let default = features.keys().copied().collect();
+            features.insert("default", default);
+        }
 
-        Ok(())
+        features
     }
 
-    fn write_rustfmt_toml(&self, dir: &Path) -> Result<(), Error> {
-        let contents = "\
+    fn render_rustfmt_toml(&self) -> String {
+        "\
 disable_all_formatting = true
-";
+"
+        .to_string()
+    }
+}
+
+fn extern_path(crate_name: &str, path: &str) -> Result<TokenStream, syn::Error> {
+    let mut full_path = format!("::{}", crate_name.replace('-', "_"));
+    if !path.is_empty() {
+        full_path.push_str("::");
+        full_path.push_str(path);
+    }
+    let full_path = syn::parse_str::<syn::Path>(&full_path)?;
+    Ok(quote!(#full_path))
+}
 
-        let file = dir.join("rustfmt.toml");
+fn parse_ir(ir_file: &Path) -> Result<ConjureDefinition, Error> {
+    let ir = fs::read_to_string(ir_file)
+        .with_context(|| format!("error reading file {}", ir_file.display()))?;
 
-        fs::write(file, contents).with_context(|| "error writing rustfmt.toml")?;
+    let defs = conjure_serde::json::client_from_str::<ConjureDefinition>(&ir)
+        .with_context(|| format!("error parsing Conjure IR file {}", ir_file.display()))?;
 
-        Ok(())
+    if defs.version() != 1 {
+        bail!(
+            "unsupported IR version {} in {}",
+            defs.version(),
+            ir_file.display()
+        );
     }
+
+    Ok(defs)
+}
+
+// Adds a `cfg` attribute to each item so it's only compiled with the feature enabled.
+fn gate_items(contents: TokenStream, feature: &str) -> TokenStream {
+    let file = syn::parse2::<syn::File>(contents).unwrap();
+    let items = file.items.iter().map(|item| {
+        quote! {
+            #[cfg(feature = #feature)]
+            #item
+        }
+    });
+
+    quote!(#(#items)*)
 }
 
 struct Type {
     module_name: String,
     type_names: Vec<String>,
+    gated_type_names: Vec<(&'static str, Vec<String>)>,
     contents: TokenStream,
 }
 
 struct ModuleTrie {
     submodules: BTreeMap<String, ModuleTrie>,
     types: Vec<Type>,
+    gated_modules: BTreeSet<String>,
+    features: BTreeMap<String, Vec<String>>,
 }
 
 impl ModuleTrie {
@@ -609,6 +1107,8 @@ impl ModuleTrie {
         ModuleTrie {
             submodules: BTreeMap::new(),
             types: vec![],
+            gated_modules: BTreeSet::new(),
+            features: BTreeMap::new(),
         }
     }
 
@@ -623,35 +1123,28 @@ impl ModuleTrie {
         }
     }
 
-    fn render(&self, dir: &Path, lib_root: bool) -> Result<(), Error> {
-        fs::create_dir_all(dir)
-            .with_context(|| format!("error creating directory {}", dir.display()))?;
-
+    fn render(&self, dir: &Path, lib_root: bool, output: &mut output::Output) -> Result<(), Error> {
         for type_ in &self.types {
-            self.write_module(
-                &dir.join(format!("{}.rs", type_.module_name)),
-                &type_.contents,
-            )?;
+            output.insert(
+                dir.join(format!("{}.rs", type_.module_name)),
+                self.render_module(&type_.contents)?,
+            );
         }
 
         for (name, module) in &self.submodules {
-            module.render(&dir.join(name), false)?;
+            module.render(&dir.join(name), false, output)?;
         }
 
         let root = self.create_root_module(lib_root);
         let file_name = if lib_root { "lib.rs" } else { "mod.rs" };
-        self.write_module(&dir.join(file_name), &root)?;
+        output.insert(dir.join(file_name), self.render_module(&root)?);
 
         Ok(())
     }
 
-    fn write_module(&self, path: &Path, contents: &TokenStream) -> Result<(), Error> {
+    fn render_module(&self, contents: &TokenStream) -> Result<String, Error> {
         let file = syn::parse2(contents.clone())?;
-        let formatted = prettyplease::unparse(&file);
-
-        fs::write(path, formatted)
-            .with_context(|| format!("error writing module {}", path.display()))?;
-        Ok(())
+        Ok(prettyplease::unparse(&file))
     }
 
     fn create_root_module(&self, lib_root: bool) -> TokenStream {
@@ -669,9 +1162,27 @@ impl ModuleTrie {
                 .type_names
                 .iter()
                 .map(|n| n.parse::<TokenStream>().unwrap());
+            let uses = if m.type_names.is_empty() {
+                quote!()
+            } else {
+                quote! {
+                    #[doc(inline)]
+                    pub use self::#module_name::{#(#type_names),*};
+                }
+            };
+
+            let gated_uses = m.gated_type_names.iter().map(|(feature, type_names)| {
+                let type_names = type_names.iter().map(|n| n.parse::<TokenStream>().unwrap());
+                quote! {
+                    #[cfg(feature = #feature)]
+                    #[doc(inline)]
+                    pub use self::#module_name::{#(#type_names),*};
+                }
+            });
+
             quote! {
-                #[doc(inline)]
-                pub use self::#module_name::{#(#type_names),*};
+                #uses
+                #(#gated_uses)*
             }
         });
 
@@ -684,7 +1195,13 @@ impl ModuleTrie {
 
         let sub_mods = self.submodules.keys().map(|v| {
             let module_name = v.parse::<TokenStream>().unwrap();
+            let cfg = if self.gated_modules.contains(v) {
+                quote!(#[cfg(feature = #v)])
+            } else {
+                quote!()
+            };
             quote! {
+                #cfg
                 pub mod #module_name;
             }
         });
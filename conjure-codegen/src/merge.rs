@@ -0,0 +1,230 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merging of definitions from multiple IR files.
+
+use anyhow::{bail, Error};
+use conjure_object::Any;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::types::{ConjureDefinition, TypeDefinition, TypeName};
+
+/// Merges IR files into a single definition.
+///
+/// Definitions present in multiple files are deduplicated as long as they are identical, since IR files routinely
+/// include the types they import from other definitions.
+pub fn merge(defs: &[(&Path, ConjureDefinition)]) -> Result<ConjureDefinition, Error> {
+    let mut names = HashMap::new();
+
+    let types = merge_definitions(
+        &mut names,
+        defs,
+        "type",
+        ConjureDefinition::types,
+        TypeDefinition::type_name,
+    )?;
+    let errors = merge_definitions(
+        &mut names,
+        defs,
+        "error",
+        ConjureDefinition::errors,
+        |def| def.error_name(),
+    )?;
+    let services = merge_definitions(
+        &mut names,
+        defs,
+        "service",
+        ConjureDefinition::services,
+        |def| def.service_name(),
+    )?;
+
+    let mut extensions = BTreeMap::new();
+    for (path, def) in defs {
+        for (key, value) in def.extensions() {
+            merge_extension(&mut extensions, path, key, value)?;
+        }
+    }
+
+    Ok(ConjureDefinition::builder()
+        .version(1)
+        .errors(errors)
+        .types(types)
+        .services(services)
+        .extensions(extensions)
+        .build())
+}
+
+fn merge_definitions<'a, T, F, G>(
+    names: &mut HashMap<&'a TypeName, &'a Path>,
+    defs: &'a [(&'a Path, ConjureDefinition)],
+    kind: &str,
+    items: F,
+    name: G,
+) -> Result<Vec<T>, Error>
+where
+    T: 'a + Clone + PartialEq,
+    F: Fn(&'a ConjureDefinition) -> &'a [T],
+    G: Fn(&'a T) -> &'a TypeName,
+{
+    let mut merged = HashMap::new();
+    let mut out = vec![];
+
+    for (path, def) in defs {
+        for item in items(def) {
+            let type_name = name(item);
+
+            if let Some(&existing) = merged.get(type_name) {
+                if existing == item {
+                    continue;
+                }
+            } else if !names.contains_key(type_name) {
+                names.insert(type_name, path);
+                merged.insert(type_name, item);
+                out.push(item.clone());
+                continue;
+            }
+
+            bail!(
+                "conflicting definitions of {kind} {}.{} in {} and {}",
+                type_name.package(),
+                type_name.name(),
+                names[type_name].display(),
+                path.display(),
+            );
+        }
+    }
+
+    Ok(out)
+}
+
+// Lists (e.g. `recommended-product-dependencies`) are concatenated, and anything else must be identical.
+fn merge_extension(
+    extensions: &mut BTreeMap<String, Any>,
+    path: &Path,
+    key: &str,
+    value: &Any,
+) -> Result<(), Error> {
+    let mut entry = match extensions.entry(key.to_string()) {
+        Entry::Vacant(entry) => {
+            entry.insert(value.clone());
+            return Ok(());
+        }
+        Entry::Occupied(entry) => entry,
+    };
+
+    if entry.get() == value {
+        return Ok(());
+    }
+
+    let existing = entry.get().clone().deserialize_into::<Vec<Any>>();
+    let new = value.clone().deserialize_into::<Vec<Any>>();
+    match (existing, new) {
+        (Ok(mut existing), Ok(new)) => {
+            for value in new {
+                if !existing.contains(&value) {
+                    existing.push(value);
+                }
+            }
+            *entry.get_mut() = Any::new(existing)?;
+            Ok(())
+        }
+        _ => bail!(
+            "conflicting values for extension {key} in {}",
+            path.display()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixtures::{alias, primitive};
+    use crate::types::PrimitiveType;
+
+    fn ir(types: Vec<TypeDefinition>) -> ConjureDefinition {
+        ConjureDefinition::builder().version(1).types(types).build()
+    }
+
+    #[test]
+    fn merge_identical() {
+        let defs = [
+            (
+                Path::new("a.json"),
+                ir(vec![
+                    alias(
+                        "com.palantir.test",
+                        "Shared",
+                        primitive(PrimitiveType::String),
+                    ),
+                    alias("com.palantir.test", "A", primitive(PrimitiveType::Integer)),
+                ]),
+            ),
+            (
+                Path::new("b.json"),
+                ir(vec![
+                    alias(
+                        "com.palantir.test",
+                        "Shared",
+                        primitive(PrimitiveType::String),
+                    ),
+                    alias("com.palantir.test", "B", primitive(PrimitiveType::Integer)),
+                ]),
+            ),
+        ];
+
+        let merged = merge(&defs).unwrap();
+        assert_eq!(
+            merged.types(),
+            [
+                alias(
+                    "com.palantir.test",
+                    "Shared",
+                    primitive(PrimitiveType::String)
+                ),
+                alias("com.palantir.test", "A", primitive(PrimitiveType::Integer)),
+                alias("com.palantir.test", "B", primitive(PrimitiveType::Integer)),
+            ],
+        );
+    }
+
+    #[test]
+    fn merge_conflicting() {
+        let defs = [
+            (
+                Path::new("a.json"),
+                ir(vec![alias(
+                    "com.palantir.test",
+                    "Shared",
+                    primitive(PrimitiveType::String),
+                )]),
+            ),
+            (
+                Path::new("b.json"),
+                ir(vec![alias(
+                    "com.palantir.test",
+                    "Shared",
+                    primitive(PrimitiveType::Integer),
+                )]),
+            ),
+        ];
+
+        let error = merge(&defs).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "conflicting definitions of type com.palantir.test.Shared in a.json and b.json",
+        );
+    }
+}
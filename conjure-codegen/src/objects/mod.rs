@@ -19,11 +19,13 @@ use quote::quote;
 mod deserialize;
 mod object;
 mod serialize;
+mod validate;
 
 pub fn generate(ctx: &Context, def: &ObjectDefinition) -> TokenStream {
     let object = object::generate(ctx, def);
     let serialize = serialize::generate(ctx, def);
     let deserialize = deserialize::generate(ctx, def);
+    let validate = validate::generate(ctx, def);
 
     quote! {
         use conjure_object::serde::{ser, de};
@@ -33,6 +35,7 @@ pub fn generate(ctx: &Context, def: &ObjectDefinition) -> TokenStream {
         #object
         #serialize
         #deserialize
+        #validate
     }
 }
 
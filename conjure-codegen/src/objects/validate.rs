@@ -0,0 +1,61 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::constraints;
+use crate::context::Context;
+use crate::types::ObjectDefinition;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+pub fn generate(ctx: &Context, def: &ObjectDefinition) -> TokenStream {
+    if !ctx.validation() {
+        return quote!();
+    }
+
+    let name = ctx.type_name(def.type_name().name());
+    let result = ctx.result_ident(def.type_name());
+    let ok = ctx.ok_ident(def.type_name());
+
+    let mut checks = vec![];
+    for field in def.fields() {
+        let field_name = ctx.field_name(field.field_name());
+        let key = &field.field_name().0;
+
+        if ctx.needs_validation(field.type_()) {
+            checks.push(quote! {
+                conjure_object::Validate::validate(&self.#field_name)
+                    .map_err(|e| e.with_field(#key))?;
+            });
+        }
+
+        if let Some(constraint) = ctx.field_constraint(def.type_name(), key) {
+            checks.push(constraints::generate_checks(
+                ctx,
+                def.type_name(),
+                quote!(&self.#field_name),
+                field.type_(),
+                constraint,
+                Some(key),
+            ));
+        }
+    }
+
+    quote! {
+        impl conjure_object::Validate for #name {
+            fn validate(&self) -> #result<(), conjure_object::ValidationError> {
+                #(#checks)*
+                #ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Writing of generated files to the output directory.
+//!
+//! Files are only written if their contents have changed, so their modification times are preserved across
+//! regenerations. The paths of all generated files are recorded in a manifest so files which are no longer generated
+//! can be removed.
+
+use anyhow::{bail, Context as _, Error};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::{fmt, fs};
+
+const MANIFEST: &str = ".conjure-manifest";
+
+/// The generated files, keyed by their path relative to the output directory.
+pub struct Output {
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl Output {
+    pub fn new() -> Output {
+        Output {
+            files: BTreeMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, path: PathBuf, contents: String) {
+        self.files.insert(path, contents);
+    }
+
+    /// Updates the output directory to match the generated files.
+    pub fn write(&self, dir: &Path) -> Result<(), Error> {
+        for change in self.changes(dir)? {
+            let path = dir.join(change.path());
+            match change {
+                Change::Added(_, contents) | Change::Modified(_, contents) => {
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).with_context(|| {
+                            format!("error creating directory {}", parent.display())
+                        })?;
+                    }
+                    fs::write(&path, contents.as_bytes())
+                        .with_context(|| format!("error writing file {}", path.display()))?;
+                }
+                Change::Removed(relative) => {
+                    fs::remove_file(&path)
+                        .with_context(|| format!("error removing file {}", path.display()))?;
+                    remove_empty_parents(dir, &relative);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error describing the differences between the output directory and the generated files, if any.
+    pub fn check(&self, dir: &Path) -> Result<(), Error> {
+        let changes = self.changes(dir)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!("generated code in {} is out of date:", dir.display());
+        for change in &changes {
+            write!(message, "\n    {change}").unwrap();
+        }
+        bail!(message)
+    }
+
+    fn changes(&self, dir: &Path) -> Result<Vec<Change<'_>>, Error> {
+        let manifest = (Path::new(MANIFEST), Cow::Owned(self.manifest()));
+        let files = self
+            .files
+            .iter()
+            .map(|(path, contents)| (&**path, Cow::Borrowed(&**contents)))
+            .chain([manifest]);
+
+        let mut changes = vec![];
+
+        for path in self.previous_files(dir)? {
+            if !self.files.contains_key(&path) && dir.join(&path).exists() {
+                changes.push(Change::Removed(path));
+            }
+        }
+
+        // the manifest comes last so it's only updated once everything else has been
+        for (path, contents) in files {
+            let file = dir.join(path);
+            match fs::read_to_string(&file) {
+                Ok(existing) if existing == contents => {}
+                Ok(_) => changes.push(Change::Modified(path.to_path_buf(), contents)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                    changes.push(Change::Added(path.to_path_buf(), contents))
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("error reading file {}", file.display()))
+                }
+            }
+        }
+
+        Ok(changes)
+    }
+
+    fn manifest(&self) -> String {
+        let mut manifest = String::new();
+        for path in self.files.keys() {
+            let components = path
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>();
+            writeln!(manifest, "{}", components.join("/")).unwrap();
+        }
+        manifest
+    }
+
+    fn previous_files(&self, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let file = dir.join(MANIFEST);
+        let manifest = match fs::read_to_string(&file) {
+            Ok(manifest) => manifest,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => {
+                return Err(e).with_context(|| format!("error reading file {}", file.display()))
+            }
+        };
+
+        let mut files = vec![];
+        for line in manifest.lines() {
+            let path = PathBuf::from(line);
+            // never touch anything outside of the output directory
+            if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+                bail!("invalid path `{line}` in {}", file.display());
+            }
+            files.push(path);
+        }
+
+        Ok(files)
+    }
+}
+
+enum Change<'a> {
+    Added(PathBuf, Cow<'a, str>),
+    Modified(PathBuf, Cow<'a, str>),
+    Removed(PathBuf),
+}
+
+impl Change<'_> {
+    fn path(&self) -> &Path {
+        match self {
+            Change::Added(path, _) | Change::Modified(path, _) | Change::Removed(path) => path,
+        }
+    }
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            Change::Added(..) => "missing",
+            Change::Modified(..) => "modified",
+            Change::Removed(_) => "stale",
+        };
+        write!(fmt, "{kind}: {}", self.path().display())
+    }
+}
+
+// Removes directories left empty by the removal of a file, stopping at the first which isn't empty.
+fn remove_empty_parents(dir: &Path, relative: &Path) {
+    let mut parent = relative.parent();
+    while let Some(path) = parent {
+        if path.as_os_str().is_empty() || fs::remove_dir(dir.join(path)).is_err() {
+            break;
+        }
+        parent = path.parent();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(files: &[(&str, &str)]) -> Output {
+        let mut output = Output::new();
+        for (path, contents) in files {
+            output.insert(PathBuf::from(path), contents.to_string());
+        }
+        output
+    }
+
+    #[test]
+    fn incremental() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        let first = output(&[("mod.rs", "a"), ("foo/mod.rs", "b"), ("foo/bar.rs", "c")]);
+        first.check(dir).unwrap_err();
+        first.write(dir).unwrap();
+        first.check(dir).unwrap();
+
+        let unchanged = fs::metadata(dir.join("mod.rs"))
+            .unwrap()
+            .modified()
+            .unwrap();
+
+        let second = output(&[("mod.rs", "a"), ("baz.rs", "d")]);
+        let error = second.check(dir).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "generated code in {} is out of date:
+    stale: {}
+    stale: {}
+    missing: baz.rs
+    modified: .conjure-manifest",
+                dir.display(),
+                Path::new("foo/bar.rs").display(),
+                Path::new("foo/mod.rs").display(),
+            ),
+        );
+
+        second.write(dir).unwrap();
+        second.check(dir).unwrap();
+        assert!(!dir.join("foo").exists());
+        assert_eq!(fs::read_to_string(dir.join("baz.rs")).unwrap(), "d");
+        assert_eq!(
+            fs::metadata(dir.join("mod.rs"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            unchanged,
+        );
+    }
+}
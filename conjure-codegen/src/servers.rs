@@ -1,3 +1,4 @@
+use crate::constraints;
 use crate::context::Context;
 use crate::human_size;
 use crate::types::{
@@ -17,14 +18,83 @@ pub fn generate(ctx: &Context, def: &ServiceDefinition) -> TokenStream {
     let sync_trait = generate_trait(ctx, def, Style::Sync);
     let async_trait = generate_trait(ctx, def, Style::Async);
 
+    let constraints = def.endpoints().iter().flat_map(|endpoint| {
+        endpoint
+            .args()
+            .iter()
+            .filter_map(move |arg| generate_constraint(ctx, def, endpoint, arg))
+    });
+
     quote! {
         use conjure_http::endpoint;
 
         #sync_trait
         #async_trait
+
+        #(#constraints)*
     }
 }
 
+// Arguments with constrained markers are checked by a type implementing `conjure_http::server::Constraint`.
+fn generate_constraint(
+    ctx: &Context,
+    def: &ServiceDefinition,
+    endpoint: &EndpointDefinition,
+    arg: &ArgumentDefinition,
+) -> Option<TokenStream> {
+    if !is_constrained(ctx, arg) {
+        return None;
+    }
+
+    let constraints = constraints::marker_constraints(ctx, arg);
+    let name = constraint_type(def, endpoint, arg);
+    let ty = ctx.rust_type(def.service_name(), arg.type_());
+    let result = ctx.result_ident(def.service_name());
+    let ok = ctx.ok_ident(def.service_name());
+    let checks = constraints.iter().map(|constraint| {
+        constraints::generate_checks(
+            ctx,
+            def.service_name(),
+            quote!(value),
+            arg.type_(),
+            constraint,
+            None,
+        )
+    });
+
+    Some(quote! {
+        enum #name {}
+
+        impl conjure_http::server::Constraint<#ty> for #name {
+            fn check(value: &#ty) -> #result<(), conjure_object::ValidationError> {
+                #(#checks)*
+                #ok(())
+            }
+        }
+    })
+}
+
+fn is_constrained(ctx: &Context, arg: &ArgumentDefinition) -> bool {
+    ctx.validation()
+        && !ctx.is_binary(arg.type_())
+        && !constraints::marker_constraints(ctx, arg).is_empty()
+}
+
+fn constraint_type(
+    def: &ServiceDefinition,
+    endpoint: &EndpointDefinition,
+    arg: &ArgumentDefinition,
+) -> TokenStream {
+    format!(
+        "{}{}{}Constraint",
+        def.service_name().name(),
+        endpoint.endpoint_name().to_upper_camel_case(),
+        arg.arg_name().to_upper_camel_case(),
+    )
+    .parse()
+    .unwrap()
+}
+
 fn generate_trait(ctx: &Context, def: &ServiceDefinition, style: Style) -> TokenStream {
     let docs = ctx.docs(def.docs());
     let service_name = def.service_name().name();
@@ -167,6 +237,8 @@ fn generate_trait_endpoint(
         }
         None => quote!(),
     };
+    let tags = tags(endpoint);
+    let markers = markers(endpoint);
 
     let auth_arg = auth_arg(endpoint);
     let args = endpoint.args().iter().map(|a| arg(ctx, def, endpoint, a));
@@ -180,7 +252,7 @@ fn generate_trait_endpoint(
     // ignore deprecation since the endpoint has to be implemented regardless
     quote! {
         #docs
-        #[endpoint(method = #method, path = #path, name = #endpoint_name #produces)]
+        #[endpoint(method = #method, path = #path, name = #endpoint_name #produces #tags #markers)]
         #async_ fn #name(&self #auth_arg #(, #args)* #request_context_arg) -> #ret_ty;
     }
 }
@@ -198,6 +270,36 @@ fn produces(ctx: &Context, ty: &Type) -> TokenStream {
     }
 }
 
+fn tags(endpoint: &EndpointDefinition) -> TokenStream {
+    if endpoint.tags().is_empty() {
+        return quote!();
+    }
+
+    let tags = endpoint.tags().iter();
+    quote!(, tags = [#(#tags),*])
+}
+
+fn markers(endpoint: &EndpointDefinition) -> TokenStream {
+    let markers = endpoint
+        .markers()
+        .iter()
+        .filter_map(|marker| {
+            let name = match marker {
+                Type::Reference(name) => name,
+                Type::External(def) => def.external_reference(),
+                _ => return None,
+            };
+            Some(name.qualified_name())
+        })
+        .collect::<Vec<_>>();
+
+    if markers.is_empty() {
+        return quote!();
+    }
+
+    quote!(, markers = [#(#markers),*])
+}
+
 fn auth_arg(endpoint: &EndpointDefinition) -> TokenStream {
     match endpoint.auth() {
         Some(auth) => {
@@ -235,6 +337,8 @@ fn arg(
         quote!()
     };
 
+    let constraint = is_constrained(ctx, arg).then(|| constraint_type(def, endpoint, arg));
+
     let attr = match arg.param_type() {
         ParameterType::Body(_) => {
             let deserializer = if ctx.is_optional(arg.type_()).is_some() {
@@ -257,6 +361,20 @@ fn arg(
                 };
                 quote!(conjure_http::server::StdRequestDeserializer #param)
             };
+            let deserializer = if ctx.validation()
+                && ctx.needs_validation(arg.type_())
+                && !ctx.is_binary(arg.type_())
+            {
+                quote!(conjure_http::server::ValidatingRequestDeserializer<#deserializer>)
+            } else {
+                deserializer
+            };
+            let deserializer = match constraint {
+                Some(constraint) => quote! {
+                    conjure_http::server::ConstrainedRequestDeserializer<#deserializer, #constraint>
+                },
+                None => deserializer,
+            };
             quote!(#[body(deserializer = #deserializer #log_as #safe)])
         }
         ParameterType::Header(header) => {
@@ -266,14 +384,19 @@ fn arg(
             } else {
                 quote!(conjure_http::server::conjure::FromPlainDecoder)
             };
+            let decoder = constrained_decoder(decoder, constraint);
             quote!(#[header(name = #name, decoder = #decoder #log_as #safe)])
         }
         ParameterType::Path(_) => {
             let name = &**arg.arg_name();
+            let decoder = constrained_decoder(
+                quote!(conjure_http::server::conjure::FromPlainDecoder),
+                constraint,
+            );
             quote! {
                 #[path(
                     name = #name,
-                    decoder = conjure_http::server::conjure::FromPlainDecoder
+                    decoder = #decoder
                     #log_as
                     #safe
                 )]
@@ -288,6 +411,7 @@ fn arg(
             } else {
                 quote!(conjure_http::server::conjure::FromPlainDecoder)
             };
+            let decoder = constrained_decoder(decoder, constraint);
             quote!(#[query(name = #name, decoder = #decoder #log_as #safe)])
         }
     };
@@ -300,6 +424,13 @@ fn arg(
     quote!(#attr #name: #ty)
 }
 
+fn constrained_decoder(decoder: TokenStream, constraint: Option<TokenStream>) -> TokenStream {
+    match constraint {
+        Some(constraint) => quote!(conjure_http::server::ConstrainedDecoder<#decoder, #constraint>),
+        None => decoder,
+    }
+}
+
 fn optional_decoder(ctx: &Context, def: &ServiceDefinition, ty: &Type) -> TokenStream {
     let mut decoder = quote!(conjure_http::server::conjure::FromPlainOptionDecoder);
     let dealiased = ctx.dealiased_type(ty);
@@ -0,0 +1,34 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::types::{TypeDefinition, TypeName};
+
+impl TypeDefinition {
+    /// Returns the name of the defined type.
+    pub(crate) fn type_name(&self) -> &TypeName {
+        match self {
+            TypeDefinition::Alias(def) => def.type_name(),
+            TypeDefinition::Enum(def) => def.type_name(),
+            TypeDefinition::Object(def) => def.type_name(),
+            TypeDefinition::Union(def) => def.type_name(),
+        }
+    }
+}
+
+impl TypeName {
+    /// Returns the package-qualified name, e.g. `com.palantir.product.Foo`.
+    pub(crate) fn qualified_name(&self) -> String {
+        format!("{}.{}", self.package(), self.name())
+    }
+}
@@ -24,6 +24,7 @@ pub fn generate(ctx: &Context, def: &UnionDefinition) -> TokenStream {
     let deserialize = generate_deserialize(ctx, def);
     let variant = generate_variant(ctx, def);
     let unknown = generate_unknown(ctx, def);
+    let validate = generate_validate(ctx, def);
 
     quote! {
         use conjure_object::serde::{ser, de};
@@ -36,6 +37,7 @@ pub fn generate(ctx: &Context, def: &UnionDefinition) -> TokenStream {
         #deserialize
         #variant
         #unknown
+        #validate
     }
 }
 
@@ -487,3 +489,56 @@ fn generate_unknown(ctx: &Context, def: &UnionDefinition) -> TokenStream {
         }
     }
 }
+
+fn generate_validate(ctx: &Context, def: &UnionDefinition) -> TokenStream {
+    if !ctx.validation() {
+        return quote!();
+    }
+
+    let name = ctx.type_name(def.type_name().name());
+    let result = ctx.result_ident(def.type_name());
+    let ok = ctx.ok_ident(def.type_name());
+    let err = ctx.err_ident(def.type_name());
+
+    let arms = def.union_().iter().map(|f| {
+        let variant = ctx.type_name(f.field_name());
+        let allow_deprecated = ctx.allow_deprecated(f.deprecated());
+        if ctx.needs_validation(f.type_()) {
+            let key = &f.field_name().0;
+            quote! {
+                #allow_deprecated
+                #name::#variant(ref value) => {
+                    conjure_object::Validate::validate(value).map_err(|e| e.with_field(#key))
+                }
+            }
+        } else {
+            quote! {
+                #allow_deprecated
+                #name::#variant(_) => #ok(()),
+            }
+        }
+    });
+
+    let unknown_arm = if ctx.exhaustive() {
+        quote!()
+    } else {
+        let unknown = unknown(ctx, def);
+        quote! {
+            #name::#unknown(ref value) => #err(conjure_object::ValidationError::new(format!(
+                "unknown variant `{}`",
+                value.type_(),
+            ))),
+        }
+    };
+
+    quote! {
+        impl conjure_object::Validate for #name {
+            fn validate(&self) -> #result<(), conjure_object::ValidationError> {
+                match *self {
+                    #(#arms)*
+                    #unknown_arm
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of definitions before code generation.
+
+use anyhow::{bail, Error};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write;
+
+use crate::constraints::{self, Constraint};
+use crate::context::Context;
+use crate::http_paths::{self, PathSegment};
+use crate::types::{
+    ConjureDefinition, EndpointDefinition, FieldDefinition, ParameterType, ServiceDefinition, Type,
+    TypeDefinition, TypeName,
+};
+
+/// Checks definitions for problems which would otherwise cause a panic or invalid generated code.
+///
+/// All problems are reported together in the returned error.
+pub fn validate(ctx: &Context, defs: &ConjureDefinition) -> Result<(), Error> {
+    let mut validator = Validator {
+        ctx,
+        types: defs.types().iter().map(TypeDefinition::type_name).collect(),
+        problems: vec![],
+    };
+
+    for def in defs.types() {
+        validator.validate_type(def);
+    }
+
+    for def in defs.errors() {
+        let location = format!("error {}", def.error_name().qualified_name());
+        validator.validate_fields(
+            &location,
+            def.safe_args().iter().chain(def.unsafe_args()),
+            |f| ctx.field_name(f).to_string(),
+        );
+    }
+
+    for def in defs.services() {
+        validator.validate_service(def);
+    }
+
+    for (name, constraint) in ctx.constraints() {
+        validator.validate_constraint(defs, name, constraint);
+    }
+
+    if validator.problems.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = format!(
+        "found {} problem(s) in Conjure IR:",
+        validator.problems.len()
+    );
+    for problem in &validator.problems {
+        write!(message, "\n    {problem}").unwrap();
+    }
+    bail!(message)
+}
+
+struct Validator<'a> {
+    ctx: &'a Context,
+    types: HashSet<&'a TypeName>,
+    problems: Vec<String>,
+}
+
+impl Validator<'_> {
+    fn validate_type(&mut self, def: &TypeDefinition) {
+        let location = format!("type {}", def.type_name().qualified_name());
+
+        match def {
+            TypeDefinition::Alias(def) => self.validate_references(&location, def.alias()),
+            TypeDefinition::Enum(def) => {
+                let mut variants = HashMap::new();
+                for value in def.values() {
+                    let value = value.value();
+                    if !conjure_object::private::valid_enum_variant(value) {
+                        self.problems
+                            .push(format!("{location}: invalid enum value `{value}`"));
+                    }
+
+                    let variant = self.ctx.type_name(value).to_string();
+                    if let Some(existing) = variants.insert(variant.clone(), value) {
+                        self.problems.push(format!(
+                            "{location}: enum values `{existing}` and `{value}` both map to `{variant}`"
+                        ));
+                    }
+                }
+            }
+            TypeDefinition::Object(def) => {
+                let ctx = self.ctx;
+                self.validate_fields(&location, def.fields(), |f| ctx.field_name(f).to_string());
+            }
+            TypeDefinition::Union(def) => {
+                let ctx = self.ctx;
+                self.validate_fields(&location, def.union_(), |f| ctx.type_name(f).to_string());
+            }
+        }
+    }
+
+    fn validate_fields<'b, I, F>(&mut self, location: &str, fields: I, rust_name: F)
+    where
+        I: IntoIterator<Item = &'b FieldDefinition>,
+        F: Fn(&str) -> String,
+    {
+        let mut names = HashMap::new();
+        for field in fields {
+            let field_name = &***field.field_name();
+            self.validate_references(&format!("{location} field `{field_name}`"), field.type_());
+
+            let rust_name = rust_name(field_name);
+            if let Some(existing) = names.insert(rust_name.clone(), field_name) {
+                self.problems.push(format!(
+                    "{location}: fields `{existing}` and `{field_name}` both map to `{rust_name}`"
+                ));
+            }
+        }
+    }
+
+    fn validate_service(&mut self, def: &ServiceDefinition) {
+        let location = format!("service {}", def.service_name().qualified_name());
+
+        let mut names = HashMap::new();
+        for endpoint in def.endpoints() {
+            let endpoint_name = &***endpoint.endpoint_name();
+            let rust_name = self.ctx.field_name(endpoint_name).to_string();
+            if let Some(existing) = names.insert(rust_name.clone(), endpoint_name) {
+                self.problems.push(format!(
+                    "{location}: endpoints `{existing}` and `{endpoint_name}` both map to `{rust_name}`"
+                ));
+            }
+
+            self.validate_endpoint(&format!("{location} endpoint `{endpoint_name}`"), endpoint);
+        }
+    }
+
+    fn validate_endpoint(&mut self, location: &str, endpoint: &EndpointDefinition) {
+        let mut names = HashMap::new();
+        for arg in endpoint.args() {
+            let arg_name = &***arg.arg_name();
+            self.validate_references(&format!("{location} argument `{arg_name}`"), arg.type_());
+
+            let rust_name = self.ctx.field_name(arg_name).to_string();
+            if let Some(existing) = names.insert(rust_name.clone(), arg_name) {
+                self.problems.push(format!(
+                    "{location}: arguments `{existing}` and `{arg_name}` both map to `{rust_name}`"
+                ));
+            }
+        }
+
+        if let Some(returns) = endpoint.returns() {
+            self.validate_references(&format!("{location} return type"), returns);
+        }
+
+        let path_args = endpoint
+            .args()
+            .iter()
+            .filter(|arg| matches!(arg.param_type(), ParameterType::Path(_)))
+            .map(|arg| &***arg.arg_name())
+            .collect::<BTreeSet<_>>();
+
+        let mut path_params = BTreeSet::new();
+        for segment in http_paths::parse(endpoint.http_path()) {
+            if let PathSegment::Parameter { name, .. } = segment {
+                if !path_params.insert(name) {
+                    self.problems.push(format!(
+                        "{location}: path parameter `{name}` appears multiple times"
+                    ));
+                }
+            }
+        }
+
+        for name in path_params.difference(&path_args) {
+            self.problems.push(format!(
+                "{location}: path parameter `{name}` has no corresponding path argument"
+            ));
+        }
+        for name in path_args.difference(&path_params) {
+            self.problems.push(format!(
+                "{location}: path argument `{name}` does not appear in the path template"
+            ));
+        }
+    }
+
+    fn validate_constraint(
+        &mut self,
+        defs: &ConjureDefinition,
+        name: &str,
+        constraint: &Constraint,
+    ) {
+        let location = format!("constraint {name}");
+
+        let target = defs.types().iter().find_map(|def| {
+            let type_name = def.type_name();
+            if type_name.qualified_name() == name {
+                return match def {
+                    TypeDefinition::Alias(def) => Some(Ok(def.alias())),
+                    _ => Some(Err(
+                        "constraints can only be applied to aliases, object fields, and argument markers",
+                    )),
+                };
+            }
+
+            let field = name
+                .strip_prefix(&type_name.qualified_name())?
+                .strip_prefix('.')?;
+            match def {
+                TypeDefinition::Object(def) => def
+                    .fields()
+                    .iter()
+                    .find(|f| &***f.field_name() == field)
+                    .map(|f| Ok(f.type_())),
+                _ => None,
+            }
+        });
+
+        let marked_args = defs
+            .services()
+            .iter()
+            .flat_map(|service| {
+                service.endpoints().iter().flat_map(move |endpoint| {
+                    endpoint
+                        .args()
+                        .iter()
+                        .map(move |arg| (service, endpoint, arg))
+                })
+            })
+            .filter(|(_, _, arg)| {
+                arg.markers()
+                    .iter()
+                    .filter_map(constraints::marker_name)
+                    .any(|marker| marker.qualified_name() == name)
+            })
+            .collect::<Vec<_>>();
+
+        match target {
+            Some(Ok(def)) => {
+                for problem in constraints::constraint_problems(self.ctx, def, constraint) {
+                    self.problems.push(format!("{location}: {problem}"));
+                }
+            }
+            Some(Err(_)) | None if !marked_args.is_empty() => {
+                for (service, endpoint, arg) in marked_args {
+                    let arg_location = format!(
+                        "service {} endpoint `{}` argument `{}`",
+                        service.service_name().qualified_name(),
+                        &***endpoint.endpoint_name(),
+                        &***arg.arg_name(),
+                    );
+                    for problem in
+                        constraints::constraint_problems(self.ctx, arg.type_(), constraint)
+                    {
+                        self.problems
+                            .push(format!("{location}: {arg_location}: {problem}"));
+                    }
+                }
+            }
+            Some(Err(problem)) => self.problems.push(format!("{location}: {problem}")),
+            None => self.problems.push(format!(
+                "{location}: no such alias, object field, or argument marker"
+            )),
+        }
+    }
+
+    fn validate_references(&mut self, location: &str, def: &Type) {
+        match def {
+            Type::Primitive(_) => {}
+            Type::Optional(def) => self.validate_references(location, def.item_type()),
+            Type::List(def) => self.validate_references(location, def.item_type()),
+            Type::Set(def) => self.validate_references(location, def.item_type()),
+            Type::Map(def) => {
+                self.validate_references(location, def.key_type());
+                self.validate_references(location, def.value_type());
+            }
+            Type::Reference(name) => {
+                if !self.types.contains(name) {
+                    self.problems.push(format!(
+                        "{location}: reference to undefined type {}",
+                        name.qualified_name()
+                    ));
+                }
+            }
+            Type::External(def) => self.validate_references(location, def.fallback()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::context::ExternPaths;
+    use crate::fixtures::{alias, primitive, reference};
+    use crate::types::{
+        ArgumentDefinition, ArgumentName, EndpointDefinition, EndpointName, EnumDefinition,
+        EnumValueDefinition, FieldName, HttpMethod, HttpPath, ObjectDefinition, ParameterId,
+        PathParameterType, PrimitiveType, QueryParameterType,
+    };
+    use conjure_object::Any;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn collects_all_problems() {
+        let string = Type::Primitive(PrimitiveType::String);
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                TypeDefinition::Object(
+                    ObjectDefinition::builder()
+                        .type_name(TypeName::new("Object", "com.palantir.test"))
+                        .fields([
+                            FieldDefinition::new(FieldName("fooBar".to_string()), string.clone()),
+                            FieldDefinition::new(FieldName("foo_bar".to_string()), string.clone()),
+                            FieldDefinition::new(
+                                FieldName("missing".to_string()),
+                                Type::Reference(TypeName::new("Missing", "com.palantir.test")),
+                            ),
+                        ])
+                        .build(),
+                ),
+                TypeDefinition::Enum(
+                    EnumDefinition::builder()
+                        .type_name(TypeName::new("Enum", "com.palantir.test"))
+                        .values([EnumValueDefinition::new("lower")])
+                        .build(),
+                ),
+            ])
+            .services([ServiceDefinition::builder()
+                .service_name(TypeName::new("Service", "com.palantir.test"))
+                .endpoints([EndpointDefinition::builder()
+                    .endpoint_name(EndpointName("get".to_string()))
+                    .http_method(HttpMethod::Get)
+                    .http_path(HttpPath("/get/{id}".to_string()))
+                    .args([ArgumentDefinition::new(
+                        ArgumentName("name".to_string()),
+                        string,
+                        ParameterType::Path(PathParameterType::new()),
+                    )])
+                    .build()])
+                .build()])
+            .build();
+        let ctx = Context::new(
+            &defs,
+            false,
+            None,
+            None,
+            HashMap::new(),
+            ExternPaths::default(),
+            None,
+        );
+
+        let error = validate(&ctx, &defs).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "found 5 problem(s) in Conjure IR:
+    type com.palantir.test.Object: fields `fooBar` and `foo_bar` both map to `foo_bar`
+    type com.palantir.test.Object field `missing`: reference to undefined type com.palantir.test.Missing
+    type com.palantir.test.Enum: invalid enum value `lower`
+    service com.palantir.test.Service endpoint `get`: path parameter `id` has no corresponding path argument
+    service com.palantir.test.Service endpoint `get`: path argument `name` does not appear in the path template",
+        );
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let constraints = BTreeMap::from([
+            ("com.palantir.test.Alias", BTreeMap::from([("minSize", 1)])),
+            (
+                "com.palantir.test.Object.foo",
+                BTreeMap::from([("min", 2), ("max", 1)]),
+            ),
+            ("com.palantir.test.Object.bar", BTreeMap::from([("min", 1)])),
+            ("com.palantir.test.Marker", BTreeMap::from([("minSize", 1)])),
+        ]);
+        let defs = ConjureDefinition::builder()
+            .version(1)
+            .types([
+                alias(
+                    "com.palantir.test",
+                    "Alias",
+                    primitive(PrimitiveType::String),
+                ),
+                TypeDefinition::Object(
+                    ObjectDefinition::builder()
+                        .type_name(TypeName::new("Object", "com.palantir.test"))
+                        .fields([FieldDefinition::new(
+                            FieldName("foo".to_string()),
+                            Type::Primitive(PrimitiveType::Integer),
+                        )])
+                        .build(),
+                ),
+            ])
+            .services([ServiceDefinition::builder()
+                .service_name(TypeName::new("Service", "com.palantir.test"))
+                .endpoints([EndpointDefinition::builder()
+                    .endpoint_name(EndpointName("get".to_string()))
+                    .http_method(HttpMethod::Get)
+                    .http_path(HttpPath("/get".to_string()))
+                    .args([ArgumentDefinition::builder()
+                        .arg_name(ArgumentName("name".to_string()))
+                        .type_(primitive(PrimitiveType::String))
+                        .param_type(ParameterType::Query(QueryParameterType::new(ParameterId(
+                            "name".to_string(),
+                        ))))
+                        .markers([reference("com.palantir.test", "Marker")])
+                        .build()])
+                    .build()])
+                .build()])
+            .extensions([("constraints".to_string(), Any::new(constraints).unwrap())])
+            .build();
+        let constraints = constraints::parse_constraints(&defs).unwrap();
+        let ctx = Context::new(
+            &defs,
+            false,
+            None,
+            None,
+            HashMap::new(),
+            ExternPaths::default(),
+            Some(constraints),
+        );
+
+        let error = validate(&ctx, &defs).err().unwrap();
+        let mut problems = error
+            .to_string()
+            .lines()
+            .skip(1)
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        problems.sort();
+        assert_eq!(
+            problems,
+            [
+                "    constraint com.palantir.test.Alias: `minSize` does not apply to values of this type",
+                "    constraint com.palantir.test.Marker: service com.palantir.test.Service endpoint `get` argument `name`: `minSize` does not apply to values of this type",
+                "    constraint com.palantir.test.Object.bar: no such alias, object field, or argument marker",
+                "    constraint com.palantir.test.Object.foo: `min` is greater than `max`",
+            ],
+        );
+    }
+}
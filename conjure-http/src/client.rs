@@ -14,20 +14,23 @@
 
 //! The Conjure HTTP client API.
 
-use crate::private::{self, APPLICATION_JSON};
-use bytes::Bytes;
-use conjure_error::Error;
-use conjure_serde::json;
+use crate::private::{self, APPLICATION_JSON, APPLICATION_SMILE, APPLICATION_SMILE_OR_JSON};
+use bytes::{Buf, Bytes};
+use conjure_error::{Error, ErrorKind, ErrorType, SerializableError};
+use conjure_serde::{json, smile};
 use futures_core::Stream;
-use http::header::CONTENT_TYPE;
-use http::{HeaderValue, Request, Response};
+use http::header::RETRY_AFTER;
+use http::{HeaderMap, HeaderValue, Request, Response, StatusCode};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::convert::TryFrom;
-use std::fmt::Display;
+use std::error;
+use std::fmt::{self, Display};
 use std::future::Future;
 use std::io::Write;
 use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
 
 #[allow(missing_docs)]
 #[deprecated(note = "renamed to RequestBody", since = "3.5.0")]
@@ -359,6 +362,39 @@ where
     }
 }
 
+/// A body serializer which encodes values as Smile rather than JSON.
+///
+/// Conjure servers accept Smile bodies in place of JSON, and it is typically more compact.
+pub enum SmileRequestSerializer {}
+
+impl<'a, T, W> SerializeRequest<'a, T, W> for SmileRequestSerializer
+where
+    T: Serialize,
+{
+    fn content_type(_: &T) -> HeaderValue {
+        APPLICATION_SMILE
+    }
+
+    fn serialize(value: T) -> Result<RequestBody<'a, W>, Error> {
+        let body = smile::to_vec(&value).map_err(Error::internal)?;
+        Ok(RequestBody::Fixed(body.into()))
+    }
+}
+
+impl<'a, T, W> AsyncSerializeRequest<'a, T, W> for SmileRequestSerializer
+where
+    T: Serialize,
+{
+    fn content_type(_: &T) -> HeaderValue {
+        APPLICATION_SMILE
+    }
+
+    fn serialize(value: T) -> Result<AsyncRequestBody<'a, W>, Error> {
+        let buf = smile::to_vec(&value).map_err(Error::internal)?;
+        Ok(AsyncRequestBody::Fixed(Bytes::from(buf)))
+    }
+}
+
 /// A trait implemented by response deserializers used by custom Conjure client trait
 /// implementations.
 pub trait DeserializeResponse<T, R> {
@@ -406,6 +442,8 @@ where
 }
 
 /// A response deserializer which acts like a Conjure-generated client would.
+///
+/// It accepts both Smile and JSON responses.
 pub enum ConjureResponseDeserializer {}
 
 impl<T, R> DeserializeResponse<T, R> for ConjureResponseDeserializer
@@ -414,15 +452,13 @@ where
     R: Iterator<Item = Result<Bytes, Error>>,
 {
     fn accept() -> Option<HeaderValue> {
-        Some(APPLICATION_JSON)
+        Some(APPLICATION_SMILE_OR_JSON)
     }
 
     fn deserialize(response: Response<R>) -> Result<T, Error> {
-        if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-            return Err(Error::internal_safe("invalid response Content-Type"));
-        }
+        let encoding = private::response_encoding(response.headers())?;
         let buf = private::read_body(response.into_body(), None)?;
-        json::client_from_slice(&buf).map_err(Error::internal)
+        private::decode_serializable_body(encoding, &buf)
     }
 }
 
@@ -432,18 +468,293 @@ where
     R: Stream<Item = Result<Bytes, Error>> + Send,
 {
     fn accept() -> Option<HeaderValue> {
-        Some(APPLICATION_JSON)
+        Some(APPLICATION_SMILE_OR_JSON)
     }
 
     async fn deserialize(response: Response<R>) -> Result<T, Error> {
-        if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
+        let encoding = private::response_encoding(response.headers())?;
+        let buf = private::async_read_body(response.into_body(), None).await?;
+        private::decode_serializable_body(encoding, &buf)
+    }
+}
+
+/// A response deserializer which lazily deserializes the elements of a JSON array response.
+///
+/// Rather than buffering the entire response, it returns a [`ListResponse`] iterator for blocking clients or an
+/// [`AsyncListResponse`] stream for async clients, which read and deserialize one element at a time following the same
+/// semantics as [`ConjureResponseDeserializer`]. Only JSON responses are supported.
+///
+/// A `204 No Content` response is treated as an empty list.
+pub enum ListResponseDeserializer {}
+
+impl<T, R> DeserializeResponse<ListResponse<T, R>, R> for ListResponseDeserializer
+where
+    T: DeserializeOwned,
+    R: Iterator<Item = Result<Bytes, Error>>,
+{
+    fn accept() -> Option<HeaderValue> {
+        Some(APPLICATION_JSON)
+    }
+
+    fn deserialize(response: Response<R>) -> Result<ListResponse<T, R>, Error> {
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(ListResponse(None));
+        }
+
+        if private::response_encoding(response.headers())? != private::Encoding::Json {
             return Err(Error::internal_safe("invalid response Content-Type"));
         }
-        let buf = private::async_read_body(response.into_body(), None).await?;
-        json::client_from_slice(&buf).map_err(Error::internal)
+
+        let reader = private::BodyReader::new(response.into_body());
+        Ok(ListResponse(Some(json::client_array_from_reader(reader))))
     }
 }
 
+/// An iterator over the elements of a JSON array response.
+///
+/// Created by [`ListResponseDeserializer`]. Iteration stops after the first error.
+pub struct ListResponse<T, R>(Option<json::ClientArrayIter<private::BodyReader<R>, T>>);
+
+impl<T, R> Iterator for ListResponse<T, R>
+where
+    T: DeserializeOwned,
+    R: Iterator<Item = Result<Bytes, Error>>,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        let iter = self.0.as_mut()?;
+        let result = iter.next()?;
+        // errors reading the body are propagated directly rather than through serde_json
+        Some(result.map_err(|e| match iter.get_mut().take_error() {
+            Some(error) => error,
+            None => Error::internal(e),
+        }))
+    }
+}
+
+impl<T, R> AsyncDeserializeResponse<AsyncListResponse<T, R>, R> for ListResponseDeserializer
+where
+    T: DeserializeOwned,
+    R: Stream<Item = Result<Bytes, Error>> + Send,
+{
+    fn accept() -> Option<HeaderValue> {
+        Some(APPLICATION_JSON)
+    }
+
+    async fn deserialize(response: Response<R>) -> Result<AsyncListResponse<T, R>, Error> {
+        if response.status() == StatusCode::NO_CONTENT {
+            return Ok(AsyncListResponse(None));
+        }
+
+        if private::response_encoding(response.headers())? != private::Encoding::Json {
+            return Err(Error::internal_safe("invalid response Content-Type"));
+        }
+
+        Ok(AsyncListResponse(Some(AsyncListState {
+            body: Box::pin(response.into_body()),
+            cur: Bytes::new(),
+            eof: false,
+            decoder: json::ClientArrayDecoder::new(),
+        })))
+    }
+}
+
+/// A stream over the elements of a JSON array response.
+///
+/// Created by [`ListResponseDeserializer`]. The stream ends after the first error.
+pub struct AsyncListResponse<T, R>(Option<AsyncListState<T, R>>);
+
+struct AsyncListState<T, R> {
+    body: Pin<Box<R>>,
+    cur: Bytes,
+    eof: bool,
+    decoder: json::ClientArrayDecoder<T>,
+}
+
+impl<T, R> Stream for AsyncListResponse<T, R>
+where
+    T: DeserializeOwned,
+    R: Stream<Item = Result<Bytes, Error>>,
+{
+    type Item = Result<T, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Result<T, Error>>> {
+        let this = self.get_mut();
+        let Some(state) = &mut this.0 else {
+            return Poll::Ready(None);
+        };
+
+        loop {
+            // the decoder consumes the entire chunk unless it completes an element
+            let value = if !state.cur.is_empty() {
+                let (len, value) = state.decoder.decode(&state.cur);
+                state.cur.advance(len);
+                value
+            } else if state.eof {
+                state.decoder.finish()
+            } else {
+                None
+            };
+
+            match value {
+                Some(Ok(value)) => return Poll::Ready(Some(Ok(value))),
+                Some(Err(e)) => {
+                    this.0 = None;
+                    return Poll::Ready(Some(Err(Error::internal(e))));
+                }
+                None if state.eof => return Poll::Ready(None),
+                None => {}
+            }
+
+            match state.body.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) => state.cur = bytes,
+                Poll::Ready(Some(Err(e))) => {
+                    this.0 = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Ready(None) => state.eof = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// The cause of errors created by [`decode_error`] and [`async_decode_error`].
+#[derive(Debug)]
+pub struct RemoteError {
+    status: StatusCode,
+    error: Option<SerializableError>,
+}
+
+impl RemoteError {
+    /// Returns the status code of the response.
+    #[inline]
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the serialized Conjure error contained in the response body, if present.
+    #[inline]
+    pub fn error(&self) -> Option<&SerializableError> {
+        self.error.as_ref()
+    }
+}
+
+impl fmt::Display for RemoteError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "remote error: {}", self.status)?;
+        if let Some(error) = &self.error {
+            write!(fmt, " ({})", error.error_name())?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for RemoteError {}
+
+// Error bodies are only expected to contain a small JSON object, so larger bodies are not buffered.
+const ERROR_BODY_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// Converts a non-2xx response into an error.
+///
+/// `429 Too Many Requests` responses are converted into throttle errors, using the `Retry-After` header as the
+/// duration if present, and `503 Service Unavailable` responses are converted into unavailable errors. Only the
+/// delta-seconds form of `Retry-After` is supported; an HTTP-date value is ignored. Responses with a serialized Conjure
+/// error body of up to 1 MiB are converted into propagated service errors, and all others into internal errors. The
+/// cause of the returned error is a [`RemoteError`].
+pub fn decode_error<R>(response: Response<R>) -> Error
+where
+    R: Iterator<Item = Result<Bytes, Error>>,
+{
+    if let Some(error) = decode_qos_error(&response) {
+        return error;
+    }
+
+    let status = response.status();
+    let body = private::read_body(response.into_body(), Some(ERROR_BODY_SIZE_LIMIT));
+    decode_service_error(status, body)
+}
+
+/// Converts a non-2xx response into an error.
+///
+/// It behaves identically to [`decode_error`].
+pub async fn async_decode_error<R>(response: Response<R>) -> Error
+where
+    R: Stream<Item = Result<Bytes, Error>>,
+{
+    if let Some(error) = decode_qos_error(&response) {
+        return error;
+    }
+
+    let status = response.status();
+    let body = private::async_read_body(response.into_body(), Some(ERROR_BODY_SIZE_LIMIT)).await;
+    decode_service_error(status, body)
+}
+
+fn decode_qos_error<R>(response: &Response<R>) -> Option<Error> {
+    let status = response.status();
+    let cause = RemoteError {
+        status,
+        error: None,
+    };
+
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => match retry_after(response.headers()) {
+            Some(duration) => Some(Error::throttle_for_safe(cause, duration)),
+            None => Some(Error::throttle_safe(cause)),
+        },
+        StatusCode::SERVICE_UNAVAILABLE => Some(Error::unavailable_safe(cause)),
+        _ => None,
+    }
+}
+
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let secs = headers.get(RETRY_AFTER)?.to_str().ok()?.parse().ok()?;
+    Some(Duration::from_secs(secs))
+}
+
+fn decode_service_error(status: StatusCode, body: Result<Bytes, Error>) -> Error {
+    let error = body
+        .ok()
+        .and_then(|body| json::client_from_slice::<SerializableError>(&body).ok());
+
+    match error {
+        Some(error) => Error::propagated_service_safe(
+            RemoteError {
+                status,
+                error: Some(error.clone()),
+            },
+            error,
+        ),
+        None => Error::internal_safe(RemoteError {
+            status,
+            error: None,
+        }),
+    }
+}
+
+/// Attempts to convert a service error into a specific Conjure error type.
+///
+/// Returns `None` if the error is not a service error, its name does not match the type's, or its parameters can't be
+/// deserialized into the type. This is typically used with Conjure-generated error types to recover the parameters of
+/// errors propagated from remote services.
+pub fn downcast_error<T>(error: &Error) -> Option<T>
+where
+    T: ErrorType + DeserializeOwned,
+{
+    let ErrorKind::Service(error) = error.kind() else {
+        return None;
+    };
+
+    let value = private::error_params::from_params::<T>(error.parameters()).ok()?;
+    if value.name() != error.error_name() {
+        return None;
+    }
+
+    Some(value)
+}
+
 /// A trait implemented by header encoders used by custom Conjure client trait implementations.
 pub trait EncodeHeader<T> {
     /// Encodes the value into headers.
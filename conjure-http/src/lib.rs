@@ -21,6 +21,12 @@
 //!
 //! If the `macros` Cargo feature is enabled, the `conjure_client` macro can be used to create
 //! client implementations for non-Conjure APIs.
+//!
+//! # Features
+//!
+//! The `router` Cargo feature enables the server-side [`Router`](server::Router) and the in-memory
+//! clients in the `loopback` module. The `retry` Cargo feature enables the retrying client
+//! decorators in the `retry` module.
 #![warn(missing_docs, clippy::all)]
 // https://github.com/rust-lang/rust-clippy/issues/7752
 #![allow(
@@ -38,7 +44,12 @@ pub use crate::path_params::PathParams;
 pub use crate::safe_params::SafeParams;
 
 pub mod client;
+#[cfg(feature = "router")]
+pub mod loopback;
+pub mod middleware;
 pub mod path_params;
+#[cfg(feature = "retry")]
+pub mod retry;
 pub mod safe_params;
 pub mod server;
 
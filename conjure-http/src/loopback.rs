@@ -0,0 +1,284 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! In-memory clients which dispatch requests directly to server endpoints.
+//!
+//! These are primarily intended for tests, allowing Conjure clients to make end-to-end calls against server
+//! implementations without an HTTP transport.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use conjure_error::Error;
+use futures_core::Stream;
+use http::request::Parts;
+use http::{Extensions, Request, Response};
+
+use crate::client::{self, AsyncClient, AsyncRequestBody, Client, RequestBody};
+use crate::server::{
+    self, AsyncEndpoint, AsyncResponseBody, AsyncService, AsyncWriteBody, BoxAsyncEndpoint,
+    ConjureRuntime, EndpointMetadata, ResponseBody, Route, RouterError, Routes, Service,
+};
+
+/// A request or response body passed between a loopback client and server.
+///
+/// It implements both `Iterator` and `Stream`, yielding the entire body as a single chunk.
+pub struct LoopbackBody(Option<Bytes>);
+
+impl LoopbackBody {
+    fn new(bytes: Bytes) -> Self {
+        if bytes.is_empty() {
+            LoopbackBody(None)
+        } else {
+            LoopbackBody(Some(bytes))
+        }
+    }
+}
+
+impl Iterator for LoopbackBody {
+    type Item = Result<Bytes, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.take().map(Ok)
+    }
+}
+
+impl Stream for LoopbackBody {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Poll::Ready(self.0.take().map(Ok))
+    }
+}
+
+/// A blocking client which dispatches requests directly to the endpoints of a [`Service`].
+///
+/// Requests are dispatched to the endpoint identified by the service and name of their [`client::Endpoint`]
+/// extension, and must match that endpoint's method and path. Errors returned by the server are encoded with
+/// [`server::error_response`] and decoded with [`client::decode_error`], producing the same errors a remote client
+/// would. The response extensions populated by the endpoint are added to the extensions of successful responses.
+pub struct LoopbackClient {
+    endpoints: Endpoints<Box<dyn server::Endpoint<LoopbackBody, Vec<u8>> + Sync + Send>>,
+}
+
+impl LoopbackClient {
+    /// Creates a new client dispatching to the endpoints of the provided service.
+    ///
+    /// Returns an error if two endpoints have the same name, or if a path parameter's regex is invalid.
+    pub fn new<S>(service: S) -> Result<Self, RouterError>
+    where
+        S: Service<LoopbackBody, Vec<u8>>,
+    {
+        Ok(LoopbackClient {
+            endpoints: Endpoints::new(service.endpoints(&Arc::new(ConjureRuntime::new())))?,
+        })
+    }
+}
+
+impl Client for LoopbackClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = LoopbackBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            RequestBody::Empty => Bytes::new(),
+            RequestBody::Fixed(bytes) => bytes,
+            RequestBody::Streaming(mut writer) => {
+                let mut buf = vec![];
+                writer.write_body(&mut buf)?;
+                Bytes::from(buf)
+            }
+        };
+
+        let mut response_extensions = Extensions::new();
+        let response = match self.endpoints.route(&parts)? {
+            Route::Found {
+                endpoint,
+                path_params,
+            } => {
+                let mut request = server_request(&parts, body);
+                request.extensions_mut().insert(path_params);
+                endpoint.handle(request, &mut response_extensions)
+            }
+            Route::MethodNotAllowed { allowed } => {
+                Ok(server::method_not_allowed(&allowed, ResponseBody::Empty))
+            }
+            Route::NotFound => Err(server::not_found()),
+        };
+
+        let (parts, body) = response
+            .unwrap_or_else(|e| server::error_response(&e))
+            .into_parts();
+        let body = match body {
+            ResponseBody::Empty => Bytes::new(),
+            ResponseBody::Fixed(bytes) => bytes,
+            ResponseBody::Streaming(writer) => {
+                let mut buf = vec![];
+                writer.write_body(&mut buf)?;
+                Bytes::from(buf)
+            }
+        };
+
+        client_response(parts, body, response_extensions)
+    }
+}
+
+/// An async client which dispatches requests directly to the endpoints of an [`AsyncService`].
+///
+/// It follows the same behavior as [`LoopbackClient`].
+pub struct AsyncLoopbackClient {
+    endpoints: Endpoints<BoxAsyncEndpoint<'static, LoopbackBody, Vec<u8>>>,
+}
+
+impl AsyncLoopbackClient {
+    /// Creates a new client dispatching to the endpoints of the provided service.
+    ///
+    /// Returns an error if two endpoints have the same name, or if a path parameter's regex is invalid.
+    pub fn new<S>(service: S) -> Result<Self, RouterError>
+    where
+        S: AsyncService<LoopbackBody, Vec<u8>>,
+    {
+        Ok(AsyncLoopbackClient {
+            endpoints: Endpoints::new(service.endpoints(&Arc::new(ConjureRuntime::new())))?,
+        })
+    }
+}
+
+impl AsyncClient for AsyncLoopbackClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = LoopbackBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, body) = req.into_parts();
+        let body = match body {
+            AsyncRequestBody::Empty => Bytes::new(),
+            AsyncRequestBody::Fixed(bytes) => bytes,
+            AsyncRequestBody::Streaming(mut writer) => {
+                let mut buf = vec![];
+                client::AsyncWriteBody::write_body(Pin::new(&mut writer), Pin::new(&mut buf))
+                    .await?;
+                Bytes::from(buf)
+            }
+        };
+
+        let mut response_extensions = Extensions::new();
+        let response = match self.endpoints.route(&parts)? {
+            Route::Found {
+                endpoint,
+                path_params,
+            } => {
+                let mut request = server_request(&parts, body);
+                request.extensions_mut().insert(path_params);
+                endpoint.handle(request, &mut response_extensions).await
+            }
+            Route::MethodNotAllowed { allowed } => Ok(server::method_not_allowed(
+                &allowed,
+                AsyncResponseBody::Empty,
+            )),
+            Route::NotFound => Err(server::not_found()),
+        };
+
+        let (parts, body) = response
+            .unwrap_or_else(|e| server::async_error_response(&e))
+            .into_parts();
+        let body = match body {
+            AsyncResponseBody::Empty => Bytes::new(),
+            AsyncResponseBody::Fixed(bytes) => bytes,
+            AsyncResponseBody::Streaming(writer) => {
+                let mut buf = vec![];
+                writer.write_body(Pin::new(&mut buf)).await?;
+                Bytes::from(buf)
+            }
+        };
+
+        client_response(parts, body, response_extensions)
+    }
+}
+
+// Each endpoint gets its own routes so that a request is only ever matched against the endpoint it names.
+struct Endpoints<E> {
+    endpoints: HashMap<(String, String), Routes<E>>,
+}
+
+impl<E> Endpoints<E>
+where
+    E: EndpointMetadata,
+{
+    fn new(endpoints: Vec<E>) -> Result<Self, RouterError> {
+        let mut map = HashMap::new();
+        for endpoint in endpoints {
+            let key = (
+                endpoint.service_name().to_string(),
+                endpoint.name().to_string(),
+            );
+            if map.contains_key(&key) {
+                return Err(RouterError(format!(
+                    "multiple endpoints are named {}.{}",
+                    key.0, key.1,
+                )));
+            }
+            map.insert(key, Routes::new(vec![endpoint])?);
+        }
+
+        Ok(Endpoints { endpoints: map })
+    }
+
+    fn route(&self, parts: &Parts) -> Result<Route<'_, E>, Error> {
+        let endpoint = parts
+            .extensions
+            .get::<client::Endpoint>()
+            .ok_or_else(|| Error::internal_safe("request is missing its Endpoint extension"))?;
+
+        let route = self
+            .endpoints
+            .get(&(endpoint.service().to_string(), endpoint.name().to_string()))
+            .map_or(Route::NotFound, |routes| {
+                routes.route(&parts.method, parts.uri.path())
+            });
+
+        Ok(route)
+    }
+}
+
+fn server_request(parts: &Parts, body: Bytes) -> Request<LoopbackBody> {
+    let mut request = Request::new(LoopbackBody::new(body));
+    *request.method_mut() = parts.method.clone();
+    *request.uri_mut() = parts.uri.clone();
+    *request.headers_mut() = parts.headers.clone();
+    request
+}
+
+fn client_response(
+    parts: http::response::Parts,
+    body: Bytes,
+    response_extensions: Extensions,
+) -> Result<Response<LoopbackBody>, Error> {
+    let mut response = Response::from_parts(parts, LoopbackBody::new(body));
+    if !response.status().is_success() {
+        return Err(client::decode_error(response));
+    }
+
+    response.extensions_mut().extend(response_extensions);
+    Ok(response)
+}
@@ -0,0 +1,220 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Middleware adding cross-cutting behavior to clients.
+//!
+//! A [`Middleware`] or [`AsyncMiddleware`] intercepts each request made through a client. It can modify the request
+//! before passing it on to the next client in the chain, and observe or replace the response or error it returns.
+//! Requests always contain a [`client::Endpoint`](crate::client::Endpoint) extension identifying the endpoint being
+//! called.
+//!
+//! # Examples
+//!
+//! ```
+//! use conjure_error::Error;
+//! use conjure_http::client::{Client, RequestBody};
+//! use conjure_http::middleware::{Middleware, MiddlewareBuilder};
+//! use http::header::{HeaderValue, USER_AGENT};
+//! use http::{Request, Response};
+//!
+//! struct UserAgent(HeaderValue);
+//!
+//! impl<C> Middleware<C> for UserAgent
+//! where
+//!     C: Client,
+//! {
+//!     fn send(
+//!         &self,
+//!         mut req: Request<RequestBody<'_, C::BodyWriter>>,
+//!         next: &C,
+//!     ) -> Result<Response<C::ResponseBody>, Error> {
+//!         req.headers_mut().insert(USER_AGENT, self.0.clone());
+//!         next.send(req)
+//!     }
+//! }
+//!
+//! # fn make_client<C: Client>(transport: C) -> impl Client {
+//! let client = MiddlewareBuilder::new()
+//!     .layer(UserAgent(HeaderValue::from_static("my-service/1.0.0")))
+//!     .build(transport);
+//! # client
+//! # }
+//! ```
+
+use conjure_error::Error;
+use http::{Request, Response};
+use std::future::Future;
+
+use crate::client::{AsyncClient, AsyncRequestBody, Client, RequestBody};
+
+/// A middleware wrapping requests made through a blocking client.
+pub trait Middleware<C>
+where
+    C: Client,
+{
+    /// Makes a request, delegating to `next` to continue down the chain.
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> Result<Response<C::ResponseBody>, Error>;
+}
+
+/// A middleware wrapping requests made through an async client.
+pub trait AsyncMiddleware<C>
+where
+    C: AsyncClient,
+{
+    /// Makes a request, delegating to `next` to continue down the chain.
+    fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> impl Future<Output = Result<Response<C::ResponseBody>, Error>> + Send;
+}
+
+/// A client which passes requests through a middleware before the client it wraps.
+pub struct Layered<M, C> {
+    middleware: M,
+    inner: C,
+}
+
+impl<M, C> Layered<M, C> {
+    /// Creates a new client applying a middleware to the requests made through another client.
+    pub fn new(middleware: M, inner: C) -> Self {
+        Layered { middleware, inner }
+    }
+
+    /// Returns a shared reference to the middleware.
+    pub fn middleware(&self) -> &M {
+        &self.middleware
+    }
+
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+}
+
+impl<M, C> Client for Layered<M, C>
+where
+    M: Middleware<C>,
+    C: Client,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        self.middleware.send(req, &self.inner)
+    }
+}
+
+impl<M, C> AsyncClient for Layered<M, C>
+where
+    M: AsyncMiddleware<C> + Sync,
+    C: AsyncClient + Sync,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> impl Future<Output = Result<Response<Self::ResponseBody>, Error>> + Send {
+        self.middleware.send(req, &self.inner)
+    }
+}
+
+/// A set of middleware which can be applied to a client.
+pub trait Layer<C> {
+    /// The client produced by applying the middleware.
+    type Client;
+
+    /// Wraps a client with the middleware.
+    fn layer(self, inner: C) -> Self::Client;
+}
+
+/// A [`Layer`] which applies no middleware.
+pub struct Identity(());
+
+impl<C> Layer<C> for Identity {
+    type Client = C;
+
+    fn layer(self, inner: C) -> C {
+        inner
+    }
+}
+
+/// A [`Layer`] which applies a middleware inside of an outer layer.
+pub struct Stack<L, M> {
+    outer: L,
+    middleware: M,
+}
+
+impl<L, M, C> Layer<C> for Stack<L, M>
+where
+    L: Layer<Layered<M, C>>,
+{
+    type Client = L::Client;
+
+    fn layer(self, inner: C) -> Self::Client {
+        self.outer.layer(Layered::new(self.middleware, inner))
+    }
+}
+
+/// A builder composing middleware into a chain.
+///
+/// Middleware is applied in the order it is added, so the first middleware added sees each request first and its
+/// response last.
+pub struct MiddlewareBuilder<L> {
+    layer: L,
+}
+
+impl MiddlewareBuilder<Identity> {
+    /// Creates a new builder with no middleware.
+    pub fn new() -> Self {
+        MiddlewareBuilder {
+            layer: Identity(()),
+        }
+    }
+}
+
+impl Default for MiddlewareBuilder<Identity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L> MiddlewareBuilder<L> {
+    /// Adds a middleware to the end of the chain.
+    pub fn layer<M>(self, middleware: M) -> MiddlewareBuilder<Stack<L, M>> {
+        MiddlewareBuilder {
+            layer: Stack {
+                outer: self.layer,
+                middleware,
+            },
+        }
+    }
+
+    /// Applies the middleware to a client.
+    pub fn build<C>(self, client: C) -> L::Client
+    where
+        L: Layer<C>,
+    {
+        self.layer.layer(client)
+    }
+}
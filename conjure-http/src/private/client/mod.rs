@@ -13,19 +13,24 @@
 // limitations under the License.
 use crate::client::{AsyncRequestBody, AsyncWriteBody, BoxAsyncWriteBody, RequestBody, WriteBody};
 pub use crate::private::client::uri_builder::UriBuilder;
-use crate::private::{async_read_body, read_body, APPLICATION_JSON, APPLICATION_OCTET_STREAM};
+use crate::private::{
+    async_read_body, read_body, with_error_path, Encoding, APPLICATION_JSON,
+    APPLICATION_OCTET_STREAM, APPLICATION_SMILE, APPLICATION_SMILE_OR_JSON,
+};
 use bytes::Bytes;
 use conjure_error::Error;
 use conjure_object::{BearerToken, Plain, ToPlain};
-use conjure_serde::json;
+use conjure_serde::{json, smile};
 use futures_core::Stream;
 use http::header::{
     HeaderName, HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, COOKIE,
 };
-use http::{Request, Response, StatusCode};
+use http::{HeaderMap, Request, Response, StatusCode};
 use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::Serialize;
+use std::io;
 
+pub(crate) mod error_params;
 mod uri_builder;
 
 pub fn encode_empty_request<'a, W>() -> Request<RequestBody<'a, W>>
@@ -46,26 +51,47 @@ pub fn encode_serializable_request<T, S>(body: &T) -> Request<RequestBody<S>>
 where
     T: Serialize,
 {
-    inner_encode_serializable_request(body, RequestBody::Fixed)
+    inner_encode_serializable_request(body, Encoding::Json, RequestBody::Fixed)
 }
 
 pub fn async_encode_serializable_request<T, S>(body: &T) -> Request<AsyncRequestBody<S>>
 where
     T: Serialize,
 {
-    inner_encode_serializable_request(body, AsyncRequestBody::Fixed)
+    inner_encode_serializable_request(body, Encoding::Json, AsyncRequestBody::Fixed)
+}
+
+pub fn encode_smile_request<T, S>(body: &T) -> Request<RequestBody<'_, S>>
+where
+    T: Serialize,
+{
+    inner_encode_serializable_request(body, Encoding::Smile, RequestBody::Fixed)
 }
 
-fn inner_encode_serializable_request<T, B, F>(body: &T, make_body: F) -> Request<B>
+pub fn async_encode_smile_request<T, S>(body: &T) -> Request<AsyncRequestBody<'_, S>>
+where
+    T: Serialize,
+{
+    inner_encode_serializable_request(body, Encoding::Smile, AsyncRequestBody::Fixed)
+}
+
+fn inner_encode_serializable_request<T, B, F>(
+    body: &T,
+    encoding: Encoding,
+    make_body: F,
+) -> Request<B>
 where
     T: Serialize,
     F: FnOnce(Bytes) -> B,
 {
-    let buf = json::to_vec(body).unwrap();
+    let (buf, content_type) = match encoding {
+        Encoding::Json => (json::to_vec(body).unwrap(), APPLICATION_JSON),
+        Encoding::Smile => (smile::to_vec(body).unwrap(), APPLICATION_SMILE),
+    };
     let len = buf.len();
 
     let mut request = Request::new(make_body(Bytes::from(buf)));
-    request.headers_mut().insert(CONTENT_TYPE, APPLICATION_JSON);
+    request.headers_mut().insert(CONTENT_TYPE, content_type);
     request
         .headers_mut()
         .insert(CONTENT_LENGTH, HeaderValue::from(len));
@@ -104,7 +130,9 @@ pub fn encode_empty_response_headers<B>(request: &mut Request<B>) {
 }
 
 pub fn encode_serializable_response_headers<B>(request: &mut Request<B>) {
-    request.headers_mut().insert(ACCEPT, APPLICATION_JSON);
+    request
+        .headers_mut()
+        .insert(ACCEPT, APPLICATION_SMILE_OR_JSON);
 }
 
 pub fn encode_binary_response_headers<B>(request: &mut Request<B>) {
@@ -219,14 +247,9 @@ where
     T: DeserializeOwned,
     I: Iterator<Item = Result<Bytes, Error>>,
 {
-    if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-        return Err(Error::internal_safe("invalid response Content-Type"));
-    }
-
+    let encoding = response_encoding(response.headers())?;
     let body = read_body(response.into_body(), None)?;
-    let body = json::client_from_slice(&body).map_err(Error::internal)?;
-
-    Ok(body)
+    decode_serializable_body(encoding, &body)
 }
 
 pub async fn async_decode_serializable_response<T, I>(response: Response<I>) -> Result<T, Error>
@@ -234,14 +257,77 @@ where
     T: DeserializeOwned,
     I: Stream<Item = Result<Bytes, Error>>,
 {
-    if response.headers().get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-        return Err(Error::internal("invalid response Content-Type"));
+    let encoding = response_encoding(response.headers())?;
+    let body = async_read_body(response.into_body(), None).await?;
+    decode_serializable_body(encoding, &body)
+}
+
+/// An `io::Read` adapter over a response body which stashes body errors so they can be returned unchanged.
+pub struct BodyReader<I> {
+    body: I,
+    cur: Bytes,
+    error: Option<Error>,
+}
+
+impl<I> BodyReader<I> {
+    pub(crate) fn new(body: I) -> BodyReader<I> {
+        BodyReader {
+            body,
+            cur: Bytes::new(),
+            error: None,
+        }
     }
 
-    let body = async_read_body(response.into_body(), None).await?;
-    let body = json::client_from_slice(&body).map_err(Error::internal)?;
+    pub(crate) fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+}
+
+impl<I> io::Read for BodyReader<I>
+where
+    I: Iterator<Item = Result<Bytes, Error>>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.cur.is_empty() {
+            match self.body.next() {
+                Some(Ok(bytes)) => self.cur = bytes,
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    return Err(io::Error::other("error reading body"));
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let len = usize::min(buf.len(), self.cur.len());
+        buf[..len].copy_from_slice(&self.cur.split_to(len));
+        Ok(len)
+    }
+}
+
+pub(crate) fn response_encoding(headers: &HeaderMap) -> Result<Encoding, Error> {
+    Encoding::from_content_type(headers)
+        .ok_or_else(|| Error::internal_safe("invalid response Content-Type"))
+}
 
-    Ok(body)
+pub(crate) fn decode_serializable_body<T>(encoding: Encoding, body: &[u8]) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    match encoding {
+        Encoding::Json => {
+            let mut de = json::ClientDeserializer::from_slice(body);
+            T::deserialize(&mut de)
+                .and_then(|v| de.end().map(|()| v))
+                .map_err(|e| with_error_path(Error::internal(e), de.error_path()))
+        }
+        Encoding::Smile => {
+            let mut de = smile::ClientDeserializer::from_slice(body);
+            T::deserialize(&mut de)
+                .and_then(|v| de.end().map(|()| v))
+                .map_err(|e| with_error_path(Error::internal(e), de.error_path()))
+        }
+    }
 }
 
 pub fn decode_optional_binary_response<I>(response: Response<I>) -> Result<Option<I>, Error> {
@@ -35,15 +35,44 @@ pub use std::vec::Vec;
 use bytes::BytesMut;
 use conjure_error::InvalidArgument;
 use futures_util::TryStreamExt;
-use http::HeaderValue;
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue};
 
 mod client;
 mod server;
 
 pub(crate) const APPLICATION_JSON: HeaderValue = HeaderValue::from_static("application/json");
+pub(crate) const APPLICATION_SMILE: HeaderValue =
+    HeaderValue::from_static("application/x-jackson-smile");
+pub(crate) const APPLICATION_SMILE_OR_JSON: HeaderValue =
+    HeaderValue::from_static("application/x-jackson-smile, application/json");
 pub(crate) const APPLICATION_OCTET_STREAM: HeaderValue =
     HeaderValue::from_static("application/octet-stream");
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Json,
+    Smile,
+}
+
+impl Encoding {
+    pub(crate) fn from_content_type(headers: &HeaderMap) -> Option<Encoding> {
+        match headers.get(CONTENT_TYPE) {
+            Some(v) if *v == APPLICATION_JSON => Some(Encoding::Json),
+            Some(v) if *v == APPLICATION_SMILE => Some(Encoding::Smile),
+            _ => None,
+        }
+    }
+}
+
+// attaches the location of a deserialization error, if known
+pub(crate) fn with_error_path(error: Error, path: Option<String>) -> Error {
+    match path {
+        Some(path) => error.with_safe_param("path", path),
+        None => error,
+    }
+}
+
 // slightly nontrivial to avoid a copy for single-chunk bodies
 pub fn read_body<I>(mut body: I, limit: Option<usize>) -> Result<Bytes, Error>
 where
@@ -111,26 +111,27 @@ fn parse_auth_inner(
 
 pub fn body_arg<D, T, I>(
     runtime: &ConjureRuntime,
-    headers: &HeaderMap,
+    parts: &mut request::Parts,
     body: I,
     log_as: &str,
 ) -> Result<T, Error>
 where
     D: DeserializeRequest<T, I>,
 {
-    D::deserialize(runtime, headers, body).map_err(|e| e.with_safe_param("param", log_as))
+    D::deserialize_with_extensions(runtime, &parts.headers, &mut parts.extensions, body)
+        .map_err(|e| e.with_safe_param("param", log_as))
 }
 
 pub async fn async_body_arg<D, T, I>(
     runtime: &ConjureRuntime,
-    headers: &HeaderMap,
+    parts: &mut request::Parts,
     body: I,
     log_as: &str,
 ) -> Result<T, Error>
 where
     D: AsyncDeserializeRequest<T, I>,
 {
-    D::deserialize(runtime, headers, body)
+    D::deserialize_with_extensions(runtime, &parts.headers, &mut parts.extensions, body)
         .await
         .map_err(|e| e.with_safe_param("param", log_as))
 }
@@ -0,0 +1,300 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client decorators which retry failed requests.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+use conjure_error::{Error, ErrorKind};
+use http::{Method, Request, Response};
+
+use crate::client::{
+    AsyncClient, AsyncRequestBody, AsyncWriteBody, BoxAsyncWriteBody, Client, RemoteError,
+    RequestBody, WriteBody,
+};
+
+/// A client which retries failed requests made through another client.
+///
+/// QoS errors ([`ErrorKind::Throttle`] and [`ErrorKind::Unavailable`]) are retried for all requests, since the server
+/// did not process them. Other errors are retried only for requests with idempotent methods, and only if they were not
+/// produced from a response of the server (i.e. their cause is not a [`RemoteError`]).
+///
+/// Retries are delayed with exponential backoff and full jitter, except for throttle errors with a duration, which are
+/// retried after that duration. All delays are capped at [`RetryingClientBuilder::max_backoff`]. Streaming request
+/// bodies are reset before being resent, and the request fails with the last error if the body cannot be reset.
+///
+/// Blocking requests sleep on the calling thread. Async requests wait on the sleep function provided with
+/// [`RetryingClientBuilder::async_sleep`], which must be set for the client to implement [`AsyncClient`].
+pub struct RetryingClient<C, S = ()> {
+    inner: C,
+    max_num_retries: u32,
+    backoff_slot_size: Duration,
+    max_backoff: Duration,
+    sleep: S,
+}
+
+impl<C> RetryingClient<C> {
+    /// Creates a new client with the default configuration.
+    pub fn new(inner: C) -> Self {
+        Self::builder(inner).build()
+    }
+
+    /// Returns a builder used to configure a client.
+    pub fn builder(inner: C) -> RetryingClientBuilder<C> {
+        RetryingClientBuilder {
+            inner,
+            max_num_retries: 4,
+            backoff_slot_size: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            sleep: (),
+        }
+    }
+}
+
+impl<C, S> RetryingClient<C, S> {
+    /// Returns a shared reference to the inner client.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    fn retry_delay(&self, method: &Method, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_num_retries {
+            return None;
+        }
+
+        match error.kind() {
+            ErrorKind::Throttle(e) => Some(match e.duration() {
+                Some(duration) => duration.min(self.max_backoff),
+                None => self.backoff(attempt),
+            }),
+            ErrorKind::Unavailable(_) => Some(self.backoff(attempt)),
+            _ if is_idempotent(method) && !error.cause().is::<RemoteError>() => {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let upper = 2u32
+            .checked_pow(attempt)
+            .and_then(|scale| self.backoff_slot_size.checked_mul(scale))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+
+        upper.mul_f64(rand::random::<f64>())
+    }
+}
+
+/// A builder for [`RetryingClient`]s.
+pub struct RetryingClientBuilder<C, S = ()> {
+    inner: C,
+    max_num_retries: u32,
+    backoff_slot_size: Duration,
+    max_backoff: Duration,
+    sleep: S,
+}
+
+impl<C, S> RetryingClientBuilder<C, S> {
+    /// Sets the maximum number of times a request will be retried.
+    ///
+    /// Defaults to 4.
+    pub fn max_num_retries(mut self, max_num_retries: u32) -> Self {
+        self.max_num_retries = max_num_retries;
+        self
+    }
+
+    /// Sets the base delay of the exponential backoff between retries.
+    ///
+    /// The delay before the `n`th retry is chosen uniformly at random between zero and `backoff_slot_size * 2^n`.
+    ///
+    /// Defaults to 250 milliseconds.
+    pub fn backoff_slot_size(mut self, backoff_slot_size: Duration) -> Self {
+        self.backoff_slot_size = backoff_slot_size;
+        self
+    }
+
+    /// Sets the upper bound of the delay between retries.
+    ///
+    /// It also applies to durations requested by throttle errors.
+    ///
+    /// Defaults to 30 seconds.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Sets the function used to wait between retries of async requests.
+    ///
+    /// This is typically the sleep function of the async runtime, for example `tokio::time::sleep`.
+    pub fn async_sleep<F, R>(self, sleep: F) -> RetryingClientBuilder<C, F>
+    where
+        F: Fn(Duration) -> R,
+        R: Future<Output = ()>,
+    {
+        RetryingClientBuilder {
+            inner: self.inner,
+            max_num_retries: self.max_num_retries,
+            backoff_slot_size: self.backoff_slot_size,
+            max_backoff: self.max_backoff,
+            sleep,
+        }
+    }
+
+    /// Creates the client.
+    pub fn build(self) -> RetryingClient<C, S> {
+        RetryingClient {
+            inner: self.inner,
+            max_num_retries: self.max_num_retries,
+            backoff_slot_size: self.backoff_slot_size,
+            max_backoff: self.max_backoff,
+            sleep: self.sleep,
+        }
+    }
+}
+
+impl<C, S> Client for RetryingClient<C, S>
+where
+    C: Client,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+
+        let mut attempt = 0;
+        loop {
+            let attempt_body = match &mut body {
+                RequestBody::Empty => RequestBody::Empty,
+                RequestBody::Fixed(bytes) => RequestBody::Fixed(bytes.clone()),
+                RequestBody::Streaming(writer) => {
+                    RequestBody::Streaming(Box::new(BorrowedWriteBody(&mut **writer)))
+                }
+            };
+
+            let error = match self
+                .inner
+                .send(Request::from_parts(parts.clone(), attempt_body))
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let Some(delay) = self.retry_delay(&parts.method, attempt, &error) else {
+                return Err(error);
+            };
+
+            if let RequestBody::Streaming(writer) = &mut body {
+                if !writer.reset() {
+                    return Err(error);
+                }
+            }
+
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+}
+
+impl<C, S, R> AsyncClient for RetryingClient<C, S>
+where
+    C: AsyncClient + Sync,
+    C::BodyWriter: Send,
+    S: Fn(Duration) -> R + Sync,
+    R: Future<Output = ()> + Send,
+{
+    type BodyWriter = C::BodyWriter;
+    type ResponseBody = C::ResponseBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let (parts, mut body) = req.into_parts();
+
+        let mut attempt = 0;
+        loop {
+            let attempt_body = match &mut body {
+                AsyncRequestBody::Empty => AsyncRequestBody::Empty,
+                AsyncRequestBody::Fixed(bytes) => AsyncRequestBody::Fixed(bytes.clone()),
+                AsyncRequestBody::Streaming(writer) => AsyncRequestBody::Streaming(
+                    BoxAsyncWriteBody::new(BorrowedAsyncWriteBody(writer)),
+                ),
+            };
+
+            let error = match self
+                .inner
+                .send(Request::from_parts(parts.clone(), attempt_body))
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            let Some(delay) = self.retry_delay(&parts.method, attempt, &error) else {
+                return Err(error);
+            };
+
+            if let AsyncRequestBody::Streaming(writer) = &mut body {
+                if !Pin::new(writer).reset().await {
+                    return Err(error);
+                }
+            }
+
+            (self.sleep)(delay).await;
+            attempt += 1;
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::PUT | Method::DELETE | Method::TRACE
+    )
+}
+
+// Lends a streaming body to a single attempt so it can be reset and reused by the next.
+struct BorrowedWriteBody<'a, 'b, W>(&'a mut (dyn WriteBody<W> + 'b));
+
+impl<W> WriteBody<W> for BorrowedWriteBody<'_, '_, W> {
+    fn write_body(&mut self, w: &mut W) -> Result<(), Error> {
+        self.0.write_body(w)
+    }
+
+    fn reset(&mut self) -> bool {
+        self.0.reset()
+    }
+}
+
+struct BorrowedAsyncWriteBody<'a, 'b, W>(&'a mut BoxAsyncWriteBody<'b, W>);
+
+impl<W> AsyncWriteBody<W> for BorrowedAsyncWriteBody<'_, '_, W>
+where
+    W: Send,
+{
+    async fn write_body(self: Pin<&mut Self>, w: Pin<&mut W>) -> Result<(), Error> {
+        Pin::new(&mut *self.get_mut().0).write_body(w).await
+    }
+
+    async fn reset(self: Pin<&mut Self>) -> bool {
+        Pin::new(&mut *self.get_mut().0).reset().await
+    }
+}
@@ -20,7 +20,7 @@ use bytes::Bytes;
 use conjure_error::{Error, InvalidArgument};
 use conjure_object::FromPlain;
 use futures_core::Stream;
-use http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, Response};
+use http::{header::CONTENT_TYPE, Extensions, HeaderMap, HeaderValue, Response};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::private::APPLICATION_OCTET_STREAM;
@@ -50,6 +50,21 @@ where
 
         <StdRequestDeserializer as DeserializeRequest<_, _>>::deserialize(runtime, headers, body)
     }
+
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<Option<T>, Error> {
+        if !headers.contains_key(CONTENT_TYPE) {
+            return Ok(None);
+        }
+
+        <StdRequestDeserializer as DeserializeRequest<_, _>>::deserialize_with_extensions(
+            runtime, headers, extensions, body,
+        )
+    }
 }
 
 impl<T, R> AsyncDeserializeRequest<Option<T>, R> for OptionalRequestDeserializer
@@ -71,6 +86,22 @@ where
         )
         .await
     }
+
+    async fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<Option<T>, Error> {
+        if !headers.contains_key(CONTENT_TYPE) {
+            return Ok(None);
+        }
+
+        <StdRequestDeserializer as AsyncDeserializeRequest<_, _>>::deserialize_with_extensions(
+            runtime, headers, extensions, body,
+        )
+        .await
+    }
 }
 
 /// A request deserializer for binary body types.
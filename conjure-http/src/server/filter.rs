@@ -0,0 +1,229 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Filters wrapping the endpoints of services.
+
+use std::future::Future;
+use std::sync::Arc;
+
+use conjure_error::Error;
+use http::{Extensions, Method, Request, Response};
+
+use crate::server::{
+    AsyncEndpoint, AsyncResponseBody, AsyncService, BoxAsyncEndpoint, ConjureRuntime, Endpoint,
+    EndpointMetadata, PathSegment, ResponseBody, Service,
+};
+
+/// A filter applied to requests to blocking endpoints.
+///
+/// Filters can inspect and modify requests before passing them on to the endpoint, short-circuit by returning an
+/// error without calling it, and inspect or replace its response. The endpoint's [`EndpointMetadata`] is available
+/// through `next`.
+pub trait Filter<I, O> {
+    /// Handles a request, delegating to `next` to invoke the endpoint.
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &dyn Endpoint<I, O>,
+    ) -> Result<Response<ResponseBody<O>>, Error>;
+}
+
+impl<T, I, O> Filter<I, O> for Arc<T>
+where
+    T: ?Sized + Filter<I, O>,
+{
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &dyn Endpoint<I, O>,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        (**self).handle(req, response_extensions, next)
+    }
+}
+
+/// A filter applied to requests to async endpoints.
+///
+/// It behaves like a [`Filter`].
+pub trait AsyncFilter<I, O> {
+    /// Handles a request, delegating to `next` to invoke the endpoint.
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &BoxAsyncEndpoint<'static, I, O>,
+    ) -> impl Future<Output = Result<Response<AsyncResponseBody<O>>, Error>> + Send;
+}
+
+impl<T, I, O> AsyncFilter<I, O> for Arc<T>
+where
+    T: ?Sized + AsyncFilter<I, O>,
+{
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &BoxAsyncEndpoint<'static, I, O>,
+    ) -> impl Future<Output = Result<Response<AsyncResponseBody<O>>, Error>> + Send {
+        (**self).handle(req, response_extensions, next)
+    }
+}
+
+/// A service which applies a filter to all endpoints of another service.
+///
+/// Filters can be chained by nesting filtered services, in which case the outermost filter sees each request first.
+pub struct FilteredService<S, F> {
+    service: S,
+    filter: Arc<F>,
+}
+
+impl<S, F> FilteredService<S, F> {
+    /// Creates a new service applying a filter to the endpoints of another service.
+    pub fn new(service: S, filter: F) -> Self {
+        FilteredService {
+            service,
+            filter: Arc::new(filter),
+        }
+    }
+}
+
+impl<S, F, I, O> Service<I, O> for FilteredService<S, F>
+where
+    S: Service<I, O>,
+    F: Filter<I, O> + 'static + Sync + Send,
+    I: 'static,
+    O: 'static,
+{
+    fn endpoints(
+        &self,
+        runtime: &Arc<ConjureRuntime>,
+    ) -> Vec<Box<dyn Endpoint<I, O> + Sync + Send>> {
+        self.service
+            .endpoints(runtime)
+            .into_iter()
+            .map(|endpoint| {
+                Box::new(FilteredEndpoint {
+                    filter: self.filter.clone(),
+                    endpoint,
+                }) as _
+            })
+            .collect()
+    }
+}
+
+impl<S, F, I, O> AsyncService<I, O> for FilteredService<S, F>
+where
+    S: AsyncService<I, O>,
+    F: AsyncFilter<I, O> + 'static + Sync + Send,
+    I: 'static + Send,
+    O: 'static,
+{
+    fn endpoints(&self, runtime: &Arc<ConjureRuntime>) -> Vec<BoxAsyncEndpoint<'static, I, O>> {
+        self.service
+            .endpoints(runtime)
+            .into_iter()
+            .map(|endpoint| {
+                BoxAsyncEndpoint::new(FilteredEndpoint {
+                    filter: self.filter.clone(),
+                    endpoint,
+                })
+            })
+            .collect()
+    }
+}
+
+/// An endpoint wrapped by a filter.
+pub struct FilteredEndpoint<F, E> {
+    filter: Arc<F>,
+    endpoint: E,
+}
+
+impl<F, E> FilteredEndpoint<F, E> {
+    /// Creates a new endpoint applying a filter to another endpoint.
+    ///
+    /// Like [`FilteredService::new`], this takes ownership of the filter. Filters implemented for `T` are also
+    /// implemented for `Arc<T>`, so one filter can be shared between endpoints by passing clones of an `Arc`.
+    pub fn new(endpoint: E, filter: F) -> Self {
+        FilteredEndpoint {
+            filter: Arc::new(filter),
+            endpoint,
+        }
+    }
+}
+
+impl<F, E> EndpointMetadata for FilteredEndpoint<F, E>
+where
+    E: EndpointMetadata,
+{
+    fn method(&self) -> Method {
+        self.endpoint.method()
+    }
+
+    fn path(&self) -> &[PathSegment] {
+        self.endpoint.path()
+    }
+
+    fn template(&self) -> &str {
+        self.endpoint.template()
+    }
+
+    fn service_name(&self) -> &str {
+        self.endpoint.service_name()
+    }
+
+    fn name(&self) -> &str {
+        self.endpoint.name()
+    }
+
+    fn deprecated(&self) -> Option<&str> {
+        self.endpoint.deprecated()
+    }
+
+    fn tags(&self) -> &[&str] {
+        self.endpoint.tags()
+    }
+
+    fn markers(&self) -> &[&str] {
+        self.endpoint.markers()
+    }
+}
+
+impl<F, I, O> Endpoint<I, O> for FilteredEndpoint<F, Box<dyn Endpoint<I, O> + Sync + Send>>
+where
+    F: Filter<I, O>,
+{
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        self.filter
+            .handle(req, response_extensions, &*self.endpoint)
+    }
+}
+
+impl<F, I, O> AsyncEndpoint<I, O> for FilteredEndpoint<F, BoxAsyncEndpoint<'static, I, O>>
+where
+    F: AsyncFilter<I, O> + Sync + Send,
+    I: Send,
+{
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> impl Future<Output = Result<Response<AsyncResponseBody<O>>, Error>> + Send {
+        self.filter.handle(req, response_extensions, &self.endpoint)
+    }
+}
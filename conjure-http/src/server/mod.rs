@@ -14,30 +14,48 @@
 
 //! The Conjure HTTP server API.
 use bytes::Bytes;
-use conjure_error::{Error, InvalidArgument};
-use conjure_serde::json;
+use conjure_error::{Error, ErrorKind, Internal, InvalidArgument};
+use conjure_object::{Validate, ValidationError};
+use conjure_serde::{json, smile};
 use futures_core::Stream;
-use http::header::CONTENT_TYPE;
+use futures_sink::Sink;
+use futures_util::{SinkExt, StreamExt};
+use http::header::{ACCEPT, CONTENT_TYPE, RETRY_AFTER};
 use http::{
     request, Extensions, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri,
 };
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
 use std::borrow::Cow;
 use std::error;
 use std::future::Future;
 use std::io::Write;
 use std::iter::FromIterator;
 use std::marker::PhantomData;
+use std::mem;
 use std::ops::Deref;
 use std::pin::Pin;
 use std::str;
 use std::str::FromStr;
 use std::sync::Arc;
 
-use crate::private::{self, APPLICATION_JSON, SERIALIZABLE_REQUEST_SIZE_LIMIT};
+use crate::private::{
+    self, Encoding, APPLICATION_JSON, APPLICATION_SMILE, SERIALIZABLE_REQUEST_SIZE_LIMIT,
+};
+
+pub use self::filter::{AsyncFilter, Filter, FilteredEndpoint, FilteredService};
+#[cfg(feature = "router")]
+pub(crate) use self::router::{method_not_allowed, not_found, Routes};
+#[cfg(feature = "router")]
+pub use self::router::{
+    AsyncRouter, AsyncRouterBuilder, Route, Router, RouterBuilder, RouterError,
+};
 
 pub mod conjure;
+mod filter;
+#[cfg(feature = "router")]
+mod router;
 
 /// Metadata about an HTTP endpoint.
 pub trait EndpointMetadata {
@@ -62,6 +80,19 @@ pub trait EndpointMetadata {
 
     /// If the endpoint is deprecated, returns the deprecation documentation.
     fn deprecated(&self) -> Option<&str>;
+
+    /// The endpoint's tags.
+    fn tags(&self) -> &[&str] {
+        &[]
+    }
+
+    /// The endpoint's markers.
+    ///
+    /// Each marker is identified by the fully qualified name of its Conjure type, for example
+    /// `com.palantir.logsafe.Safe`.
+    fn markers(&self) -> &[&str] {
+        &[]
+    }
 }
 
 impl<T> EndpointMetadata for Box<T>
@@ -91,6 +122,14 @@ where
     fn deprecated(&self) -> Option<&str> {
         (**self).deprecated()
     }
+
+    fn tags(&self) -> &[&str] {
+        (**self).tags()
+    }
+
+    fn markers(&self) -> &[&str] {
+        (**self).markers()
+    }
 }
 
 /// A blocking HTTP endpoint.
@@ -224,6 +263,14 @@ impl<'a, I, O> EndpointMetadata for BoxAsyncEndpoint<'a, I, O> {
     fn deprecated(&self) -> Option<&str> {
         self.inner.deprecated()
     }
+
+    fn tags(&self) -> &[&str] {
+        self.inner.tags()
+    }
+
+    fn markers(&self) -> &[&str] {
+        self.inner.markers()
+    }
 }
 
 impl<'a, I, O> AsyncEndpoint<I, O> for BoxAsyncEndpoint<'a, I, O>
@@ -291,12 +338,26 @@ pub trait AsyncService<I, O> {
 }
 
 /// A type providing server logic that is configured at runtime.
-pub struct ConjureRuntime(());
+pub struct ConjureRuntime {
+    unknown_field_policy: UnknownFieldPolicy,
+}
 
 impl ConjureRuntime {
     /// Creates a new runtime with default settings.
     pub fn new() -> Self {
-        ConjureRuntime(())
+        Self::builder().build()
+    }
+
+    /// Returns a builder used to create a runtime with custom settings.
+    pub fn builder() -> ConjureRuntimeBuilder {
+        ConjureRuntimeBuilder {
+            unknown_field_policy: UnknownFieldPolicy::Reject,
+        }
+    }
+
+    /// Returns the policy applied to unknown fields in request bodies.
+    pub fn unknown_field_policy(&self) -> UnknownFieldPolicy {
+        self.unknown_field_policy
     }
 }
 
@@ -306,6 +367,42 @@ impl Default for ConjureRuntime {
     }
 }
 
+/// A builder for [`ConjureRuntime`]s.
+pub struct ConjureRuntimeBuilder {
+    unknown_field_policy: UnknownFieldPolicy,
+}
+
+impl ConjureRuntimeBuilder {
+    /// Sets the policy applied to unknown fields in request bodies deserialized by [`StdRequestDeserializer`].
+    ///
+    /// Defaults to [`UnknownFieldPolicy::Reject`].
+    pub fn unknown_field_policy(mut self, unknown_field_policy: UnknownFieldPolicy) -> Self {
+        self.unknown_field_policy = unknown_field_policy;
+        self
+    }
+
+    /// Creates the runtime.
+    pub fn build(self) -> ConjureRuntime {
+        ConjureRuntime {
+            unknown_field_policy: self.unknown_field_policy,
+        }
+    }
+}
+
+/// The handling of unknown object fields in request bodies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UnknownFieldPolicy {
+    /// Reject the request with an `InvalidArgument` error.
+    ///
+    /// This is the behavior required by the Conjure specification.
+    Reject,
+    /// Ignore the fields.
+    Ignore,
+    /// Ignore the fields, but record their paths so they can be retrieved from the endpoint's [`RequestContext`] with
+    /// [`RequestContext::unknown_fields`].
+    Collect,
+}
+
 /// A trait implemented by streaming bodies.
 pub trait WriteBody<W> {
     /// Writes the body out, in its entirety.
@@ -462,8 +559,24 @@ impl<'a> RequestContext<'a> {
     pub fn response_extensions_mut(&mut self) -> &mut Extensions {
         self.response_extensions
     }
+
+    /// Returns the paths of the unknown fields ignored when deserializing the request body, for example
+    /// `items[3].bogus`.
+    ///
+    /// Fields are only recorded when the runtime's [`UnknownFieldPolicy`] is [`UnknownFieldPolicy::Collect`]. The
+    /// paths include field names provided by the client, so they should not be treated as safe.
+    #[inline]
+    pub fn unknown_fields(&self) -> &[String] {
+        self.request_parts
+            .extensions
+            .get::<UnknownFields>()
+            .map_or(&[], |f| &f.0)
+    }
 }
 
+#[derive(Clone)]
+struct UnknownFields(Vec<String>);
+
 enum MaybeBorrowed<'a, T> {
     Borrowed(&'a T),
     Owned(T),
@@ -486,6 +599,19 @@ impl<T> Deref for MaybeBorrowed<'_, T> {
 pub trait DeserializeRequest<T, R> {
     /// Deserializes the request body.
     fn deserialize(runtime: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error>;
+
+    /// Deserializes the request body, with mutable access to the request's extensions.
+    ///
+    /// Values added to the extensions are visible to the endpoint through its [`RequestContext`]. The default
+    /// implementation delegates to [`DeserializeRequest::deserialize`].
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        _extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        Self::deserialize(runtime, headers, body)
+    }
 }
 
 /// A trait implemented by response deserializers used by custom async Conjure server trait
@@ -497,24 +623,88 @@ pub trait AsyncDeserializeRequest<T, R> {
         headers: &HeaderMap,
         body: R,
     ) -> impl Future<Output = Result<T, Error>> + Send;
+
+    /// Deserializes the request body, with mutable access to the request's extensions.
+    ///
+    /// Values added to the extensions are visible to the endpoint through its [`RequestContext`]. The default
+    /// implementation delegates to [`AsyncDeserializeRequest::deserialize`].
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        _extensions: &mut Extensions,
+        body: R,
+    ) -> impl Future<Output = Result<T, Error>> + Send {
+        Self::deserialize(runtime, headers, body)
+    }
 }
 
 /// A request deserializer for standard body types.
 ///
+/// Bodies are decoded as JSON or Smile depending on the request's `Content-Type`. Unknown fields are handled according
+/// to the runtime's [`UnknownFieldPolicy`].
+///
 /// It is parameterized by the maximum number of bytes that will be read from the request body
 /// before an error is returned. The limit defaults to 50 MiB.
 pub enum StdRequestDeserializer<const N: usize = { SERIALIZABLE_REQUEST_SIZE_LIMIT }> {}
 
 impl<const N: usize> StdRequestDeserializer<N> {
-    fn check_content_type(headers: &HeaderMap) -> Result<(), Error> {
-        if headers.get(CONTENT_TYPE) != Some(&APPLICATION_JSON) {
-            return Err(Error::service_safe(
-                "invalid request Content-Type",
-                InvalidArgument::new(),
-            ));
+    fn check_content_type(headers: &HeaderMap) -> Result<Encoding, Error> {
+        Encoding::from_content_type(headers).ok_or_else(|| {
+            Error::service_safe("invalid request Content-Type", InvalidArgument::new())
+        })
+    }
+
+    fn deserialize_inner<T>(
+        runtime: &ConjureRuntime,
+        encoding: Encoding,
+        buf: &[u8],
+        extensions: Option<&mut Extensions>,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let ignore_unknown_fields = runtime.unknown_field_policy != UnknownFieldPolicy::Reject;
+        // there's nowhere to put the fields without the extensions
+        let collect_unknown_fields =
+            runtime.unknown_field_policy == UnknownFieldPolicy::Collect && extensions.is_some();
+        let (value, unknown_fields) = match encoding {
+            Encoding::Json => {
+                let mut de = json::ServerDeserializer::from_slice(buf);
+                de.ignore_unknown_fields(ignore_unknown_fields);
+                de.collect_unknown_fields(collect_unknown_fields);
+                let value = T::deserialize(&mut de)
+                    .and_then(|v| de.end().map(|()| v))
+                    .map_err(|e| {
+                        private::with_error_path(
+                            Error::service(e, InvalidArgument::new()),
+                            de.error_path(),
+                        )
+                    })?;
+                (value, de.take_unknown_fields())
+            }
+            Encoding::Smile => {
+                let mut de = smile::ServerDeserializer::from_slice(buf);
+                de.ignore_unknown_fields(ignore_unknown_fields);
+                de.collect_unknown_fields(collect_unknown_fields);
+                let value = T::deserialize(&mut de)
+                    .and_then(|v| de.end().map(|()| v))
+                    .map_err(|e| {
+                        private::with_error_path(
+                            Error::service(e, InvalidArgument::new()),
+                            de.error_path(),
+                        )
+                    })?;
+                (value, de.take_unknown_fields())
+            }
+        };
+
+        if !unknown_fields.is_empty() {
+            if let Some(extensions) = extensions {
+                extensions.insert(UnknownFields(unknown_fields));
+            }
         }
 
-        Ok(())
+        Ok(value)
     }
 }
 
@@ -523,10 +713,21 @@ where
     T: DeserializeOwned,
     R: Iterator<Item = Result<Bytes, Error>>,
 {
-    fn deserialize(_: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
-        Self::check_content_type(headers)?;
+    fn deserialize(runtime: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
+        let encoding = Self::check_content_type(headers)?;
+        let buf = private::read_body(body, Some(N))?;
+        Self::deserialize_inner(runtime, encoding, &buf, None)
+    }
+
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        let encoding = Self::check_content_type(headers)?;
         let buf = private::read_body(body, Some(N))?;
-        json::server_from_slice(&buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+        Self::deserialize_inner(runtime, encoding, &buf, Some(extensions))
     }
 }
 
@@ -535,10 +736,25 @@ where
     T: DeserializeOwned,
     R: Stream<Item = Result<Bytes, Error>> + Send,
 {
-    async fn deserialize(_: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
-        Self::check_content_type(headers)?;
+    async fn deserialize(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        body: R,
+    ) -> Result<T, Error> {
+        let encoding = Self::check_content_type(headers)?;
         let buf = private::async_read_body(body, Some(N)).await?;
-        json::server_from_slice(&buf).map_err(|e| Error::service(e, InvalidArgument::new()))
+        Self::deserialize_inner(runtime, encoding, &buf, None)
+    }
+
+    async fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        let encoding = Self::check_content_type(headers)?;
+        let buf = private::async_read_body(body, Some(N)).await?;
+        Self::deserialize_inner(runtime, encoding, &buf, Some(extensions))
     }
 }
 
@@ -555,6 +771,15 @@ where
     fn deserialize(runtime: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
         D::deserialize(runtime, headers, body).map(From::from)
     }
+
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body).map(From::from)
+    }
 }
 
 impl<T, R, D, U> AsyncDeserializeRequest<T, R> for FromRequestDeserializer<D, U>
@@ -570,6 +795,197 @@ where
     ) -> Result<T, Error> {
         D::deserialize(runtime, headers, body).await.map(From::from)
     }
+
+    async fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body)
+            .await
+            .map(From::from)
+    }
+}
+
+/// A request deserializer which validates the output of another with [`Validate::validate`].
+///
+/// Validation failures are reported as [`InvalidArgument`] errors.
+pub struct ValidatingRequestDeserializer<D = StdRequestDeserializer> {
+    _p: PhantomData<D>,
+}
+
+impl<D> ValidatingRequestDeserializer<D> {
+    fn validate<T>(value: T) -> Result<T, Error>
+    where
+        T: Validate,
+    {
+        value.validate().map_err(invalid_argument)?;
+        Ok(value)
+    }
+}
+
+fn invalid_argument(error: ValidationError) -> Error {
+    let path = error.path().to_string();
+    Error::service(error, InvalidArgument::new()).with_safe_param("path", path)
+}
+
+impl<T, R, D> DeserializeRequest<T, R> for ValidatingRequestDeserializer<D>
+where
+    T: Validate,
+    D: DeserializeRequest<T, R>,
+{
+    fn deserialize(runtime: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
+        D::deserialize(runtime, headers, body).and_then(Self::validate)
+    }
+
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body).and_then(Self::validate)
+    }
+}
+
+impl<T, R, D> AsyncDeserializeRequest<T, R> for ValidatingRequestDeserializer<D>
+where
+    T: Validate,
+    D: AsyncDeserializeRequest<T, R>,
+    R: Send,
+{
+    async fn deserialize(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize(runtime, headers, body)
+            .await
+            .and_then(Self::validate)
+    }
+
+    async fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body)
+            .await
+            .and_then(Self::validate)
+    }
+}
+
+/// A constraint on the values of a request argument.
+///
+/// Generated servers implement this for arguments with markers that have declared constraints, and check it with
+/// [`ConstrainedDecoder`] and [`ConstrainedRequestDeserializer`].
+pub trait Constraint<T> {
+    /// Checks that the value satisfies the constraint.
+    fn check(value: &T) -> Result<(), ValidationError>;
+}
+
+/// A decoder which checks the output of another against a [`Constraint`].
+///
+/// Violations are reported as [`InvalidArgument`] errors.
+pub struct ConstrainedDecoder<D, C> {
+    _p: PhantomData<(D, C)>,
+}
+
+impl<T, D, C> DecodeParam<T> for ConstrainedDecoder<D, C>
+where
+    D: DecodeParam<T>,
+    C: Constraint<T>,
+{
+    fn decode<I>(runtime: &ConjureRuntime, params: I) -> Result<T, Error>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let value = D::decode(runtime, params)?;
+        C::check(&value).map_err(invalid_argument)?;
+        Ok(value)
+    }
+}
+
+impl<T, D, C> DecodeHeader<T> for ConstrainedDecoder<D, C>
+where
+    D: DecodeHeader<T>,
+    C: Constraint<T>,
+{
+    fn decode<'a, I>(runtime: &ConjureRuntime, headers: I) -> Result<T, Error>
+    where
+        I: IntoIterator<Item = &'a HeaderValue>,
+    {
+        let value = D::decode(runtime, headers)?;
+        C::check(&value).map_err(invalid_argument)?;
+        Ok(value)
+    }
+}
+
+/// A request deserializer which checks the output of another against a [`Constraint`].
+///
+/// Violations are reported as [`InvalidArgument`] errors.
+pub struct ConstrainedRequestDeserializer<D, C> {
+    _p: PhantomData<(D, C)>,
+}
+
+impl<D, C> ConstrainedRequestDeserializer<D, C> {
+    fn check<T>(value: T) -> Result<T, Error>
+    where
+        C: Constraint<T>,
+    {
+        C::check(&value).map_err(invalid_argument)?;
+        Ok(value)
+    }
+}
+
+impl<T, R, D, C> DeserializeRequest<T, R> for ConstrainedRequestDeserializer<D, C>
+where
+    D: DeserializeRequest<T, R>,
+    C: Constraint<T>,
+{
+    fn deserialize(runtime: &ConjureRuntime, headers: &HeaderMap, body: R) -> Result<T, Error> {
+        D::deserialize(runtime, headers, body).and_then(Self::check)
+    }
+
+    fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body).and_then(Self::check)
+    }
+}
+
+impl<T, R, D, C> AsyncDeserializeRequest<T, R> for ConstrainedRequestDeserializer<D, C>
+where
+    D: AsyncDeserializeRequest<T, R>,
+    C: Constraint<T>,
+    R: Send,
+{
+    async fn deserialize(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize(runtime, headers, body)
+            .await
+            .and_then(Self::check)
+    }
+
+    async fn deserialize_with_extensions(
+        runtime: &ConjureRuntime,
+        headers: &HeaderMap,
+        extensions: &mut Extensions,
+        body: R,
+    ) -> Result<T, Error> {
+        D::deserialize_with_extensions(runtime, headers, extensions, body)
+            .await
+            .and_then(Self::check)
+    }
 }
 
 /// A trait implemented by response serializers used by custom Conjure server trait implementations.
@@ -625,22 +1041,33 @@ impl<W> AsyncSerializeResponse<(), W> for EmptyResponseSerializer {
 }
 
 /// A body serializer for standard response types.
+///
+/// The body is encoded as Smile if the request's `Accept` header prefers
+/// `application/x-jackson-smile`, and as JSON otherwise.
 pub enum StdResponseSerializer {}
 
 impl StdResponseSerializer {
     fn serialize_inner<T, B>(
+        request_headers: &HeaderMap,
         value: T,
         make_body: impl FnOnce(Bytes) -> B,
     ) -> Result<Response<B>, Error>
     where
         T: Serialize,
     {
-        let body = json::to_vec(&value).map_err(Error::internal)?;
+        let (body, content_type) = match Encoding::negotiate(request_headers) {
+            Encoding::Json => (
+                json::to_vec(&value).map_err(Error::internal)?,
+                APPLICATION_JSON,
+            ),
+            Encoding::Smile => (
+                smile::to_vec(&value).map_err(Error::internal)?,
+                APPLICATION_SMILE,
+            ),
+        };
 
         let mut response = Response::new(make_body(body.into()));
-        response
-            .headers_mut()
-            .insert(CONTENT_TYPE, APPLICATION_JSON);
+        response.headers_mut().insert(CONTENT_TYPE, content_type);
 
         Ok(response)
     }
@@ -652,10 +1079,10 @@ where
 {
     fn serialize(
         _runtime: &ConjureRuntime,
-        _request_headers: &HeaderMap,
+        request_headers: &HeaderMap,
         value: T,
     ) -> Result<Response<ResponseBody<W>>, Error> {
-        Self::serialize_inner(value, ResponseBody::Fixed)
+        Self::serialize_inner(request_headers, value, ResponseBody::Fixed)
     }
 }
 
@@ -665,13 +1092,313 @@ where
 {
     fn serialize(
         _runtime: &ConjureRuntime,
-        _request_headers: &HeaderMap,
+        request_headers: &HeaderMap,
         value: T,
     ) -> Result<Response<AsyncResponseBody<W>>, Error> {
-        Self::serialize_inner(value, AsyncResponseBody::Fixed)
+        Self::serialize_inner(request_headers, value, AsyncResponseBody::Fixed)
+    }
+}
+
+/// A response serializer which incrementally writes the items of an iterator as an array.
+///
+/// The body is encoded as Smile if the request's `Accept` header prefers `application/x-jackson-smile`, and as JSON
+/// otherwise, just like [`StdResponseSerializer`]. Unlike that serializer, items are serialized as the body is written
+/// so the entire response never needs to be buffered in memory. Since the response has already started by then, an
+/// error serializing an item will cause the body to be truncated rather than producing an error response.
+pub enum IterResponseSerializer {}
+
+impl<T, W> SerializeResponse<T, W> for IterResponseSerializer
+where
+    T: IntoIterator,
+    T::IntoIter: 'static,
+    T::Item: Serialize,
+    W: Write,
+{
+    fn serialize(
+        _runtime: &ConjureRuntime,
+        request_headers: &HeaderMap,
+        value: T,
+    ) -> Result<Response<ResponseBody<W>>, Error> {
+        let encoding = Encoding::negotiate(request_headers);
+        let body = IterBody {
+            encoding,
+            it: value.into_iter(),
+        };
+
+        let mut response = Response::new(ResponseBody::Streaming(Box::new(body)));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, encoding.content_type());
+        Ok(response)
     }
 }
 
+struct IterBody<I> {
+    encoding: Encoding,
+    it: I,
+}
+
+impl<I, W> WriteBody<W> for IterBody<I>
+where
+    I: Iterator,
+    I::Item: Serialize,
+    W: Write,
+{
+    fn write_body(self: Box<Self>, w: &mut W) -> Result<(), Error> {
+        match self.encoding {
+            Encoding::Json => write_seq(&mut json::Serializer::new(w), self.it),
+            Encoding::Smile => write_seq(&mut smile::Serializer::new(w), self.it),
+        }
+    }
+}
+
+fn write_seq<S, I>(serializer: S, it: I) -> Result<(), Error>
+where
+    S: Serializer<Ok = ()>,
+    S::Error: Into<Box<dyn error::Error + Sync + Send>>,
+    I: Iterator,
+    I::Item: Serialize,
+{
+    let mut seq = serializer.serialize_seq(None).map_err(Error::internal)?;
+    for item in it {
+        seq.serialize_element(&item).map_err(Error::internal)?;
+    }
+    seq.end().map_err(Error::internal)
+}
+
+/// An async response serializer which incrementally writes the items of a stream as an array.
+///
+/// It behaves like [`IterResponseSerializer`], but consumes a [`Stream`] and sends the encoded array to a [`Sink`] of
+/// [`Bytes`] in chunks.
+pub enum StreamResponseSerializer {}
+
+impl<T, W> AsyncSerializeResponse<T, W> for StreamResponseSerializer
+where
+    T: Stream + 'static + Send,
+    T::Item: Serialize + Send,
+    W: Sink<Bytes, Error = Error> + Send,
+{
+    fn serialize(
+        _runtime: &ConjureRuntime,
+        request_headers: &HeaderMap,
+        value: T,
+    ) -> Result<Response<AsyncResponseBody<W>>, Error> {
+        let encoding = Encoding::negotiate(request_headers);
+        let body = StreamBody {
+            encoding,
+            stream: value,
+        };
+
+        let mut response =
+            Response::new(AsyncResponseBody::Streaming(BoxAsyncWriteBody::new(body)));
+        response
+            .headers_mut()
+            .insert(CONTENT_TYPE, encoding.content_type());
+        Ok(response)
+    }
+}
+
+struct StreamBody<S> {
+    encoding: Encoding,
+    stream: S,
+}
+
+impl<S, W> AsyncWriteBody<W> for StreamBody<S>
+where
+    S: Stream + Send,
+    S::Item: Serialize + Send,
+    W: Sink<Bytes, Error = Error> + Send,
+{
+    async fn write_body(self, mut w: Pin<&mut W>) -> Result<(), Error> {
+        let stream = self.stream;
+        pin_utils::pin_mut!(stream);
+
+        let mut encoder = ArrayEncoder::new(self.encoding)?;
+        while let Some(item) = stream.next().await {
+            encoder.push(&item)?;
+            if encoder.buf().len() >= STREAM_FLUSH_THRESHOLD {
+                w.as_mut().send(encoder.take()).await?;
+            }
+        }
+        w.send(encoder.finish()).await
+    }
+}
+
+const STREAM_FLUSH_THRESHOLD: usize = 8 * 1024;
+
+const SMILE_START_ARRAY: u8 = 0xf8;
+const SMILE_END_ARRAY: u8 = 0xf9;
+
+// Serde serializers are synchronous, so the array delimiters are written by hand and each element is encoded on its
+// own into a buffer which is periodically flushed to the writer.
+enum ArrayEncoder {
+    Json { buf: Vec<u8>, empty: bool },
+    // A single serializer is used for the whole array so that Smile shared string references stay valid.
+    Smile(smile::Serializer<Vec<u8>>),
+}
+
+impl ArrayEncoder {
+    fn new(encoding: Encoding) -> Result<Self, Error> {
+        match encoding {
+            Encoding::Json => Ok(ArrayEncoder::Json {
+                buf: vec![b'['],
+                empty: true,
+            }),
+            Encoding::Smile => {
+                let mut serializer = smile::Serializer::new(vec![]);
+                serializer.write_header().map_err(Error::internal)?;
+                serializer.get_mut().push(SMILE_START_ARRAY);
+                Ok(ArrayEncoder::Smile(serializer))
+            }
+        }
+    }
+
+    fn push<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize,
+    {
+        match self {
+            ArrayEncoder::Json { buf, empty } => {
+                if !mem::replace(empty, false) {
+                    buf.push(b',');
+                }
+                json::to_writer(&mut *buf, value).map_err(Error::internal)
+            }
+            ArrayEncoder::Smile(serializer) => value.serialize(serializer).map_err(Error::internal),
+        }
+    }
+
+    fn buf(&mut self) -> &mut Vec<u8> {
+        match self {
+            ArrayEncoder::Json { buf, .. } => buf,
+            ArrayEncoder::Smile(serializer) => serializer.get_mut(),
+        }
+    }
+
+    fn take(&mut self) -> Bytes {
+        Bytes::from(mem::take(self.buf()))
+    }
+
+    fn finish(mut self) -> Bytes {
+        let end = match self {
+            ArrayEncoder::Json { .. } => b']',
+            ArrayEncoder::Smile(_) => SMILE_END_ARRAY,
+        };
+        self.buf().push(end);
+        self.take()
+    }
+}
+
+impl Encoding {
+    fn content_type(self) -> HeaderValue {
+        match self {
+            Encoding::Json => APPLICATION_JSON,
+            Encoding::Smile => APPLICATION_SMILE,
+        }
+    }
+
+    // Picks the encoding with the highest quality value in the request's Accept header, preferring
+    // whichever is listed first on ties. JSON is used if the header is missing or names neither.
+    fn negotiate(request_headers: &HeaderMap) -> Encoding {
+        let mut best = None::<(Encoding, f32)>;
+
+        let ranges = request_headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','));
+
+        for range in ranges {
+            let mut parts = range.split(';');
+            let media_type = parts.next().unwrap_or("").trim();
+            let encoding = if media_type.eq_ignore_ascii_case("application/x-jackson-smile") {
+                Encoding::Smile
+            } else if media_type.eq_ignore_ascii_case("application/json")
+                || media_type.eq_ignore_ascii_case("application/*")
+                || media_type.eq_ignore_ascii_case("*/*")
+            {
+                Encoding::Json
+            } else {
+                continue;
+            };
+
+            let quality = parts
+                .filter_map(|p| p.split_once('='))
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, v)| v.trim().parse::<f32>().ok())
+                .unwrap_or(1.);
+            if quality <= 0. {
+                continue;
+            }
+
+            match best {
+                Some((_, q)) if q >= quality => {}
+                _ => best = Some((encoding, quality)),
+            }
+        }
+
+        best.map_or(Encoding::Json, |(encoding, _)| encoding)
+    }
+}
+
+/// Creates the response corresponding to an error returned by an endpoint.
+///
+/// Service errors use the status code of their [`ErrorCode`](conjure_error::ErrorCode) and a JSON body containing
+/// their serialized form. Throttle errors produce a `429 Too Many Requests` response with a `Retry-After` header if
+/// the error has a duration, and unavailable errors produce a `503 Service Unavailable` response.
+pub fn error_response<W>(error: &Error) -> Response<ResponseBody<W>> {
+    error_response_inner(error, ResponseBody::Empty, ResponseBody::Fixed)
+}
+
+/// Creates the async response corresponding to an error returned by an endpoint.
+///
+/// It behaves identically to [`error_response`].
+pub fn async_error_response<W>(error: &Error) -> Response<AsyncResponseBody<W>> {
+    error_response_inner(error, AsyncResponseBody::Empty, AsyncResponseBody::Fixed)
+}
+
+fn error_response_inner<B>(
+    error: &Error,
+    empty: B,
+    make_body: impl FnOnce(Bytes) -> B,
+) -> Response<B> {
+    let encoded;
+    let error = match error.kind() {
+        ErrorKind::Service(error) => error,
+        ErrorKind::Throttle(error) => {
+            let mut response = Response::new(empty);
+            *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+            if let Some(duration) = error.duration() {
+                let secs = duration.as_secs() + u64::from(duration.subsec_nanos() > 0);
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(secs));
+            }
+            return response;
+        }
+        ErrorKind::Unavailable(_) => {
+            let mut response = Response::new(empty);
+            *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+            return response;
+        }
+        _ => {
+            encoded = conjure_error::encode(&Internal::new());
+            &encoded
+        }
+    };
+
+    let body = json::to_vec(error).expect("error serialization is infallible");
+
+    let mut response = Response::new(make_body(Bytes::from(body)));
+    *response.status_mut() = StatusCode::from_u16(error.error_code().status_code())
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, APPLICATION_JSON);
+
+    response
+}
+
 /// A trait implemented by header decoders used by custom Conjure server trait implementations.
 pub trait DecodeHeader<T> {
     /// Decodes the value from headers.
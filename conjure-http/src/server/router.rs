@@ -0,0 +1,510 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Request routing across the endpoints of Conjure services.
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use conjure_error::{Error, NotFound};
+use http::header::ALLOW;
+use http::{Extensions, HeaderValue, Method, Request, Response, StatusCode};
+use regex::Regex;
+
+use crate::PathParams;
+
+use super::{
+    async_error_response, error_response, AsyncEndpoint, AsyncResponseBody, AsyncService,
+    BoxAsyncEndpoint, ConjureRuntime, Endpoint, EndpointMetadata, PathSegment, ResponseBody,
+    Service,
+};
+
+/// The result of looking up a request's method and path in a router.
+pub enum Route<'a, E> {
+    /// An endpoint matched the request.
+    Found {
+        /// The matched endpoint.
+        endpoint: &'a E,
+        /// The raw, encoded, path parameters extracted from the request path.
+        path_params: PathParams,
+    },
+    /// Endpoints matched the request path, but none of them handle the request's method.
+    MethodNotAllowed {
+        /// The methods supported by the endpoints matching the request path.
+        allowed: Vec<Method>,
+    },
+    /// No endpoint matched the request path.
+    NotFound,
+}
+
+/// An error returned when building a router.
+#[derive(Debug)]
+pub struct RouterError(pub(crate) String);
+
+impl fmt::Display for RouterError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&self.0)
+    }
+}
+
+impl error::Error for RouterError {}
+
+/// A router dispatching requests to the endpoints of blocking Conjure services.
+///
+/// Endpoints are matched by their [`EndpointMetadata::path`] and [`EndpointMetadata::method`]. Literal path segments
+/// take precedence over parameters, and parameters with a regex only match segments matching it in full. A regex
+/// parameter in the final segment of a path is matched against the entire remainder of the request path, so
+/// `{name:.+}` can be used to capture multiple segments.
+pub struct Router<I, O> {
+    routes: Routes<Box<dyn Endpoint<I, O> + Sync + Send>>,
+}
+
+impl<I, O> Router<I, O> {
+    /// Returns a builder used to construct a router.
+    pub fn builder() -> RouterBuilder<I, O> {
+        RouterBuilder {
+            runtime: None,
+            services: vec![],
+            endpoints: vec![],
+        }
+    }
+
+    /// Looks up the endpoint handling a request with the given method and path.
+    pub fn route(
+        &self,
+        method: &Method,
+        path: &str,
+    ) -> Route<'_, Box<dyn Endpoint<I, O> + Sync + Send>> {
+        self.routes.route(method, path)
+    }
+
+    /// Handles a request.
+    ///
+    /// A [`PathParams`] extension is added to the request before it is passed to the matched endpoint, and any error it
+    /// returns is passed through. Requests which fail to route are answered directly rather than with an error, since a
+    /// `405` can't be represented as a service error: if no endpoint matches the request path, a `404 Not Found`
+    /// response with a serialized `NotFound` service error is returned, and if endpoints match the path but not the
+    /// method, a `405 Method Not Allowed` response with an `Allow` header is returned.
+    pub fn handle(
+        &self,
+        mut req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        match self.route(req.method(), req.uri().path()) {
+            Route::Found {
+                endpoint,
+                path_params,
+            } => {
+                req.extensions_mut().insert(path_params);
+                endpoint.handle(req, response_extensions)
+            }
+            Route::MethodNotAllowed { allowed } => {
+                Ok(method_not_allowed(&allowed, ResponseBody::Empty))
+            }
+            Route::NotFound => Ok(error_response(&not_found())),
+        }
+    }
+}
+
+/// A builder for [`Router`]s.
+pub struct RouterBuilder<I, O> {
+    runtime: Option<Arc<ConjureRuntime>>,
+    services: Vec<Box<dyn Service<I, O>>>,
+    endpoints: Vec<Box<dyn Endpoint<I, O> + Sync + Send>>,
+}
+
+impl<I, O> RouterBuilder<I, O> {
+    /// Sets the runtime passed to services when creating their endpoints.
+    ///
+    /// Defaults to a runtime with default settings.
+    pub fn runtime(mut self, runtime: Arc<ConjureRuntime>) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Adds all of the endpoints of a service to the router.
+    pub fn service<S>(mut self, service: S) -> Self
+    where
+        S: Service<I, O> + 'static,
+    {
+        self.services.push(Box::new(service));
+        self
+    }
+
+    /// Adds a single endpoint to the router.
+    pub fn endpoint<E>(mut self, endpoint: E) -> Self
+    where
+        E: Endpoint<I, O> + Sync + Send + 'static,
+    {
+        self.endpoints.push(Box::new(endpoint));
+        self
+    }
+
+    /// Builds the router.
+    ///
+    /// Returns an error if two endpoints handle the same method and path, or if a path parameter's regex is invalid.
+    pub fn build(self) -> Result<Router<I, O>, RouterError> {
+        let runtime = self.runtime.unwrap_or_default();
+        let endpoints = self
+            .services
+            .iter()
+            .flat_map(|s| s.endpoints(&runtime))
+            .chain(self.endpoints)
+            .collect();
+
+        Ok(Router {
+            routes: Routes::new(endpoints)?,
+        })
+    }
+}
+
+/// A router dispatching requests to the endpoints of async Conjure services.
+///
+/// It follows the same matching rules as [`Router`].
+pub struct AsyncRouter<I, O> {
+    routes: Routes<BoxAsyncEndpoint<'static, I, O>>,
+}
+
+impl<I, O> AsyncRouter<I, O> {
+    /// Returns a builder used to construct a router.
+    pub fn builder() -> AsyncRouterBuilder<I, O> {
+        AsyncRouterBuilder {
+            runtime: None,
+            services: vec![],
+            endpoints: vec![],
+        }
+    }
+
+    /// Looks up the endpoint handling a request with the given method and path.
+    pub fn route(&self, method: &Method, path: &str) -> Route<'_, BoxAsyncEndpoint<'static, I, O>> {
+        self.routes.route(method, path)
+    }
+
+    /// Handles a request.
+    ///
+    /// A [`PathParams`] extension is added to the request before it is passed to the matched endpoint, and any error it
+    /// returns is passed through. Requests which fail to route are answered directly rather than with an error, since a
+    /// `405` can't be represented as a service error: if no endpoint matches the request path, a `404 Not Found`
+    /// response with a serialized `NotFound` service error is returned, and if endpoints match the path but not the
+    /// method, a `405 Method Not Allowed` response with an `Allow` header is returned.
+    pub async fn handle(
+        &self,
+        mut req: Request<I>,
+        response_extensions: &mut Extensions,
+    ) -> Result<Response<AsyncResponseBody<O>>, Error>
+    where
+        I: Send,
+    {
+        match self.route(req.method(), req.uri().path()) {
+            Route::Found {
+                endpoint,
+                path_params,
+            } => {
+                req.extensions_mut().insert(path_params);
+                endpoint.handle(req, response_extensions).await
+            }
+            Route::MethodNotAllowed { allowed } => {
+                Ok(method_not_allowed(&allowed, AsyncResponseBody::Empty))
+            }
+            Route::NotFound => Ok(async_error_response(&not_found())),
+        }
+    }
+}
+
+/// A builder for [`AsyncRouter`]s.
+pub struct AsyncRouterBuilder<I, O> {
+    runtime: Option<Arc<ConjureRuntime>>,
+    services: Vec<Box<dyn AsyncService<I, O>>>,
+    endpoints: Vec<BoxAsyncEndpoint<'static, I, O>>,
+}
+
+impl<I, O> AsyncRouterBuilder<I, O> {
+    /// Sets the runtime passed to services when creating their endpoints.
+    ///
+    /// Defaults to a runtime with default settings.
+    pub fn runtime(mut self, runtime: Arc<ConjureRuntime>) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Adds all of the endpoints of a service to the router.
+    pub fn service<S>(mut self, service: S) -> Self
+    where
+        S: AsyncService<I, O> + 'static,
+    {
+        self.services.push(Box::new(service));
+        self
+    }
+
+    /// Adds a single endpoint to the router.
+    pub fn endpoint<E>(mut self, endpoint: E) -> Self
+    where
+        E: AsyncEndpoint<I, O> + Sync + Send + 'static,
+    {
+        self.endpoints.push(BoxAsyncEndpoint::new(endpoint));
+        self
+    }
+
+    /// Builds the router.
+    ///
+    /// Returns an error if two endpoints handle the same method and path, or if a path parameter's regex is invalid.
+    pub fn build(self) -> Result<AsyncRouter<I, O>, RouterError> {
+        let runtime = self.runtime.unwrap_or_default();
+        let endpoints = self
+            .services
+            .iter()
+            .flat_map(|s| s.endpoints(&runtime))
+            .chain(self.endpoints)
+            .collect();
+
+        Ok(AsyncRouter {
+            routes: Routes::new(endpoints)?,
+        })
+    }
+}
+
+pub(crate) fn method_not_allowed<B>(allowed: &[Method], body: B) -> Response<B> {
+    let allow = allowed
+        .iter()
+        .map(|m| m.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut response = Response::new(body);
+    *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+    response
+        .headers_mut()
+        .insert(ALLOW, HeaderValue::from_str(&allow).unwrap());
+    response
+}
+
+pub(crate) fn not_found() -> Error {
+    Error::service_safe("no endpoint matched the request path", NotFound::new())
+}
+
+pub(crate) struct Routes<E> {
+    endpoints: Vec<E>,
+    root: Node,
+}
+
+impl<E> Routes<E>
+where
+    E: EndpointMetadata,
+{
+    pub(crate) fn new(endpoints: Vec<E>) -> Result<Self, RouterError> {
+        let mut root = Node::default();
+
+        for (index, endpoint) in endpoints.iter().enumerate() {
+            let mut node = &mut root;
+            let mut params = vec![];
+
+            let path = endpoint.path();
+            for (i, segment) in path.iter().enumerate() {
+                node = match segment {
+                    PathSegment::Literal(literal) => {
+                        node.literals.entry(literal.to_string()).or_default()
+                    }
+                    PathSegment::Parameter { name, regex } => {
+                        params.push(name.to_string());
+                        node.param_child(regex.as_deref(), i == path.len() - 1)
+                            .map_err(|e| {
+                                RouterError(format!(
+                                    "invalid regex for parameter `{}` of endpoint {}.{}: {}",
+                                    name,
+                                    endpoint.service_name(),
+                                    endpoint.name(),
+                                    e,
+                                ))
+                            })?
+                    }
+                };
+            }
+
+            let method = endpoint.method();
+            if let Some(existing) = node.leaves.iter().find(|l| l.method == method) {
+                let existing = &endpoints[existing.endpoint];
+                return Err(RouterError(format!(
+                    "endpoints {}.{} ({} {}) and {}.{} ({} {}) conflict",
+                    existing.service_name(),
+                    existing.name(),
+                    existing.method(),
+                    existing.template(),
+                    endpoint.service_name(),
+                    endpoint.name(),
+                    method,
+                    endpoint.template(),
+                )));
+            }
+
+            node.leaves.push(Leaf {
+                method,
+                endpoint: index,
+                params,
+            });
+        }
+
+        Ok(Routes { endpoints, root })
+    }
+
+    pub(crate) fn route(&self, method: &Method, path: &str) -> Route<'_, E> {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let segments = path.split('/').collect::<Vec<_>>();
+
+        let mut captures = vec![];
+        let mut allowed = vec![];
+        match self
+            .root
+            .find(method, &segments, &mut captures, &mut allowed)
+        {
+            Some((leaf, captures)) => {
+                let mut path_params = PathParams::new();
+                for (name, value) in leaf.params.iter().zip(captures) {
+                    path_params.insert(&**name, value);
+                }
+
+                Route::Found {
+                    endpoint: &self.endpoints[leaf.endpoint],
+                    path_params,
+                }
+            }
+            None if allowed.is_empty() => Route::NotFound,
+            None => {
+                allowed.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+                allowed.dedup();
+                Route::MethodNotAllowed { allowed }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Node {
+    literals: HashMap<String, Node>,
+    params: Vec<ParamNode>,
+    leaves: Vec<Leaf>,
+}
+
+impl Node {
+    fn param_child(&mut self, regex: Option<&str>, last: bool) -> Result<&mut Node, regex::Error> {
+        // Regex parameters in the final segment consume the remainder of the path.
+        let rest = last && regex.is_some();
+        let is_same = |p: &ParamNode| p.rest == rest && p.regex_str() == regex;
+
+        if !self.params.iter().any(is_same) {
+            let regex = match regex {
+                Some(regex) => Some((regex.to_string(), Regex::new(&format!("^(?:{})$", regex))?)),
+                None => None,
+            };
+            self.params.push(ParamNode {
+                regex,
+                rest,
+                node: Node::default(),
+            });
+            // Plain parameters are checked before those with a regex, and single-segment parameters before those
+            // consuming the rest of the path.
+            self.params.sort_by_key(|p| (p.rest, p.regex.is_some()));
+        }
+
+        Ok(&mut self.params.iter_mut().find(|p| is_same(p)).unwrap().node)
+    }
+
+    fn find<'a>(
+        &'a self,
+        method: &Method,
+        segments: &[&str],
+        captures: &mut Vec<String>,
+        allowed: &mut Vec<Method>,
+    ) -> Option<(&'a Leaf, Vec<String>)> {
+        let Some((first, rest)) = segments.split_first() else {
+            return self.leaf(method, captures, allowed);
+        };
+
+        if let Some(node) = self.literals.get(*first) {
+            if let Some(found) = node.find(method, rest, captures, allowed) {
+                return Some(found);
+            }
+        }
+
+        for param in &self.params {
+            if param.rest {
+                let remainder = segments.join("/");
+                if !param.matches(&remainder) {
+                    continue;
+                }
+
+                captures.push(remainder);
+                let found = param.node.leaf(method, captures, allowed);
+                captures.pop();
+                if found.is_some() {
+                    return found;
+                }
+            } else {
+                if !param.matches(first) {
+                    continue;
+                }
+
+                captures.push(first.to_string());
+                let found = param.node.find(method, rest, captures, allowed);
+                captures.pop();
+                if found.is_some() {
+                    return found;
+                }
+            }
+        }
+
+        None
+    }
+
+    fn leaf(
+        &self,
+        method: &Method,
+        captures: &[String],
+        allowed: &mut Vec<Method>,
+    ) -> Option<(&Leaf, Vec<String>)> {
+        match self.leaves.iter().find(|l| l.method == *method) {
+            Some(leaf) => Some((leaf, captures.to_vec())),
+            None => {
+                allowed.extend(self.leaves.iter().map(|l| l.method.clone()));
+                None
+            }
+        }
+    }
+}
+
+struct ParamNode {
+    regex: Option<(String, Regex)>,
+    rest: bool,
+    node: Node,
+}
+
+impl ParamNode {
+    fn regex_str(&self) -> Option<&str> {
+        self.regex.as_ref().map(|(s, _)| &**s)
+    }
+
+    fn matches(&self, value: &str) -> bool {
+        match &self.regex {
+            Some((_, regex)) => regex.is_match(value),
+            None => !value.is_empty(),
+        }
+    }
+}
+
+struct Leaf {
+    method: Method,
+    endpoint: usize,
+    params: Vec<String>,
+}
@@ -231,10 +231,15 @@ fn create_request(
         Asyncness::Async => quote!(AsyncSerializeRequest),
     };
 
-    let serializer = arg.attr.serializer.as_ref().map_or_else(
-        || quote!(conjure_http::client::ConjureRequestSerializer),
-        |t| quote!(#t),
-    );
+    let serializer = arg
+        .attr
+        .serializer
+        .as_ref()
+        .or(service.serializer.as_ref())
+        .map_or_else(
+            || quote!(conjure_http::client::ConjureRequestSerializer),
+            |t| quote!(#t),
+        );
     let ident = &arg.ident;
 
     quote! {
@@ -304,7 +309,7 @@ fn add_path_components(builder: &TokenStream, endpoint: &Endpoint) -> TokenStrea
                     &percent_encoding::percent_encode(lit.as_bytes(), COMPONENT).to_string(),
                 );
             }
-            PathComponent::Parameter(param) => {
+            PathComponent::Parameter { name: param, .. } => {
                 if !literal_buf.is_empty() {
                     path_writes.push(quote! {
                         #builder.push_literal(#literal_buf);
@@ -494,6 +499,7 @@ struct Service {
     vis: Visibility,
     name: LitStr,
     version: TokenStream,
+    serializer: Option<Type>,
     trait_name: Ident,
     generics: Generics,
     request_writer_param: Option<Ident>,
@@ -513,6 +519,7 @@ impl Service {
                 ServiceParams {
                     name: None,
                     version: None,
+                    serializer: None,
                 }
             }
         };
@@ -568,6 +575,7 @@ impl Service {
             vis: trait_.vis.clone(),
             name,
             version,
+            serializer: service_params.serializer,
             trait_name: trait_.ident.clone(),
             generics: trait_.generics.clone(),
             request_writer_param,
@@ -763,7 +771,7 @@ fn validate_args(
             .collect::<HashMap<_, _>>();
 
         for component in path_components {
-            let PathComponent::Parameter(param) = component else {
+            let PathComponent::Parameter { name: param, .. } = component else {
                 continue;
             };
 
@@ -787,6 +795,7 @@ fn validate_args(
 struct ServiceParams {
     name: Option<LitStr>,
     version: Option<Expr>,
+    serializer: Option<Type>,
 }
 
 #[derive(StructMeta)]
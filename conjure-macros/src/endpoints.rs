@@ -4,10 +4,12 @@ use heck::ToUpperCamelCase;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use structmeta::StructMeta;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{
-    parse_macro_input, Error, FnArg, GenericParam, Generics, ItemTrait, LitStr, Meta, Pat, PatType,
-    ReturnType, TraitItem, TraitItemFn, Type, Visibility,
+    bracketed, parse_macro_input, Error, FnArg, GenericParam, Generics, ItemTrait, LitStr, Meta,
+    Pat, PatType, ReturnType, Token, TraitItem, TraitItemFn, Type, Visibility,
 };
 
 pub fn generate(
@@ -222,11 +224,17 @@ fn generate_endpoint_metadata(service: &Service, endpoint: &Endpoint) -> TokenSt
                 )
             }
         }
-        PathComponent::Parameter(param) => {
+        PathComponent::Parameter { name, regex } => {
+            let regex = match regex {
+                Some(regex) => quote! {
+                    conjure_http::private::Option::Some(conjure_http::private::Cow::Borrowed(#regex))
+                },
+                None => quote!(conjure_http::private::Option::None),
+            };
             quote! {
                 conjure_http::server::PathSegment::Parameter {
-                    name: conjure_http::private::Cow::Borrowed(#param),
-                    regex: conjure_http::private::Option::None,
+                    name: conjure_http::private::Cow::Borrowed(#name),
+                    regex: #regex,
                 }
             }
         }
@@ -240,6 +248,8 @@ fn generate_endpoint_metadata(service: &Service, endpoint: &Endpoint) -> TokenSt
             quote!(#name)
         }
     };
+    let tags = endpoint.params.tags.iter().flat_map(|t| &t.0);
+    let markers = endpoint.params.markers.iter().flat_map(|m| &m.0);
 
     quote! {
         impl<T> conjure_http::server::EndpointMetadata for #struct_name<T> {
@@ -266,6 +276,14 @@ fn generate_endpoint_metadata(service: &Service, endpoint: &Endpoint) -> TokenSt
             fn deprecated(&self) -> conjure_http::private::Option<&str> {
                 conjure_http::private::Option::None
             }
+
+            fn tags(&self) -> &[&str] {
+                &[#(#tags,)*]
+            }
+
+            fn markers(&self) -> &[&str] {
+                &[#(#markers,)*]
+            }
         }
     }
 }
@@ -322,7 +340,15 @@ fn generate_endpoint_handler(service: &Service, endpoint: &Endpoint) -> TokenStr
         quote!()
     };
 
-    let generate_args = endpoint.args.iter().map(|arg| {
+    // The body deserializer mutably borrows the request parts, so it runs after the other args are parsed and before
+    // the context is created.
+    let mut args = endpoint.args.iter().collect::<Vec<_>>();
+    args.sort_by_key(|arg| match arg {
+        ArgType::Body(_) => 1,
+        ArgType::Context(_) => 2,
+        _ => 0,
+    });
+    let generate_args = args.into_iter().map(|arg| {
         generate_arg(
             &parts,
             &body,
@@ -343,6 +369,12 @@ fn generate_endpoint_handler(service: &Service, endpoint: &Endpoint) -> TokenStr
 
     let generate_response = generate_response(&parts, &response, service, endpoint);
 
+    let mut_ = if has_body(endpoint) {
+        quote!(mut)
+    } else {
+        quote!()
+    };
+
     quote! {
         impl #impl_generics conjure_http::server::#endpoint_trait<#request_body, #response_writer> for #struct_name<#trait_impl>
         #where_clause
@@ -356,7 +388,7 @@ fn generate_endpoint_handler(service: &Service, endpoint: &Endpoint) -> TokenStr
                 conjure_http::private::Error,
             >
             {
-                let (#parts, #body) = #request.into_parts();
+                let (#mut_ #parts, #body) = #request.into_parts();
                 #generate_query_params
                 #generate_safe_params
                 #(#generate_args)*
@@ -371,6 +403,10 @@ fn has_safe_params(endpoint: &Endpoint) -> bool {
     endpoint.args.iter().any(|a| a.safe())
 }
 
+fn has_body(endpoint: &Endpoint) -> bool {
+    endpoint.args.iter().any(|a| matches!(a, ArgType::Body(_)))
+}
+
 fn has_query_params(endpoint: &Endpoint) -> bool {
     endpoint.args.iter().any(|a| matches!(a, ArgType::Query(_)))
 }
@@ -504,7 +540,7 @@ fn generate_body_arg(
     quote! {
         let #name = conjure_http::private::#function::<#deserializer, _, _>(
             &self.runtime,
-            &#parts.headers,
+            &mut #parts,
             #body,
             #log_as,
         ) #await_ ?;
@@ -762,6 +798,19 @@ struct EndpointParams {
     path: LitStr,
     name: Option<LitStr>,
     produces: Option<Type>,
+    tags: Option<LitStrArray>,
+    markers: Option<LitStrArray>,
+}
+
+struct LitStrArray(Vec<LitStr>);
+
+impl Parse for LitStrArray {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let content;
+        bracketed!(content in input);
+        let values = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+        Ok(LitStrArray(values.into_iter().collect()))
+    }
 }
 
 enum ArgType {
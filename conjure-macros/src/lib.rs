@@ -50,6 +50,8 @@ mod path;
 ///     name.
 /// * `version` - The value of the `version` field in the `Endpoint` extension. Defaults to
 ///     `Some(env!("CARGO_PKG_VERSION"))`.
+/// * `serializer` - A type implementing `SerializeRequest` used by `#[body]` parameters which don't
+///     specify their own, e.g. `SmileRequestSerializer`. Defaults to `ConjureRequestSerializer`.
 ///
 /// # Parameters
 ///
@@ -100,7 +102,7 @@ mod path;
 ///
 ///     Parameters:
 ///     * `serializer` - A type implementing `SerializeRequest` which will be used to serialize the
-///         value into a body. Defaults to `ConjureRequestSerializer`.
+///         value into a body. Defaults to the service's `serializer`.
 ///
 /// # Async
 ///
@@ -256,11 +258,13 @@ pub fn conjure_client(attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// * `method` - The HTTP method (e.g. `GET`). Required.
 /// * `path` - The HTTP path template. Path parameters should be identified by `{name}` and must
-///     make up an entire path component. Required.
+///     make up an entire path component. A regex may be given as `{name:regex}`. Required.
 /// * `name` - The value returned from the `EndpointMetadata::name` method. Defaults to the method
 ///     name.
 /// * `produces` - A type implementing `SerializeResponse` which will be used to convert the value
 ///     returned by the method into a response. Defaults to `EmptyResponseSerializer`.
+/// * `tags` - An array of the values returned by `EndpointMetadata::tags`. Defaults to empty.
+/// * `markers` - An array of the values returned by `EndpointMetadata::markers`. Defaults to empty.
 ///
 /// Each method argument must have an annotation describing the type of parameter. One of:
 ///
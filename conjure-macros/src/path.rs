@@ -16,7 +16,7 @@ use syn::{Error, LitStr};
 
 pub enum PathComponent {
     Literal(String),
-    Parameter(String),
+    Parameter { name: String, regex: Option<String> },
 }
 
 pub fn parse(path_lit: &LitStr) -> Result<Vec<PathComponent>, Error> {
@@ -33,14 +33,21 @@ pub fn parse(path_lit: &LitStr) -> Result<Vec<PathComponent>, Error> {
         ));
     };
 
-    let components = path
-        .split('/')
+    let components = split(path)
+        .ok_or_else(|| Error::new_spanned(path_lit, "unbalanced `{` or `}` in path"))?
+        .into_iter()
         .map(|component| {
             match component
                 .strip_prefix('{')
                 .and_then(|c| c.strip_suffix('}'))
             {
-                Some(parameter) => PathComponent::Parameter(parameter.to_string()),
+                Some(parameter) => {
+                    let mut it = parameter.splitn(2, ':');
+                    PathComponent::Parameter {
+                        name: it.next().unwrap().to_string(),
+                        regex: it.next().map(|s| s.to_string()),
+                    }
+                }
                 None => PathComponent::Literal(component.to_string()),
             }
         })
@@ -48,3 +55,27 @@ pub fn parse(path_lit: &LitStr) -> Result<Vec<PathComponent>, Error> {
 
     Ok(components)
 }
+
+// Splits on `/` outside of braces so that a parameter's regex can itself contain `/`.
+fn split(path: &str) -> Option<Vec<&str>> {
+    let mut components = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in path.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.checked_sub(1)?,
+            '/' if depth == 0 => {
+                components.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return None;
+    }
+    components.push(&path[start..]);
+
+    Some(components)
+}
@@ -14,16 +14,24 @@
 //! The Conjure `any` type.
 
 use crate::any::ser::AnySerializer;
+use crate::SafeLong;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
 use ordered_float::OrderedFloat;
 use serde::de::{DeserializeOwned, Unexpected};
 use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::error;
 use std::f64;
 use std::fmt;
 
 mod de;
 mod ser;
+#[cfg(test)]
+mod test;
 
 /// An error serializing to or from an `Any` value.
 #[derive(Debug)]
@@ -81,9 +89,13 @@ enum Inner {
 /// A representation of an arbitrary serializable value, corresponding to the Conjure `any` type.
 ///
 /// The type is designed to be a lossless representation of a Conjure JSON value and follows Conjure's specifications
-/// regarding various edge cases such as base64 encoded binary values and non-finite floats. Its internal structure is
-/// opaque. Values can be converted to and from it with the `Any::new` and `Any::deserialize_into` methods, and it can
-/// be deserialized to and from JSON via its `Serialize` and `Deserialize` implementations.
+/// regarding various edge cases such as base64 encoded binary values and non-finite floats. Values can be converted to
+/// and from it with the `Any::new` and `Any::deserialize_into` methods, and it can be deserialized to and from JSON via
+/// its `Serialize` and `Deserialize` implementations.
+///
+/// The contents of an `Any` can be inspected directly with methods like `Any::kind`, `Any::as_str`, and
+/// `Any::pointer`. Values can be constructed without going through serde with the `From` implementations and the
+/// `Any::seq` and `Any::map` builders.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Any(Inner);
 
@@ -115,6 +127,191 @@ impl Any {
         T::deserialize(self)
     }
 
+    /// Returns a null `Any`.
+    pub fn null() -> Any {
+        Any(Inner::Null)
+    }
+
+    /// Returns an `Any` containing binary data.
+    ///
+    /// Like other binary values, it will be serialized as a base64 encoded string in JSON.
+    pub fn binary<T>(value: T) -> Any
+    where
+        T: Into<Vec<u8>>,
+    {
+        Any(Inner::Bytes(value.into()))
+    }
+
+    /// Returns a builder for an `Any` containing a sequence of values.
+    pub fn seq() -> SeqBuilder {
+        SeqBuilder { values: vec![] }
+    }
+
+    /// Returns a builder for an `Any` containing a map of values.
+    pub fn map() -> MapBuilder {
+        MapBuilder {
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the kind of value contained in the `Any`.
+    pub fn kind(&self) -> AnyKind {
+        match &self.0 {
+            Inner::Null => AnyKind::Null,
+            Inner::Bool(_) => AnyKind::Bool,
+            Inner::I8(_)
+            | Inner::I16(_)
+            | Inner::I32(_)
+            | Inner::I64(_)
+            | Inner::I128(_)
+            | Inner::U8(_)
+            | Inner::U16(_)
+            | Inner::U32(_)
+            | Inner::U64(_)
+            | Inner::U128(_) => AnyKind::Integer,
+            Inner::F32(_) | Inner::F64(_) => AnyKind::Float,
+            Inner::Char(_) => AnyKind::Char,
+            Inner::String(_) => AnyKind::String,
+            Inner::Bytes(_) => AnyKind::Binary,
+            Inner::Seq(_) => AnyKind::Seq,
+            Inner::Map(_) => AnyKind::Map,
+        }
+    }
+
+    /// Returns `true` if the `Any` is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self.0, Inner::Null)
+    }
+
+    /// Returns the boolean contained in the `Any`, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.0 {
+            Inner::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the string contained in the `Any`, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.0 {
+            Inner::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer contained in the `Any`, if it is one representable as an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.0 {
+            Inner::I8(v) => Some(i64::from(v)),
+            Inner::I16(v) => Some(i64::from(v)),
+            Inner::I32(v) => Some(i64::from(v)),
+            Inner::I64(v) => Some(v),
+            Inner::I128(v) => i64::try_from(v).ok(),
+            Inner::U8(v) => Some(i64::from(v)),
+            Inner::U16(v) => Some(i64::from(v)),
+            Inner::U32(v) => Some(i64::from(v)),
+            Inner::U64(v) => i64::try_from(v).ok(),
+            Inner::U128(v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number contained in the `Any` as an `f64`, if it is one.
+    ///
+    /// Integers are converted to the nearest `f64`. Following Conjure's JSON representation of non-finite floats, the
+    /// strings `"NaN"`, `"Infinity"`, and `"-Infinity"` are also accepted.
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.0 {
+            Inner::I8(v) => Some(f64::from(*v)),
+            Inner::I16(v) => Some(f64::from(*v)),
+            Inner::I32(v) => Some(f64::from(*v)),
+            Inner::I64(v) => Some(*v as f64),
+            Inner::I128(v) => Some(*v as f64),
+            Inner::U8(v) => Some(f64::from(*v)),
+            Inner::U16(v) => Some(f64::from(*v)),
+            Inner::U32(v) => Some(f64::from(*v)),
+            Inner::U64(v) => Some(*v as f64),
+            Inner::U128(v) => Some(*v as f64),
+            Inner::F32(v) => Some(f64::from(v.0)),
+            Inner::F64(v) => Some(v.0),
+            Inner::String(v) if v == "NaN" => Some(f64::NAN),
+            Inner::String(v) if v == "Infinity" => Some(f64::INFINITY),
+            Inner::String(v) if v == "-Infinity" => Some(f64::NEG_INFINITY),
+            _ => None,
+        }
+    }
+
+    /// Returns the binary data contained in the `Any`, if it is some.
+    ///
+    /// Following Conjure's JSON representation of binary values, strings containing valid base64 are also accepted and
+    /// decoded.
+    pub fn as_binary(&self) -> Option<Cow<'_, [u8]>> {
+        match &self.0 {
+            Inner::Bytes(v) => Some(Cow::Borrowed(v)),
+            Inner::String(v) => STANDARD.decode(v).ok().map(Cow::Owned),
+            _ => None,
+        }
+    }
+
+    /// Returns the sequence contained in the `Any`, if it is one.
+    pub fn as_seq(&self) -> Option<&[Any]> {
+        match &self.0 {
+            Inner::Seq(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the map contained in the `Any`, if it is one.
+    pub fn as_map(&self) -> Option<&BTreeMap<Any, Any>> {
+        match &self.0 {
+            Inner::Map(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a string key in the map contained in the `Any`.
+    ///
+    /// Returns `None` if the `Any` is not a map or does not contain the key.
+    pub fn get(&self, key: &str) -> Option<&Any> {
+        match &self.0 {
+            Inner::Map(v) => v.get(&Any::from(key)),
+            _ => None,
+        }
+    }
+
+    /// Looks up a nested value by a JSON Pointer, as defined in [RFC 6901].
+    ///
+    /// Each reference token of the pointer is either a string key of a map or an index into a sequence. The empty
+    /// pointer refers to the `Any` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use conjure_object::Any;
+    ///
+    /// let value = Any::map()
+    ///     .insert("items", Any::seq().push("foo").push("bar").build())
+    ///     .build();
+    /// assert_eq!(value.pointer("/items/1").and_then(Any::as_str), Some("bar"));
+    /// ```
+    ///
+    /// [RFC 6901]: https://tools.ietf.org/html/rfc6901
+    pub fn pointer(&self, pointer: &str) -> Option<&Any> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .try_fold(self, |value, token| match &value.0 {
+                Inner::Seq(v) => parse_index(&token).and_then(|i| v.get(i)),
+                Inner::Map(_) => value.get(&token),
+                _ => None,
+            })
+    }
+
     fn unexpected(&self) -> Unexpected<'_> {
         match &self.0 {
             Inner::Null => Unexpected::Unit,
@@ -139,3 +336,156 @@ impl Any {
         }
     }
 }
+
+fn parse_index(token: &str) -> Option<usize> {
+    // RFC 6901 doesn't permit leading zeros or signs
+    if token.starts_with('+') || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+
+    token.parse().ok()
+}
+
+/// The kind of value contained in an [`Any`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnyKind {
+    /// A null value.
+    Null,
+    /// A boolean.
+    Bool,
+    /// An integer.
+    Integer,
+    /// A floating point number.
+    Float,
+    /// A character.
+    ///
+    /// These are only produced when deserializing from formats that distinguish characters from strings, and are
+    /// serialized as single-character strings in JSON.
+    Char,
+    /// A string.
+    String,
+    /// Binary data.
+    Binary,
+    /// A sequence of values.
+    Seq,
+    /// A map of values.
+    Map,
+}
+
+/// A builder for an [`Any`] containing a sequence of values.
+///
+/// Created by [`Any::seq`].
+#[derive(Debug, Clone)]
+pub struct SeqBuilder {
+    values: Vec<Any>,
+}
+
+impl SeqBuilder {
+    /// Appends a value to the sequence.
+    pub fn push<T>(mut self, value: T) -> SeqBuilder
+    where
+        T: Into<Any>,
+    {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Returns the `Any` containing the sequence.
+    pub fn build(self) -> Any {
+        Any(Inner::Seq(self.values))
+    }
+}
+
+/// A builder for an [`Any`] containing a map of values.
+///
+/// Created by [`Any::map`].
+#[derive(Debug, Clone)]
+pub struct MapBuilder {
+    values: BTreeMap<Any, Any>,
+}
+
+impl MapBuilder {
+    /// Inserts an entry into the map, replacing any existing value for the key.
+    pub fn insert<K, V>(mut self, key: K, value: V) -> MapBuilder
+    where
+        K: Into<Any>,
+        V: Into<Any>,
+    {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the `Any` containing the map.
+    pub fn build(self) -> Any {
+        Any(Inner::Map(self.values))
+    }
+}
+
+impl From<bool> for Any {
+    fn from(v: bool) -> Any {
+        Any(Inner::Bool(v))
+    }
+}
+
+impl From<i32> for Any {
+    fn from(v: i32) -> Any {
+        Any(Inner::I32(v))
+    }
+}
+
+impl From<i64> for Any {
+    fn from(v: i64) -> Any {
+        Any(Inner::I64(v))
+    }
+}
+
+impl From<SafeLong> for Any {
+    fn from(v: SafeLong) -> Any {
+        Any(Inner::I64(*v))
+    }
+}
+
+impl From<f64> for Any {
+    fn from(v: f64) -> Any {
+        Any(Inner::F64(OrderedFloat(v)))
+    }
+}
+
+impl From<&str> for Any {
+    fn from(v: &str) -> Any {
+        Any(Inner::String(v.to_string()))
+    }
+}
+
+impl From<String> for Any {
+    fn from(v: String) -> Any {
+        Any(Inner::String(v))
+    }
+}
+
+impl From<Bytes> for Any {
+    fn from(v: Bytes) -> Any {
+        Any::binary(v)
+    }
+}
+
+impl From<Vec<Any>> for Any {
+    fn from(v: Vec<Any>) -> Any {
+        Any(Inner::Seq(v))
+    }
+}
+
+impl From<BTreeMap<Any, Any>> for Any {
+    fn from(v: BTreeMap<Any, Any>) -> Any {
+        Any(Inner::Map(v))
+    }
+}
+
+impl<T> From<Option<T>> for Any
+where
+    T: Into<Any>,
+{
+    fn from(v: Option<T>) -> Any {
+        v.map_or_else(Any::null, T::into)
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::any::{Any, AnyKind};
+use bytes::Bytes;
+use serde::de::value::{self, CharDeserializer};
+use serde::de::IntoDeserializer;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+#[test]
+fn accessors() {
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), vec![1, 2]);
+    let value = Any::new(&map).unwrap();
+    assert_eq!(value.kind(), AnyKind::Map);
+    assert_eq!(value.as_map().unwrap().len(), 1);
+
+    let a = value.get("a").unwrap();
+    assert_eq!(a.kind(), AnyKind::Seq);
+    let items = a.as_seq().unwrap();
+    assert_eq!(items[1].kind(), AnyKind::Integer);
+    assert_eq!(items[1].as_i64(), Some(2));
+    assert_eq!(items[1].as_f64(), Some(2.));
+    assert_eq!(items[1].as_str(), None);
+    assert!(value.get("b").is_none());
+    assert!(a.get("a").is_none());
+
+    assert_eq!(Any::new(u64::MAX).unwrap().as_i64(), None);
+    assert_eq!(Any::new(1.5).unwrap().as_i64(), None);
+    assert_eq!(Any::new(1.5).unwrap().as_f64(), Some(1.5));
+    assert_eq!(Any::new("foo").unwrap().as_str(), Some("foo"));
+    assert_eq!(Any::new(true).unwrap().as_bool(), Some(true));
+    assert!(Any::new(()).unwrap().is_null());
+}
+
+#[test]
+fn conjure_semantics() {
+    assert!(Any::new(f64::NAN).unwrap().as_f64().unwrap().is_nan());
+    assert!(Any::from("NaN").as_f64().unwrap().is_nan());
+    assert_eq!(Any::from("Infinity").as_f64(), Some(f64::INFINITY));
+    assert_eq!(Any::from("-Infinity").as_f64(), Some(f64::NEG_INFINITY));
+    assert_eq!(Any::from("NaN").kind(), AnyKind::String);
+
+    let binary = Any::new(Bytes::from_static(b"foo")).unwrap();
+    assert_eq!(binary.kind(), AnyKind::Binary);
+    assert_eq!(binary.as_binary().as_deref(), Some(&b"foo"[..]));
+    assert_eq!(Any::from("Zm9v").as_binary().as_deref(), Some(&b"foo"[..]));
+    assert_eq!(Any::from("!").as_binary(), None);
+
+    assert_eq!(Any::from("c").deserialize_into::<char>().unwrap(), 'c');
+    let c = Any::deserialize('c'.into_deserializer() as CharDeserializer<value::Error>).unwrap();
+    assert_eq!(c.kind(), AnyKind::Char);
+    assert_eq!(c.as_str(), None);
+}
+
+#[test]
+fn pointer() {
+    let value = Any::map()
+        .insert("a/b", 1)
+        .insert("c~d", 2)
+        .insert(
+            "e",
+            Any::seq()
+                .push(Any::map().insert("f", "g").build())
+                .push(Any::null())
+                .build(),
+        )
+        .build();
+
+    assert_eq!(value.pointer(""), Some(&value));
+    assert_eq!(value.pointer("/a~1b").and_then(Any::as_i64), Some(1));
+    assert_eq!(value.pointer("/c~0d").and_then(Any::as_i64), Some(2));
+    assert_eq!(value.pointer("/e/0/f").and_then(Any::as_str), Some("g"));
+    assert!(value.pointer("/e/1").unwrap().is_null());
+    assert_eq!(value.pointer("/e/2"), None);
+    assert_eq!(value.pointer("/e/01"), None);
+    assert_eq!(value.pointer("/e/+0"), None);
+    assert_eq!(value.pointer("/a~1b/c"), None);
+    assert_eq!(value.pointer("e"), None);
+}
+
+#[test]
+fn builder() {
+    let built = Any::map()
+        .insert("name", "foo")
+        .insert("count", 3)
+        .insert("ratio", 0.5)
+        .insert("missing", None::<i32>)
+        .insert("tags", Any::seq().push("a").push(true).build())
+        .insert("data", Any::binary(&b"bar"[..]))
+        .build();
+
+    let mut map = BTreeMap::new();
+    map.insert("name", Any::new("foo").unwrap());
+    map.insert("count", Any::new(3).unwrap());
+    map.insert("ratio", Any::new(0.5).unwrap());
+    map.insert("missing", Any::new(None::<i32>).unwrap());
+    map.insert("tags", Any::new(("a", true)).unwrap());
+    map.insert("data", Any::new(Bytes::from_static(b"bar")).unwrap());
+    assert_eq!(built, Any::new(map).unwrap());
+}
@@ -35,6 +35,8 @@ pub use crate::plain::{FromPlain, Plain, ToPlain};
 pub use crate::resource_identifier::ResourceIdentifier;
 #[doc(inline)]
 pub use crate::safe_long::SafeLong;
+#[doc(inline)]
+pub use crate::validate::{Validate, ValidationError};
 
 pub mod any;
 pub mod bearer_token;
@@ -42,6 +44,7 @@ pub mod double_key;
 pub mod plain;
 pub mod resource_identifier;
 pub mod safe_long;
+pub mod validate;
 
 #[doc(hidden)]
 pub mod private;
@@ -21,6 +21,8 @@ use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::{fmt, mem};
 
+use crate::ValidationError;
+
 pub trait DoubleOps {
     fn cmp(&self, other: &Self) -> Ordering;
 
@@ -219,6 +221,80 @@ pub fn valid_enum_variant(s: &str) -> bool {
         .all(|b| matches!(b, b'A'..=b'Z' | b'0'..=b'9' | b'_'))
 }
 
+pub fn check_min_length(value: &str, min: usize) -> Result<(), ValidationError> {
+    let length = value.chars().count();
+    if length < min {
+        return Err(ValidationError::new(format!(
+            "length must be at least {min} but was {length}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_max_length(value: &str, max: usize) -> Result<(), ValidationError> {
+    let length = value.chars().count();
+    if length > max {
+        return Err(ValidationError::new(format!(
+            "length must be at most {max} but was {length}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_min_size(size: usize, min: usize) -> Result<(), ValidationError> {
+    if size < min {
+        return Err(ValidationError::new(format!(
+            "size must be at least {min} but was {size}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_max_size(size: usize, max: usize) -> Result<(), ValidationError> {
+    if size > max {
+        return Err(ValidationError::new(format!(
+            "size must be at most {max} but was {size}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_min<T>(value: T, min: T) -> Result<(), ValidationError>
+where
+    T: PartialOrd + fmt::Display,
+{
+    if !matches!(
+        value.partial_cmp(&min),
+        Some(Ordering::Greater | Ordering::Equal)
+    ) {
+        return Err(ValidationError::new(format!(
+            "value must be at least {min} but was {value}"
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn check_max<T>(value: T, max: T) -> Result<(), ValidationError>
+where
+    T: PartialOrd + fmt::Display,
+{
+    if !matches!(
+        value.partial_cmp(&max),
+        Some(Ordering::Less | Ordering::Equal)
+    ) {
+        return Err(ValidationError::new(format!(
+            "value must be at most {max} but was {value}"
+        )));
+    }
+
+    Ok(())
+}
+
 pub enum UnionField_<T> {
     Type,
     Value(T),
@@ -0,0 +1,232 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of Conjure values beyond what is checked during deserialization.
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt;
+use uuid::Uuid;
+
+use crate::{Any, BearerToken, DoubleKey, ResourceIdentifier, SafeLong};
+
+/// A trait for types which can check their own contents.
+///
+/// Generated types implement this trait when validation is enabled in codegen. Implementations check that values
+/// don't contain unknown enum or union variants and satisfy any constraints declared in the Conjure definition, and
+/// recursively validate nested values.
+pub trait Validate {
+    /// Validates the value.
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+impl<T> Validate for &T
+where
+    T: ?Sized + Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        (**self).validate()
+    }
+}
+
+impl<T> Validate for Box<T>
+where
+    T: ?Sized + Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        (**self).validate()
+    }
+}
+
+impl<T> Validate for Option<T>
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        match self {
+            Some(value) => value.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T> Validate for Vec<T>
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_seq(self)
+    }
+}
+
+impl<T> Validate for BTreeSet<T>
+where
+    T: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_seq(self)
+    }
+}
+
+fn validate_seq<'a, I, T>(values: I) -> Result<(), ValidationError>
+where
+    I: IntoIterator<Item = &'a T>,
+    T: 'a + Validate,
+{
+    for (i, value) in values.into_iter().enumerate() {
+        value.validate().map_err(|e| e.with_index(i))?;
+    }
+
+    Ok(())
+}
+
+impl<K, V> Validate for BTreeMap<K, V>
+where
+    K: Validate,
+    V: Validate,
+{
+    fn validate(&self) -> Result<(), ValidationError> {
+        for (key, value) in self {
+            key.validate()?;
+            value.validate().map_err(ValidationError::with_map_value)?;
+        }
+
+        Ok(())
+    }
+}
+
+macro_rules! impl_noop {
+    ($($t:ty),*) => {
+        $(
+            impl Validate for $t {
+                #[inline]
+                fn validate(&self) -> Result<(), ValidationError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_noop!(
+    str,
+    String,
+    bool,
+    i32,
+    f64,
+    SafeLong,
+    Bytes,
+    Uuid,
+    DateTime<Utc>,
+    ResourceIdentifier,
+    BearerToken,
+    Any,
+    DoubleKey
+);
+
+/// An error returned when a value fails validation.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    path: String,
+    message: String,
+}
+
+impl ValidationError {
+    /// Creates a new `ValidationError` with a message describing the problem.
+    pub fn new<T>(message: T) -> ValidationError
+    where
+        T: Into<String>,
+    {
+        ValidationError {
+            path: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Marks the error as having occurred within a field of an object or variant of a union.
+    pub fn with_field(mut self, field: &str) -> ValidationError {
+        if !self.path.is_empty() && !self.path.starts_with('[') {
+            self.path.insert(0, '.');
+        }
+        self.path.insert_str(0, field);
+        self
+    }
+
+    /// Marks the error as having occurred within an element of a list or set.
+    pub fn with_index(mut self, index: usize) -> ValidationError {
+        if !self.path.is_empty() && !self.path.starts_with('[') {
+            self.path.insert(0, '.');
+        }
+        self.path.insert_str(0, &format!("[{index}]"));
+        self
+    }
+
+    /// Marks the error as having occurred within a value of a map.
+    ///
+    /// Map keys may contain sensitive data, so the entry is identified by `[*]` rather than by its key.
+    pub fn with_map_value(mut self) -> ValidationError {
+        if !self.path.is_empty() && !self.path.starts_with('[') {
+            self.path.insert(0, '.');
+        }
+        self.path.insert_str(0, "[*]");
+        self
+    }
+
+    /// Returns the path to the invalid value, e.g. `items[2].name`.
+    ///
+    /// The path is empty if the error occurred in the value itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the message describing the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            fmt.write_str(&self.message)
+        } else {
+            write!(fmt, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl Error for ValidationError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn path() {
+        let error = ValidationError::new("bogus")
+            .with_field("name")
+            .with_index(2)
+            .with_field("items");
+        assert_eq!(error.path(), "items[2].name");
+        assert_eq!(error.to_string(), "items[2].name: bogus");
+
+        let error = ValidationError::new("bogus")
+            .with_field("name")
+            .with_map_value()
+            .with_field("items");
+        assert_eq!(error.path(), "items[*].name");
+    }
+}
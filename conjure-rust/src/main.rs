@@ -14,14 +14,20 @@
 #![warn(clippy::all)]
 
 use clap::Parser;
-use std::path::PathBuf;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process;
 
 #[derive(Parser)]
 enum Opts {
     #[clap(name = "generate", dont_collapse_args_in_usage = true)]
-    /// Generate Rust code from a conjure IR file.
-    Generate(Args),
+    /// Generate Rust code from conjure IR files.
+    Generate(Box<Args>),
+    #[clap(name = "diff", dont_collapse_args_in_usage = true)]
+    /// Report the changes between two versions of a conjure IR file, exiting with an error on breaking changes.
+    Diff(DiffArgs),
 }
 
 #[derive(Parser)]
@@ -53,17 +59,80 @@ struct Args {
         requires = "product_version"
     )]
     crate_version: Option<String>,
-    /// Path to a JSON-formatted Conjure IR file
-    #[clap(name = "inputJson")]
-    input_json: PathBuf,
+    /// Encode the request bodies sent by the clients of the specified comma-separated services as Smile rather than JSON
+    #[clap(
+        long = "smileRequestServices",
+        value_name = "services",
+        value_delimiter = ','
+    )]
+    smile_request_services: Option<Vec<String>>,
+    /// Path to a TOML file mapping external import names to Rust types
+    #[clap(long = "externalTypes", value_name = "file")]
+    external_types: Option<PathBuf>,
+    /// Path to a TOML file describing crates providing already generated Conjure packages and types
+    #[clap(long = "externCrates", value_name = "file")]
+    extern_crates: Option<PathBuf>,
+    /// Only generate client bindings for services
+    #[clap(long = "clientsOnly", conflicts_with_all = ["servers_only", "server_services"])]
+    clients_only: bool,
+    /// Only generate server bindings for services
+    #[clap(long = "serversOnly", conflicts_with_all = ["clients_only", "client_services"])]
+    servers_only: bool,
+    /// Limit client generation to the specified comma-separated services
+    #[clap(
+        long = "clientServices",
+        value_name = "services",
+        value_delimiter = ','
+    )]
+    client_services: Option<Vec<String>>,
+    /// Limit server generation to the specified comma-separated services
+    #[clap(
+        long = "serverServices",
+        value_name = "services",
+        value_delimiter = ','
+    )]
+    server_services: Option<Vec<String>>,
+    /// Gate the generated crate's package modules and client and server bindings behind Cargo features
+    #[clap(long = "packageFeatures", requires = "product_name")]
+    package_features: bool,
+    /// Only generate the definitions matching the specified comma-separated patterns and the types they reference
+    #[clap(long = "include", value_name = "patterns", value_delimiter = ',')]
+    include: Vec<String>,
+    /// Exclude the definitions matching the specified comma-separated patterns
+    #[clap(long = "exclude", value_name = "patterns", value_delimiter = ',')]
+    exclude: Vec<String>,
+    /// Generate validation of types against the constraints declared in the IR, and validate server request bodies
+    #[clap(long = "validation")]
+    validation: bool,
+    /// Check that the output directory is up to date rather than writing to it, exiting with an error if it isn't
+    #[clap(long = "check")]
+    check: bool,
+    /// Paths to JSON-formatted Conjure IR files
+    #[clap(name = "inputJson", required = true)]
+    input_json: Vec<PathBuf>,
     /// Directory to place generated code
     #[clap(name = "outputDirectory")]
     output_directory: PathBuf,
 }
 
+#[derive(Parser)]
+struct DiffArgs {
+    /// Path to the old JSON-formatted Conjure IR file
+    #[clap(name = "oldInputJson")]
+    old_input_json: PathBuf,
+    /// Path to the new JSON-formatted Conjure IR file
+    #[clap(name = "newInputJson")]
+    new_input_json: PathBuf,
+}
+
 fn main() {
-    let Opts::Generate(args) = Opts::parse();
+    match Opts::parse() {
+        Opts::Generate(args) => generate(*args),
+        Opts::Diff(args) => diff(args),
+    }
+}
 
+fn generate(args: Args) {
     let mut config = conjure_codegen::Config::new();
     config.exhaustive(args.exhaustive);
     if let Some(prefix) = args.strip_prefix {
@@ -79,10 +148,89 @@ fn main() {
     if let Some(product_version) = args.product_version {
         config.version(product_version);
     }
-    let r = config.generate_files(&args.input_json, &args.output_directory);
+    if let Some(services) = args.smile_request_services {
+        config.smile_request_services(services);
+    }
+    if let Some(path) = args.external_types {
+        for (name, rust_type) in read_toml::<BTreeMap<String, String>>(&path, "external types") {
+            config.external_type(name, rust_type);
+        }
+    }
+    if let Some(path) = args.extern_crates {
+        for (name, info) in read_toml::<BTreeMap<String, ExternCrate>>(&path, "extern crates") {
+            config.extern_crate(&name, &info.version, info.packages);
+            config.extern_crate_types(&name, &info.version, info.types);
+        }
+    }
+    config.package_features(args.package_features);
+    config.generate_clients(!args.servers_only);
+    config.generate_servers(!args.clients_only);
+    if let Some(services) = args.client_services {
+        config.client_services(services);
+    }
+    if let Some(services) = args.server_services {
+        config.server_services(services);
+    }
+    for pattern in args.include {
+        config.include(pattern);
+    }
+    for pattern in args.exclude {
+        config.exclude(pattern);
+    }
+    config.validation(args.validation);
+    config.check(args.check);
+    let r = config.generate_merged_files(&args.input_json, &args.output_directory);
 
     if let Err(e) = r {
         eprintln!("{e:?}");
         process::exit(1);
     }
 }
+
+fn diff(args: DiffArgs) {
+    let changes =
+        match conjure_codegen::diff::diff_files(&args.old_input_json, &args.new_input_json) {
+            Ok(changes) => changes,
+            Err(e) => {
+                eprintln!("{e:?}");
+                process::exit(1);
+            }
+        };
+
+    if changes.is_empty() {
+        println!("no changes");
+    }
+    for change in &changes {
+        println!("{change}");
+    }
+
+    if changes.iter().any(|c| c.is_breaking()) {
+        process::exit(1);
+    }
+}
+
+#[derive(Deserialize)]
+struct ExternCrate {
+    version: String,
+    #[serde(default)]
+    packages: BTreeMap<String, String>,
+    #[serde(default)]
+    types: BTreeMap<String, String>,
+}
+
+fn read_toml<T>(path: &Path, kind: &str) -> T
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let result = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|s| toml::from_str(&s).map_err(|e| e.to_string()));
+
+    match result {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("error reading {kind} file {}: {e}", path.display());
+            process::exit(1);
+        }
+    }
+}
@@ -17,16 +17,28 @@ use std::marker::PhantomData;
 
 pub mod delegating_deserializer;
 pub mod delegating_visitor;
+pub mod path;
 pub mod unknown_fields_behavior;
 pub mod wrapping_deserializer;
 
 macro_rules! impl_deserialize_body {
-    ($inner:ty, $behavior:ty) => {
+    (@call $self:ident, [$behavior:ty, ignore_unknown_fields = $lenient:ty], $method:ident($($arg:expr),*)) => {
+        if $self.ignore_unknown_fields {
+            impl_deserialize_body!(@call $self, [$lenient], $method($($arg),*))
+        } else {
+            impl_deserialize_body!(@call $self, [$behavior], $method($($arg),*))
+        }
+    };
+    (@call $self:ident, [$behavior:ty], $method:ident($($arg:expr),*)) => {{
+        $self.path.reset();
+        $self.path.track($crate::de::Override::<_, $behavior>::new(&mut $self.inner)).$method($($arg),*)
+    }};
+    ($inner:ty, $($behavior:tt)*) => {
         type Error = <$inner as de::Deserializer<'de>>::Error;
 
         impl_deserialize_body! {
             @delegate
-            $behavior,
+            [$($behavior)*],
             deserialize_any,
             deserialize_bool,
             deserialize_i8,
@@ -62,7 +74,7 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_unit_struct(name, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_unit_struct(name, visitor))
         }
 
         fn deserialize_newtype_struct<V>(
@@ -73,7 +85,7 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_newtype_struct(name, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_newtype_struct(name, visitor))
         }
 
         fn deserialize_tuple<V>(
@@ -84,7 +96,7 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_tuple(len, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_tuple(len, visitor))
         }
 
         fn deserialize_tuple_struct<V>(
@@ -96,7 +108,7 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_tuple_struct(name, len, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_tuple_struct(name, len, visitor))
         }
 
         fn deserialize_struct<V>(
@@ -108,7 +120,7 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_struct(name, fields, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_struct(name, fields, visitor))
         }
 
         fn deserialize_enum<V>(
@@ -120,16 +132,16 @@ macro_rules! impl_deserialize_body {
         where
             V: de::Visitor<'de>
         {
-            $crate::de::Override::<_, $behavior>::new(&mut self.0).deserialize_enum(name, variants, visitor)
+            impl_deserialize_body!(@call self, [$($behavior)*], deserialize_enum(name, variants, visitor))
         }
     };
-    (@delegate $behavior:ty, $($method:ident,)*) => {
+    (@delegate $behavior:tt, $($method:ident,)*) => {
         $(
             fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
             where
                 V: de::Visitor<'de>
             {
-                $crate::de::Override::<_, $behavior>::new(&mut self.0).$method(visitor)
+                impl_deserialize_body!(@call self, $behavior, $method(visitor))
             }
         )*
     }
@@ -0,0 +1,497 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::de::delegating_deserializer::{DelegatingDeserializer, Deserializer2};
+use crate::de::delegating_visitor::{DelegatingVisitor, Visitor2};
+use crate::de::wrapping_deserializer::{Delegate, WrapVisitor, WrappingDeserializer};
+use serde::de::{DeserializeSeed, Error, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::mem;
+
+/// Tracks the path to the value which caused a deserialization error so its location can be reported.
+///
+/// Only the names of declared struct fields and sequence indices are included in the path, since map keys and
+/// unknown fields may contain sensitive data. Map values, including the values of unions, are instead identified by
+/// `[*]`.
+///
+/// The path is built lazily as an error propagates outwards, so successful deserialization does not pay for it. If an
+/// error is recovered from, its partial path is discarded as soon as deserialization continues.
+///
+/// The tracker can optionally also record the paths of unknown struct fields, which do include their names. This
+/// requires the current path to be tracked eagerly.
+#[derive(Default)]
+pub struct PathTracker {
+    // innermost segment first
+    error_segments: RefCell<Vec<Segment>>,
+    unwinding: Cell<bool>,
+    segments: RefCell<Vec<Segment>>,
+    unknown_fields: Option<RefCell<Vec<String>>>,
+}
+
+#[derive(Copy, Clone)]
+enum Segment {
+    Field(&'static str),
+    Index(usize),
+    Key,
+}
+
+impl PathTracker {
+    pub fn reset(&self) {
+        self.error_segments.borrow_mut().clear();
+        self.unwinding.set(false);
+        self.segments.borrow_mut().clear();
+        if let Some(unknown_fields) = &self.unknown_fields {
+            unknown_fields.borrow_mut().clear();
+        }
+    }
+
+    pub fn error_path(&self) -> Option<String> {
+        if !self.unwinding.get() {
+            return None;
+        }
+
+        let segments = self.error_segments.borrow();
+        if segments.is_empty() {
+            return None;
+        }
+
+        let mut path = String::new();
+        for segment in segments.iter().rev() {
+            segment.render(&mut path);
+        }
+        Some(path)
+    }
+
+    pub fn collect_unknown_fields(&mut self, collect: bool) {
+        self.unknown_fields = if collect {
+            Some(RefCell::new(vec![]))
+        } else {
+            None
+        };
+    }
+
+    pub fn take_unknown_fields(&mut self) -> Vec<String> {
+        self.unknown_fields
+            .as_mut()
+            .map_or_else(Vec::new, |f| mem::take(f.get_mut()))
+    }
+
+    /// Wraps a deserializer to track the path through its contents.
+    pub fn track<'de, D>(&self, deserializer: D) -> DelegatingDeserializer<TrackPath<'_>, D>
+    where
+        D: Deserializer<'de>,
+    {
+        DelegatingDeserializer::new(TrackPath { tracker: self }, deserializer)
+    }
+
+    fn with_segment<T, E>(
+        &self,
+        segment: Option<Segment>,
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        // deserialization is continuing, so any error seen before now was recovered from
+        self.unwinding.set(false);
+
+        let eager = self.unknown_fields.is_some() && segment.is_some();
+        if eager {
+            self.segments.borrow_mut().extend(segment);
+        }
+
+        let result = f();
+
+        if eager {
+            self.segments.borrow_mut().pop();
+        }
+
+        match &result {
+            Ok(_) => self.unwinding.set(false),
+            Err(_) => self.record_error(segment),
+        }
+        result
+    }
+
+    // Errors propagate outwards, so the first segment recorded is the innermost.
+    fn record_error(&self, segment: Option<Segment>) {
+        let mut segments = self.error_segments.borrow_mut();
+        if !self.unwinding.replace(true) {
+            segments.clear();
+        }
+        segments.extend(segment);
+    }
+
+    fn record_unknown_field(&self, name: &str) {
+        let Some(unknown_fields) = &self.unknown_fields else {
+            return;
+        };
+
+        let mut path = String::new();
+        for segment in &*self.segments.borrow() {
+            segment.render(&mut path);
+        }
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(name);
+        unknown_fields.borrow_mut().push(path);
+    }
+}
+
+impl Segment {
+    fn render(&self, path: &mut String) {
+        match self {
+            Segment::Field(field) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(field);
+            }
+            Segment::Index(index) => write!(path, "[{index}]").unwrap(),
+            Segment::Key => path.push_str("[*]"),
+        }
+    }
+}
+
+pub struct TrackPath<'a> {
+    tracker: &'a PathTracker,
+}
+
+impl TrackPath<'_> {
+    fn visitor<'de, V>(
+        &self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> DelegatingVisitor<PathVisitor<'_>, V>
+    where
+        V: Visitor<'de>,
+    {
+        DelegatingVisitor::new(
+            PathVisitor {
+                tracker: self.tracker,
+                fields,
+            },
+            visitor,
+        )
+    }
+}
+
+macro_rules! track {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V>(self, deserializer: D, visitor: V) -> Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                deserializer.$method(self.visitor(None, visitor))
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer2<'de, D> for TrackPath<'_>
+where
+    D: Deserializer<'de>,
+{
+    track! {
+        deserialize_any,
+        deserialize_option,
+        deserialize_seq,
+        deserialize_map,
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        deserializer: D,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        deserializer.deserialize_newtype_struct(name, self.visitor(None, visitor))
+    }
+
+    fn deserialize_tuple<V>(
+        self,
+        deserializer: D,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        deserializer.deserialize_tuple(len, self.visitor(None, visitor))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        deserializer: D,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        deserializer.deserialize_struct(name, fields, self.visitor(Some(fields), visitor))
+    }
+}
+
+pub struct PathVisitor<'a> {
+    tracker: &'a PathTracker,
+    fields: Option<&'static [&'static str]>,
+}
+
+impl<'de, V> Visitor2<'de, V> for PathVisitor<'_>
+where
+    V: Visitor<'de>,
+{
+    fn visit_some<D>(self, visitor: V, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        visitor.visit_some(self.tracker.track(deserializer))
+    }
+
+    fn visit_newtype_struct<D>(self, visitor: V, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        visitor.visit_newtype_struct(self.tracker.track(deserializer))
+    }
+
+    fn visit_seq<A>(self, visitor: V, seq: A) -> Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        visitor.visit_seq(PathSeqAccess {
+            seq,
+            tracker: self.tracker,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A>(self, visitor: V, map: A) -> Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        visitor.visit_map(PathMapAccess {
+            map,
+            tracker: self.tracker,
+            fields: self.fields,
+            field: None,
+        })
+    }
+}
+
+struct PathSeqAccess<'a, A> {
+    seq: A,
+    tracker: &'a PathTracker,
+    index: usize,
+}
+
+impl<'de, A> SeqAccess<'de> for PathSeqAccess<'_, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let index = self.index;
+        self.index += 1;
+        self.tracker.with_segment(Some(Segment::Index(index)), || {
+            self.seq.next_element_seed(PathSeed {
+                seed,
+                tracker: self.tracker,
+            })
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.seq.size_hint()
+    }
+}
+
+struct PathMapAccess<'a, A> {
+    map: A,
+    tracker: &'a PathTracker,
+    fields: Option<&'static [&'static str]>,
+    field: Option<&'static str>,
+}
+
+impl<'de, A> MapAccess<'de> for PathMapAccess<'_, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.field = None;
+        let fields = self.fields;
+        let field = &mut self.field;
+        let map = &mut self.map;
+        let tracker = self.tracker;
+        tracker.with_segment(None, || match fields {
+            Some(fields) => map.next_key_seed(KeySeed {
+                seed,
+                tracker,
+                fields,
+                field,
+            }),
+            None => map.next_key_seed(seed),
+        })
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let segment = match (self.fields, self.field) {
+            (Some(_), Some(field)) => Some(Segment::Field(field)),
+            // the error will identify the unknown field itself
+            (Some(_), None) => None,
+            (None, _) => Some(Segment::Key),
+        };
+        self.tracker.with_segment(segment, || {
+            self.map.next_value_seed(PathSeed {
+                seed,
+                tracker: self.tracker,
+            })
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+struct PathSeed<'a, T> {
+    seed: T,
+    tracker: &'a PathTracker,
+}
+
+impl<'de, T> DeserializeSeed<'de> for PathSeed<'_, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.seed.deserialize(self.tracker.track(deserializer))
+    }
+}
+
+struct KeySeed<'a, T> {
+    seed: T,
+    tracker: &'a PathTracker,
+    fields: &'static [&'static str],
+    field: &'a mut Option<&'static str>,
+}
+
+impl<'de, T> DeserializeSeed<'de> for KeySeed<'_, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<T::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.seed.deserialize(WrappingDeserializer::new(
+            KeyWrapper {
+                tracker: self.tracker,
+                fields: self.fields,
+                field: self.field,
+            },
+            deserializer,
+        ))
+    }
+}
+
+struct KeyWrapper<'a> {
+    tracker: &'a PathTracker,
+    fields: &'static [&'static str],
+    field: &'a mut Option<&'static str>,
+}
+
+impl<'de> WrapVisitor<'de> for KeyWrapper<'_> {
+    fn wrap_visitor<D, V>(self, delegate: D, visitor: V) -> Result<V::Value, D::Error>
+    where
+        D: Delegate<'de>,
+        V: Visitor<'de>,
+    {
+        delegate.delegate(DelegatingVisitor::new(
+            KeyVisitor {
+                tracker: self.tracker,
+                fields: self.fields,
+                field: self.field,
+            },
+            visitor,
+        ))
+    }
+}
+
+struct KeyVisitor<'a> {
+    tracker: &'a PathTracker,
+    fields: &'static [&'static str],
+    field: &'a mut Option<&'static str>,
+}
+
+impl KeyVisitor<'_> {
+    fn record(self, value: &str) {
+        *self.field = self.fields.iter().copied().find(|f| *f == value);
+        if self.field.is_none() {
+            self.tracker.record_unknown_field(value);
+        }
+    }
+}
+
+impl<'de, V> Visitor2<'de, V> for KeyVisitor<'_>
+where
+    V: Visitor<'de>,
+{
+    fn visit_str<E>(self, visitor: V, value: &str) -> Result<V::Value, E>
+    where
+        E: Error,
+    {
+        self.record(value);
+        visitor.visit_str(value)
+    }
+
+    fn visit_borrowed_str<E>(self, visitor: V, value: &'de str) -> Result<V::Value, E>
+    where
+        E: Error,
+    {
+        self.record(value);
+        visitor.visit_borrowed_str(value)
+    }
+
+    fn visit_string<E>(self, visitor: V, value: String) -> Result<V::Value, E>
+    where
+        E: Error,
+    {
+        self.record(&value);
+        visitor.visit_string(value)
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::json::client_from_slice;
+use serde::de::{self, DeserializeOwned};
+use serde_json::Error;
+use std::io::{self, BufRead, BufReader};
+use std::marker::PhantomData;
+
+/// Returns an iterator over the elements of a top-level JSON array read from a reader.
+///
+/// Elements are deserialized one at a time with the same semantics as
+/// [`client_from_reader`](crate::json::client_from_reader), so only a single element needs to be held in memory at
+/// once. The reader is buffered internally.
+pub fn client_array_from_reader<R, T>(reader: R) -> ClientArrayIter<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    ClientArrayIter {
+        reader: BufReader::new(reader),
+        decoder: ClientArrayDecoder::new(),
+    }
+}
+
+/// An iterator over the elements of a top-level JSON array.
+///
+/// Created by [`client_array_from_reader`]. Iteration stops after the first error.
+pub struct ClientArrayIter<R, T> {
+    reader: BufReader<R>,
+    decoder: ClientArrayDecoder<T>,
+}
+
+impl<R, T> ClientArrayIter<R, T> {
+    /// Returns a shared reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading directly from the reader will corrupt the state of the iterator.
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+}
+
+impl<R, T> Iterator for ClientArrayIter<R, T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Result<T, Error>> {
+        loop {
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(e) => return self.decoder.fail(Error::io(e)),
+            };
+            if chunk.is_empty() {
+                return self.decoder.finish();
+            }
+
+            let (len, value) = self.decoder.decode(chunk);
+            self.reader.consume(len);
+            if value.is_some() {
+                return value;
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    Start,
+    First,
+    Element,
+    Next,
+    End,
+    Done,
+}
+
+/// A push-based decoder of the elements of a top-level JSON array.
+///
+/// It is fed the array incrementally, which allows the elements of a body read asynchronously to be deserialized as it
+/// arrives. Elements are deserialized with the same semantics as [`client_array_from_reader`]. Decoding stops after
+/// the first error.
+pub struct ClientArrayDecoder<T> {
+    buf: Vec<u8>,
+    scanner: Scanner,
+    state: State,
+    _p: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for ClientArrayDecoder<T>
+where
+    T: DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ClientArrayDecoder<T>
+where
+    T: DeserializeOwned,
+{
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        ClientArrayDecoder {
+            buf: vec![],
+            scanner: Scanner::default(),
+            state: State::Start,
+            _p: PhantomData,
+        }
+    }
+
+    /// Decodes the next chunk of the array.
+    ///
+    /// Returns the number of bytes of the chunk consumed along with the next element, if one was completed. Bytes which
+    /// were not consumed must be passed to the next call.
+    pub fn decode(&mut self, chunk: &[u8]) -> (usize, Option<Result<T, Error>>) {
+        let mut pos = 0;
+        while pos < chunk.len() {
+            let b = chunk[pos];
+            match self.state {
+                State::Element => {
+                    let len = chunk[pos..].iter().position(|b| self.scanner.push(*b));
+                    let end = len.map_or(chunk.len(), |len| pos + len);
+                    self.buf.extend_from_slice(&chunk[pos..end]);
+                    pos = end;
+
+                    if len.is_some() {
+                        self.state = State::Next;
+                        self.scanner = Scanner::default();
+                        let value = client_from_slice(&self.buf);
+                        self.buf.clear();
+                        return match value {
+                            Ok(value) => (pos, Some(Ok(value))),
+                            Err(e) => (pos, self.fail(e)),
+                        };
+                    }
+                }
+                _ if b.is_ascii_whitespace() => pos += 1,
+                State::Start if b == b'[' => {
+                    self.state = State::First;
+                    pos += 1;
+                }
+                State::Start => return (pos, self.fail(de::Error::custom("expected `[`"))),
+                State::First | State::Next if b == b']' => {
+                    self.state = State::End;
+                    pos += 1;
+                }
+                State::First => self.state = State::Element,
+                State::Next if b == b',' => {
+                    self.state = State::Element;
+                    pos += 1;
+                }
+                State::Next => {
+                    return (pos, self.fail(de::Error::custom("expected `,` or `]`")));
+                }
+                State::End => {
+                    return (pos, self.fail(de::Error::custom("trailing characters")));
+                }
+                State::Done => return (chunk.len(), None),
+            }
+        }
+
+        (pos, None)
+    }
+
+    /// Signals the end of the array's input.
+    ///
+    /// Returns an error if the array was incomplete.
+    pub fn finish(&mut self) -> Option<Result<T, Error>> {
+        match self.state {
+            State::End | State::Done => {
+                self.state = State::Done;
+                None
+            }
+            _ => self.fail(de::Error::custom("EOF while parsing a list")),
+        }
+    }
+
+    fn fail(&mut self, error: Error) -> Option<Result<T, Error>> {
+        self.state = State::Done;
+        Some(Err(error))
+    }
+}
+
+// Tracks just enough of the JSON structure to find the end of an array element. The contents of the element are
+// validated when it is deserialized.
+#[derive(Default)]
+struct Scanner {
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl Scanner {
+    // returns true if the byte is the delimiter following the element
+    fn push(&mut self, b: u8) -> bool {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+            }
+            return false;
+        }
+
+        match b {
+            b'"' => self.in_string = true,
+            b'[' | b'{' => self.depth += 1,
+            b',' | b']' if self.depth == 0 => return true,
+            b']' | b'}' => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+        false
+    }
+}
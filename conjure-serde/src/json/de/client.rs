@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 use crate::de::delegating_visitor::{DelegatingVisitor, Visitor2};
+use crate::de::path::PathTracker;
 use crate::de::Behavior;
 use base64::engine::general_purpose::STANDARD;
 use base64::Engine;
@@ -56,7 +57,10 @@ where
 }
 
 /// A serde JSON deserializer appropriate for use by Conjure clients.
-pub struct ClientDeserializer<R>(serde_json::Deserializer<R>);
+pub struct ClientDeserializer<R> {
+    inner: serde_json::Deserializer<R>,
+    path: PathTracker,
+}
 
 impl<R> ClientDeserializer<IoRead<R>>
 where
@@ -64,14 +68,20 @@ where
 {
     /// Creates a Conjure JSON client deserializer from an `io::Read`.
     pub fn from_reader(reader: R) -> ClientDeserializer<IoRead<R>> {
-        ClientDeserializer(serde_json::Deserializer::from_reader(reader))
+        ClientDeserializer {
+            inner: serde_json::Deserializer::from_reader(reader),
+            path: PathTracker::default(),
+        }
     }
 }
 
 impl<'a> ClientDeserializer<SliceRead<'a>> {
     /// Creates a Conjure JSON client deserializer from a `&[u8]`.
     pub fn from_slice(bytes: &'a [u8]) -> ClientDeserializer<SliceRead<'a>> {
-        ClientDeserializer(serde_json::Deserializer::from_slice(bytes))
+        ClientDeserializer {
+            inner: serde_json::Deserializer::from_slice(bytes),
+            path: PathTracker::default(),
+        }
     }
 }
 
@@ -79,7 +89,10 @@ impl<'a> ClientDeserializer<StrRead<'a>> {
     /// Creates a Conjure JSON client deserializer from a `&str`.
     #[allow(clippy::should_implement_trait)] // match serde_json's API
     pub fn from_str(s: &'a str) -> ClientDeserializer<StrRead<'a>> {
-        ClientDeserializer(serde_json::Deserializer::from_str(s))
+        ClientDeserializer {
+            inner: serde_json::Deserializer::from_str(s),
+            path: PathTracker::default(),
+        }
     }
 }
 
@@ -89,7 +102,15 @@ where
 {
     /// Validates that the input stream is at the end or that it only has trailing whitespace.
     pub fn end(&mut self) -> Result<(), Error> {
-        self.0.end()
+        self.inner.end()
+    }
+
+    /// Returns the path to the value which caused the most recent deserialization error, if known.
+    ///
+    /// The path is built from struct field names and sequence indices, for example `items[3].owner`. Map keys are
+    /// not included since they may contain sensitive data.
+    pub fn error_path(&self) -> Option<String> {
+        self.path.error_path()
     }
 }
 
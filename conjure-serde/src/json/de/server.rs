@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::de::path::PathTracker;
 use crate::de::unknown_fields_behavior::UnknownFieldsBehavior;
 use crate::json::de::client::ValueBehavior;
 use serde::de;
@@ -55,8 +56,13 @@ where
 /// A serde JSON deserializer appropriate for use by Conjure servers.
 ///
 /// In contrast to serde_json, the f32 and f64 types can be deserialized from the strings `"Infinity"`, `"-Infinity"`,
-/// and `"NaN"`, and bytes are deserialized from base64 encoded strings. Unknown object fields trigger errors.
-pub struct ServerDeserializer<R>(serde_json::Deserializer<R>);
+/// and `"NaN"`, and bytes are deserialized from base64 encoded strings. Unknown object fields trigger errors
+/// unless [`ignore_unknown_fields`](Self::ignore_unknown_fields) is enabled.
+pub struct ServerDeserializer<R> {
+    inner: serde_json::Deserializer<R>,
+    path: PathTracker,
+    ignore_unknown_fields: bool,
+}
 
 impl<R> ServerDeserializer<IoRead<R>>
 where
@@ -64,14 +70,22 @@ where
 {
     /// Creates a Conjure JSON server deserializer from an `io::Read`.
     pub fn from_reader(reader: R) -> ServerDeserializer<IoRead<R>> {
-        ServerDeserializer(serde_json::Deserializer::from_reader(reader))
+        ServerDeserializer {
+            inner: serde_json::Deserializer::from_reader(reader),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
 impl<'a> ServerDeserializer<SliceRead<'a>> {
     /// Creates a Conjure JSON server deserializer from a `&[u8]`.
     pub fn from_slice(bytes: &'a [u8]) -> ServerDeserializer<SliceRead<'a>> {
-        ServerDeserializer(serde_json::Deserializer::from_slice(bytes))
+        ServerDeserializer {
+            inner: serde_json::Deserializer::from_slice(bytes),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
@@ -79,7 +93,11 @@ impl<'a> ServerDeserializer<StrRead<'a>> {
     /// Creates a Conjure JSON server deserializer from a `&str`.
     #[allow(clippy::should_implement_trait)] // match serde_json's API
     pub fn from_str(s: &'a str) -> ServerDeserializer<StrRead<'a>> {
-        ServerDeserializer(serde_json::Deserializer::from_str(s))
+        ServerDeserializer {
+            inner: serde_json::Deserializer::from_str(s),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
@@ -89,7 +107,38 @@ where
 {
     /// Validates that the input stream is at the end or that it only has trailing whitespace.
     pub fn end(&mut self) -> Result<(), Error> {
-        self.0.end()
+        self.inner.end()
+    }
+
+    /// Returns the path to the value which caused the most recent deserialization error, if known.
+    ///
+    /// The path is built from struct field names and sequence indices, for example `items[3].owner`. Map keys are
+    /// not included since they may contain sensitive data.
+    pub fn error_path(&self) -> Option<String> {
+        self.path.error_path()
+    }
+
+    /// Configures the deserializer to ignore unknown object fields rather than triggering errors.
+    pub fn ignore_unknown_fields(&mut self, ignore: bool) {
+        self.ignore_unknown_fields = ignore;
+    }
+
+    /// Configures the deserializer to record the paths of ignored unknown object fields.
+    ///
+    /// The paths can be retrieved with [`take_unknown_fields`](Self::take_unknown_fields). This has no effect unless
+    /// [`ignore_unknown_fields`](Self::ignore_unknown_fields) is also enabled.
+    pub fn collect_unknown_fields(&mut self, collect: bool) {
+        self.path.collect_unknown_fields(collect);
+    }
+
+    /// Returns the paths of the unknown object fields ignored by the most recent deserialization, for example
+    /// `items[3].bogus`.
+    ///
+    /// Unlike [`error_path`](Self::error_path), the paths include the names of the unknown fields, which are
+    /// provided by the client and should not be treated as safe. The list is always empty unless unknown fields are
+    /// being ignored and collected.
+    pub fn take_unknown_fields(&mut self) -> Vec<String> {
+        self.path.take_unknown_fields()
     }
 }
 
@@ -99,7 +148,8 @@ where
 {
     impl_deserialize_body!(
         &'a mut serde_json::Deserializer<R>,
-        UnknownFieldsBehavior<ValueBehavior>
+        UnknownFieldsBehavior<ValueBehavior>,
+        ignore_unknown_fields = ValueBehavior
     );
 
     // we can't delegate this due to the signature, but luckily we know the answer
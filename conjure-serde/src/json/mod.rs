@@ -25,6 +25,7 @@
 //! This module provides `Serializer` and `Deserializer` implementations which wrap serde_json's and handle these
 //! special behaviors.
 
+pub use crate::json::de::array::{client_array_from_reader, ClientArrayDecoder, ClientArrayIter};
 pub use crate::json::de::client::{
     client_from_reader, client_from_slice, client_from_str, ClientDeserializer,
 };
@@ -168,3 +168,184 @@ fn server_unknown_fields() {
     assert!(e.to_string().contains("foo"));
     assert!(e.to_string().contains("bogus"));
 }
+
+#[test]
+fn server_ignore_unknown_fields() {
+    let json = r#"{"bogus": 1, "items": [{"foo": 1}, {"foo": 2, "extra": {"a": 1}}]}"#;
+
+    let mut de = crate::json::ServerDeserializer::from_str(json);
+    de.ignore_unknown_fields(true);
+    de.collect_unknown_fields(true);
+    let value = Bar::deserialize(&mut de).unwrap();
+    de.end().unwrap();
+    assert_eq!(value.items, [Foo { foo: 1 }, Foo { foo: 2 }]);
+    assert_eq!(de.take_unknown_fields(), ["bogus", "items[1].extra"]);
+    assert!(de.take_unknown_fields().is_empty());
+
+    let mut de = crate::json::ServerDeserializer::from_str(json);
+    de.ignore_unknown_fields(true);
+    Bar::deserialize(&mut de).unwrap();
+    assert!(de.take_unknown_fields().is_empty());
+
+    let mut de = crate::json::ServerDeserializer::from_str(json);
+    Bar::deserialize(&mut de).err().unwrap();
+    assert!(de.take_unknown_fields().is_empty());
+}
+
+#[test]
+fn client_array() {
+    let json = r#" [ {"foo": 1, "bogus": "a,]"}, {"foo": 2, "bogus": [1, {"x": "\"]"}]} ] "#;
+    let values = crate::json::client_array_from_reader::<_, Foo>(json.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values, [Foo { foo: 1 }, Foo { foo: 2 }]);
+
+    let values = crate::json::client_array_from_reader::<_, f64>(&br#"[1,"NaN"]"#[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(values[0], 1.);
+    assert!(values[1].is_nan());
+
+    let values = crate::json::client_array_from_reader::<_, i32>(&b" [ ] "[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(values.is_empty());
+}
+
+#[test]
+fn client_array_decoder() {
+    let json = r#" [ {"foo": 1, "bogus": "a,]"}, {"foo": 2, "bogus": [1, {"x": "\"]"}]} ] "#;
+    let mut decoder = crate::json::ClientArrayDecoder::<Foo>::new();
+    let mut values = vec![];
+    for b in json.as_bytes() {
+        // bytes which complete an element aren't consumed
+        let mut chunk = std::slice::from_ref(b);
+        while !chunk.is_empty() {
+            let (len, value) = decoder.decode(chunk);
+            chunk = &chunk[len..];
+            values.extend(value.map(Result::unwrap));
+        }
+    }
+    assert!(decoder.finish().is_none());
+    assert_eq!(values, [Foo { foo: 1 }, Foo { foo: 2 }]);
+
+    let mut decoder = crate::json::ClientArrayDecoder::<i32>::new();
+    let (len, value) = decoder.decode(b"[1, 2");
+    assert_eq!(len, 2);
+    assert_eq!(value.unwrap().unwrap(), 1);
+    let (len, value) = decoder.decode(b", 2");
+    assert_eq!(len, 3);
+    assert!(value.is_none());
+    decoder.finish().unwrap().unwrap_err();
+    assert!(decoder.finish().is_none());
+
+    let mut decoder = crate::json::ClientArrayDecoder::<i32>::new();
+    assert!(decoder.decode(b"[] x").1.unwrap().is_err());
+}
+
+#[test]
+fn client_array_errors() {
+    for json in ["{}", "[1, 2", "[1 2]", "[1,]", "[1] 2", "[\"a]"] {
+        let mut it = crate::json::client_array_from_reader::<_, i32>(json.as_bytes());
+        assert!(it.any(|r| r.is_err()), "{json}");
+        assert!(it.next().is_none(), "{json}");
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Bar {
+    #[serde(default)]
+    items: Vec<Foo>,
+    #[serde(default)]
+    map: BTreeMap<String, Foo>,
+    #[serde(default)]
+    opt: Option<Foo>,
+}
+
+fn client_error_path(json: &str) -> Option<String> {
+    let mut de = crate::json::ClientDeserializer::from_str(json);
+    Bar::deserialize(&mut de).err().unwrap();
+    de.error_path()
+}
+
+fn server_error_path(json: &str) -> Option<String> {
+    let mut de = crate::json::ServerDeserializer::from_str(json);
+    Bar::deserialize(&mut de).err().unwrap();
+    de.error_path()
+}
+
+#[test]
+fn error_path() {
+    let json = r#"{"items": [{"foo": 1}, {"foo": "hi"}]}"#;
+    assert_eq!(client_error_path(json).unwrap(), "items[1].foo");
+    assert_eq!(server_error_path(json).unwrap(), "items[1].foo");
+
+    let json = r#"{"map": {"secret": {"foo": true}}}"#;
+    assert_eq!(client_error_path(json).unwrap(), "map[*].foo");
+
+    let json = r#"{"opt": {"foo": null}}"#;
+    assert_eq!(client_error_path(json).unwrap(), "opt.foo");
+
+    let json = r#"{"items": [{"foo": 1}, {"foo": 2, "bogus": 3}]}"#;
+    assert_eq!(server_error_path(json).unwrap(), "items[1]");
+
+    let json = r#"{"items": [{"foo": 1}"#;
+    assert_eq!(client_error_path(json).unwrap(), "items[1]");
+
+    assert_eq!(client_error_path("1"), None);
+
+    let mut de = crate::json::ClientDeserializer::from_str(r#"{"items": [{"foo": 1}]}"#);
+    Bar::deserialize(&mut de).unwrap();
+    assert_eq!(de.error_path(), None);
+}
+
+// Ignores invalid values of `a`, but not of `b`.
+struct Lenient;
+
+impl<'de> Deserialize<'de> for Lenient {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = Lenient;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("a map")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Lenient, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                while let Some(key) = map.next_key::<String>()? {
+                    match &*key {
+                        "a" => {
+                            let _ = map.next_value::<i32>();
+                        }
+                        _ => {
+                            map.next_value::<i32>()?;
+                        }
+                    }
+                }
+                Ok(Lenient)
+            }
+        }
+
+        deserializer.deserialize_struct("Lenient", &["a", "b"], Visitor)
+    }
+}
+
+#[test]
+fn recovered_error_path() {
+    let mut de = crate::json::ClientDeserializer::from_str(r#"{"a": "x", "b": "y"}"#);
+    Lenient::deserialize(&mut de).err().unwrap();
+    assert_eq!(de.error_path().unwrap(), "b");
+
+    let mut de = crate::json::ClientDeserializer::from_str(r#"{"a": "x", "b": 1}"#);
+    Lenient::deserialize(&mut de).unwrap();
+    assert_eq!(de.error_path(), None);
+}
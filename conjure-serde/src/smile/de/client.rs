@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::de::path::PathTracker;
 use crate::de::Behavior;
 use crate::json::de::client::KeyBehavior;
 use serde::de;
@@ -53,7 +54,10 @@ where
 }
 
 /// A serde Smile deserializer appropriate for use by Conjure clients.
-pub struct ClientDeserializer<'de, R>(serde_smile::Deserializer<'de, R>);
+pub struct ClientDeserializer<'de, R> {
+    inner: serde_smile::Deserializer<'de, R>,
+    path: PathTracker,
+}
 
 impl<'de, R> ClientDeserializer<'de, IoRead<R>>
 where
@@ -61,21 +65,30 @@ where
 {
     /// Creates a Conjure Smile client deserializer from an `io::Read`.
     pub fn from_reader(reader: R) -> Self {
-        ClientDeserializer(serde_smile::Deserializer::from_reader(reader))
+        ClientDeserializer {
+            inner: serde_smile::Deserializer::from_reader(reader),
+            path: PathTracker::default(),
+        }
     }
 }
 
 impl<'a> ClientDeserializer<'a, SliceRead<'a>> {
     /// Creates a Conjure Smile client deserializer from a `&[u8]`.
     pub fn from_slice(bytes: &'a [u8]) -> Self {
-        ClientDeserializer(serde_smile::Deserializer::from_slice(bytes))
+        ClientDeserializer {
+            inner: serde_smile::Deserializer::from_slice(bytes),
+            path: PathTracker::default(),
+        }
     }
 }
 
 impl<'a> ClientDeserializer<'a, MutSliceRead<'a>> {
     /// Creates a Conjure Smile client deserializer from a `&mut [u8]`.
     pub fn from_mut_slice(bytes: &'a mut [u8]) -> Self {
-        ClientDeserializer(serde_smile::Deserializer::from_mut_slice(bytes))
+        ClientDeserializer {
+            inner: serde_smile::Deserializer::from_mut_slice(bytes),
+            path: PathTracker::default(),
+        }
     }
 }
 
@@ -85,22 +98,30 @@ where
 {
     /// Returns a shared reference to the inner reader.
     pub fn get_ref(&self) -> &R {
-        self.0.get_ref()
+        self.inner.get_ref()
     }
 
     /// Returns a mutable reference to the inner writer.
     pub fn get_mut(&mut self) -> &mut R {
-        self.0.get_mut()
+        self.inner.get_mut()
     }
 
     /// Consumes the `ClientDeserializer`, returning the inner reader.
     pub fn into_inner(self) -> R {
-        self.0.into_inner()
+        self.inner.into_inner()
     }
 
     /// Validates that the input stream is at the end or the Smile end of stream token.
     pub fn end(&mut self) -> Result<(), Error> {
-        self.0.end()
+        self.inner.end()
+    }
+
+    /// Returns the path to the value which caused the most recent deserialization error, if known.
+    ///
+    /// The path is built from struct field names and sequence indices, for example `items[3].owner`. Map keys are
+    /// not included since they may contain sensitive data.
+    pub fn error_path(&self) -> Option<String> {
+        self.path.error_path()
     }
 }
 
@@ -11,6 +11,7 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::de::path::PathTracker;
 use crate::de::unknown_fields_behavior::UnknownFieldsBehavior;
 use crate::smile::de::client::ValueBehavior;
 use serde::de;
@@ -53,7 +54,11 @@ where
 }
 
 /// A serde Smile deserializer appropriate for use by Conjure servers.
-pub struct ServerDeserializer<'de, R>(serde_smile::Deserializer<'de, R>);
+pub struct ServerDeserializer<'de, R> {
+    inner: serde_smile::Deserializer<'de, R>,
+    path: PathTracker,
+    ignore_unknown_fields: bool,
+}
 
 impl<'de, R> ServerDeserializer<'de, IoRead<R>>
 where
@@ -61,21 +66,33 @@ where
 {
     /// Creates a Conjure Smile server deserializer from an `io::Read`.
     pub fn from_reader(reader: R) -> Self {
-        ServerDeserializer(serde_smile::Deserializer::from_reader(reader))
+        ServerDeserializer {
+            inner: serde_smile::Deserializer::from_reader(reader),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
 impl<'a> ServerDeserializer<'a, SliceRead<'a>> {
     /// Creates a Conjure Smile server deserializer from a `&[u8]`.
     pub fn from_slice(bytes: &'a [u8]) -> Self {
-        ServerDeserializer(serde_smile::Deserializer::from_slice(bytes))
+        ServerDeserializer {
+            inner: serde_smile::Deserializer::from_slice(bytes),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
 impl<'a> ServerDeserializer<'a, MutSliceRead<'a>> {
     /// Creates a Conjure Smile server deserializer from a `&mut [u8]`.
     pub fn from_mut_slice(bytes: &'a mut [u8]) -> Self {
-        ServerDeserializer(serde_smile::Deserializer::from_mut_slice(bytes))
+        ServerDeserializer {
+            inner: serde_smile::Deserializer::from_mut_slice(bytes),
+            path: PathTracker::default(),
+            ignore_unknown_fields: false,
+        }
     }
 }
 
@@ -85,22 +102,53 @@ where
 {
     /// Returns a shared reference to the inner reader.
     pub fn get_ref(&self) -> &R {
-        self.0.get_ref()
+        self.inner.get_ref()
     }
 
     /// Returns a mutable reference to the inner writer.
     pub fn get_mut(&mut self) -> &mut R {
-        self.0.get_mut()
+        self.inner.get_mut()
     }
 
     /// Consumes the `ServerDeserializer`, returning the inner reader.
     pub fn into_inner(self) -> R {
-        self.0.into_inner()
+        self.inner.into_inner()
     }
 
     /// Validates that the input stream is at the end or the Smile end of stream token.
     pub fn end(&mut self) -> Result<(), Error> {
-        self.0.end()
+        self.inner.end()
+    }
+
+    /// Returns the path to the value which caused the most recent deserialization error, if known.
+    ///
+    /// The path is built from struct field names and sequence indices, for example `items[3].owner`. Map keys are
+    /// not included since they may contain sensitive data.
+    pub fn error_path(&self) -> Option<String> {
+        self.path.error_path()
+    }
+
+    /// Configures the deserializer to ignore unknown object fields rather than triggering errors.
+    pub fn ignore_unknown_fields(&mut self, ignore: bool) {
+        self.ignore_unknown_fields = ignore;
+    }
+
+    /// Configures the deserializer to record the paths of ignored unknown object fields.
+    ///
+    /// The paths can be retrieved with [`take_unknown_fields`](Self::take_unknown_fields). This has no effect unless
+    /// [`ignore_unknown_fields`](Self::ignore_unknown_fields) is also enabled.
+    pub fn collect_unknown_fields(&mut self, collect: bool) {
+        self.path.collect_unknown_fields(collect);
+    }
+
+    /// Returns the paths of the unknown object fields ignored by the most recent deserialization, for example
+    /// `items[3].bogus`.
+    ///
+    /// Unlike [`error_path`](Self::error_path), the paths include the names of the unknown fields, which are
+    /// provided by the client and should not be treated as safe. The list is always empty unless unknown fields are
+    /// being ignored and collected.
+    pub fn take_unknown_fields(&mut self) -> Vec<String> {
+        self.path.take_unknown_fields()
     }
 }
 
@@ -110,7 +158,8 @@ where
 {
     impl_deserialize_body!(
         &'a mut serde_smile::Deserializer<'de, R>,
-        UnknownFieldsBehavior<ValueBehavior>
+        UnknownFieldsBehavior<ValueBehavior>,
+        ignore_unknown_fields = ValueBehavior
     );
 
     fn is_human_readable(&self) -> bool {
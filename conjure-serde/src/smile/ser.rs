@@ -55,6 +55,13 @@ where
         )
     }
 
+    /// Writes the Smile header to the writer, if not already written.
+    ///
+    /// This happens automatically when the first value is serialized.
+    pub fn write_header(&mut self) -> Result<(), Error> {
+        self.0.write_header()
+    }
+
     /// Returns a shared reference to the inner writer.
     pub fn get_ref(&self) -> &W {
         self.0.get_ref()
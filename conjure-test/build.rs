@@ -8,6 +8,7 @@ fn main() {
     let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure");
     conjure_codegen::Config::new()
         .strip_prefix("com.palantir.conjure".to_string())
+        .external_type("java.lang.Long", "i64")
         .generate_files(input, output)
         .unwrap();
 
@@ -17,4 +18,45 @@ fn main() {
         .exhaustive(true)
         .generate_files(input, output)
         .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-smile-requests");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .smile_request_services(["com.palantir.conjure.TestService"])
+        .generate_files(input, output)
+        .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-extern");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .external_type("java.lang.Long", "i64")
+        .extern_crate("conjure-test", "0.0.0", [("com.palantir.conjure", "types")])
+        .extern_crate_types(
+            "conjure-test",
+            "0.0.0",
+            [(
+                "com.palantir.conjure.foo.SubpackageObject",
+                "types::foo::SubpackageObject",
+            )],
+        )
+        .generate_files(input, output)
+        .unwrap();
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-clients");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .generate_servers(false)
+        .client_services(["TinyService"])
+        .generate_files(input, output)
+        .unwrap();
+
+    let input = "validation-ir.json";
+    println!("cargo:rerun-if-changed={}", input);
+
+    let output = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("conjure-validation");
+    conjure_codegen::Config::new()
+        .strip_prefix("com.palantir.conjure".to_string())
+        .validation(true)
+        .generate_files(input, output)
+        .unwrap();
 }
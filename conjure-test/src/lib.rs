@@ -13,6 +13,8 @@
 // limitations under the License.
 #![warn(clippy::all)]
 
+extern crate self as conjure_test;
+
 #[cfg(test)]
 mod test;
 
@@ -25,3 +27,23 @@ mod types {
 mod exhaustive_types {
     include!(concat!(env!("OUT_DIR"), "/conjure-exhaustive/mod.rs"));
 }
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod smile_request_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-smile-requests/mod.rs"));
+}
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod extern_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-extern/mod.rs"));
+}
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod client_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-clients/mod.rs"));
+}
+
+#[allow(dead_code, unused_imports, clippy::all)]
+mod validation_types {
+    include!(concat!(env!("OUT_DIR"), "/conjure-validation/mod.rs"));
+}
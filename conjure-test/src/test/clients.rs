@@ -14,15 +14,18 @@
 
 use crate::test::RemoteBody;
 use crate::types::*;
+use bytes::Bytes;
 use conjure_error::Error;
 use conjure_http::client::{
-    AsyncClient, AsyncRequestBody, AsyncService, AsyncWriteBody, Client,
-    ConjureResponseDeserializer, DeserializeResponse, DisplaySeqEncoder, Endpoint, RequestBody,
-    SerializeRequest, Service, WriteBody,
+    AsyncClient, AsyncListResponse, AsyncRequestBody, AsyncService, AsyncWriteBody, Client,
+    ConjureRequestSerializer, ConjureResponseDeserializer, DeserializeResponse, DisplaySeqEncoder,
+    Endpoint, ListResponse, ListResponseDeserializer, RequestBody, SerializeRequest, Service,
+    SmileRequestSerializer, WriteBody,
 };
 use conjure_macros::{conjure_client, endpoint};
 use conjure_object::{BearerToken, ResourceIdentifier};
-use futures::executor;
+use conjure_serde::smile;
+use futures::{executor, Stream, StreamExt, TryStreamExt};
 use http::header::CONTENT_TYPE;
 use http::{HeaderMap, HeaderValue, Method, Request, Response, StatusCode};
 use std::collections::{BTreeMap, BTreeSet};
@@ -57,6 +60,7 @@ impl AsyncWriteBody<Vec<u8>> for StreamingBody<'_> {
 enum TestBody<T = Vec<u8>> {
     Empty,
     Json(String),
+    Smile(Vec<u8>),
     Streaming(T),
 }
 
@@ -118,8 +122,10 @@ impl<'b> Client for &'b TestClient {
             assert_eq!(endpoint, req.extensions().get::<Endpoint>().unwrap());
         }
 
+        let smile = is_smile(req.headers());
         let body = match req.into_body() {
             RequestBody::Empty => TestBody::Empty,
+            RequestBody::Fixed(body) if smile => TestBody::Smile(body.to_vec()),
             RequestBody::Fixed(body) => TestBody::Json(String::from_utf8(body.to_vec()).unwrap()),
             RequestBody::Streaming(mut body) => {
                 let mut buf = vec![];
@@ -139,6 +145,11 @@ impl<'b> Client for &'b TestClient {
                 .header(CONTENT_TYPE, "application/json")
                 .body(RemoteBody(json.as_bytes().to_vec()))
                 .unwrap()),
+            TestBody::Smile(smile) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/x-jackson-smile")
+                .body(RemoteBody(smile.clone()))
+                .unwrap()),
             TestBody::Streaming(buf) => Ok(Response::builder()
                 .status(StatusCode::OK)
                 .header(CONTENT_TYPE, "application/octet-stream")
@@ -164,8 +175,10 @@ impl AsyncClient for &'_ TestClient {
             assert_eq!(endpoint, req.extensions().get::<Endpoint>().unwrap());
         }
 
+        let smile = is_smile(req.headers());
         let body = match req.into_body() {
             AsyncRequestBody::Empty => TestBody::Empty,
+            AsyncRequestBody::Fixed(body) if smile => TestBody::Smile(body.to_vec()),
             AsyncRequestBody::Fixed(body) => {
                 TestBody::Json(String::from_utf8(body.to_vec()).unwrap())
             }
@@ -187,6 +200,11 @@ impl AsyncClient for &'_ TestClient {
                 .header(CONTENT_TYPE, "application/json")
                 .body(RemoteBody(json.as_bytes().to_vec()))
                 .unwrap()),
+            TestBody::Smile(smile) => Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header(CONTENT_TYPE, "application/x-jackson-smile")
+                .body(RemoteBody(smile.clone()))
+                .unwrap()),
             TestBody::Streaming(buf) => Ok(Response::builder()
                 .status(StatusCode::OK)
                 .header(CONTENT_TYPE, "application/octet-stream")
@@ -196,6 +214,12 @@ impl AsyncClient for &'_ TestClient {
     }
 }
 
+fn is_smile(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .is_some_and(|v| v == "application/x-jackson-smile")
+}
+
 macro_rules! check {
     ($client:ident, $call:expr) => {
         check!($client, $call, ());
@@ -256,6 +280,12 @@ trait CustomService {
     #[endpoint(method = POST, path = "/test/jsonRequest")]
     fn json_request(&self, #[body] body: &str) -> Result<(), Error>;
 
+    #[endpoint(method = POST, path = "/test/jsonRequest")]
+    fn smile_request(
+        &self,
+        #[body(serializer = SmileRequestSerializer)] body: &str,
+    ) -> Result<(), Error>;
+
     #[endpoint(method = GET, path = "/test/jsonResponse", accept = ConjureResponseDeserializer)]
     fn json_response(&self) -> Result<String, Error>;
 
@@ -297,6 +327,12 @@ trait CustomServiceAsync {
     #[endpoint(method = POST, path = "/test/jsonRequest")]
     async fn json_request(&self, #[body] body: &str) -> Result<(), Error>;
 
+    #[endpoint(method = POST, path = "/test/jsonRequest")]
+    async fn smile_request(
+        &self,
+        #[body(serializer = SmileRequestSerializer)] body: &str,
+    ) -> Result<(), Error>;
+
     #[endpoint(method = GET, path = "/test/jsonResponse", accept = ConjureResponseDeserializer)]
     async fn json_response(&self) -> Result<String, Error>;
 
@@ -310,6 +346,24 @@ trait CustomServiceAsync {
     ) -> Result<(), Error>;
 }
 
+#[conjure_client]
+trait ListService<#[response_body] I>
+where
+    I: Iterator<Item = Result<Bytes, Error>>,
+{
+    #[endpoint(method = GET, path = "/test/list", accept = ListResponseDeserializer)]
+    fn list(&self) -> Result<ListResponse<String, I>, Error>;
+}
+
+#[conjure_client]
+trait AsyncListService<#[response_body] I>
+where
+    I: Stream<Item = Result<Bytes, Error>>,
+{
+    #[endpoint(method = GET, path = "/test/list", accept = ListResponseDeserializer)]
+    async fn list(&self) -> Result<AsyncListResponse<String, I>, Error>;
+}
+
 #[test]
 fn custom_query_params() {
     let client = TestClient::new(
@@ -355,10 +409,55 @@ fn custom_json_request() {
     check_custom!(client, client.json_request("hello world"));
 }
 
+#[test]
+fn custom_smile_request() {
+    let client = TestClient::new(Method::POST, "/test/jsonRequest")
+        .header("Content-Type", "application/x-jackson-smile")
+        .body(TestBody::Smile(smile::to_vec("hello world").unwrap()));
+    check_custom!(client, client.smile_request("hello world"));
+}
+
+#[conjure_client(serializer = SmileRequestSerializer)]
+trait SmileService {
+    #[endpoint(method = POST, path = "/test/jsonRequest")]
+    fn smile_request(&self, #[body] body: &str) -> Result<(), Error>;
+
+    #[endpoint(method = POST, path = "/test/jsonRequest")]
+    fn json_request(
+        &self,
+        #[body(serializer = ConjureRequestSerializer)] body: &str,
+    ) -> Result<(), Error>;
+}
+
+#[test]
+fn service_smile_request() {
+    let client = TestClient::new(Method::POST, "/test/jsonRequest")
+        .header("Content-Type", "application/x-jackson-smile")
+        .body(TestBody::Smile(smile::to_vec("hello world").unwrap()));
+    SmileServiceClient::new(&client)
+        .smile_request("hello world")
+        .unwrap();
+
+    let client = TestClient::new(Method::POST, "/test/jsonRequest")
+        .header("Content-Type", "application/json")
+        .body(TestBody::Json(r#""hello world""#.to_string()));
+    SmileServiceClient::new(&client)
+        .json_request("hello world")
+        .unwrap();
+}
+
+#[test]
+fn custom_smile_response() {
+    let client = TestClient::new(Method::GET, "/test/jsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json")
+        .response(TestBody::Smile(smile::to_vec("hello world").unwrap()));
+    check_custom!(client, client.json_response(), "hello world");
+}
+
 #[test]
 fn custom_json_repsonse() {
     let client = TestClient::new(Method::GET, "/test/jsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#""hello world""#.to_string()));
     check_custom!(client, client.json_response(), "hello world");
 }
@@ -386,7 +485,7 @@ fn query_params() {
         Method::GET,
         "/test/queryParams?normal=hello%20world&custom=10&list=1&list=2&set=true",
     )
-    .header("Accept", "application/json");
+    .header("Accept", "application/x-jackson-smile, application/json");
     let mut set = BTreeSet::new();
     set.insert(true);
     check!(
@@ -395,7 +494,7 @@ fn query_params() {
     );
 
     let client = TestClient::new(Method::GET, "/test/queryParams?normal=foo")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(
         client,
         client.query_params("foo", None, &[], &BTreeSet::new())
@@ -408,7 +507,7 @@ fn path_params() {
         Method::GET,
         "/test/pathParams/hello%20world/false/raw/ri.conjure.main.test.foo",
     )
-    .header("Accept", "application/json");
+    .header("Accept", "application/x-jackson-smile, application/json");
 
     check!(
         client,
@@ -424,27 +523,27 @@ fn path_params() {
 fn headers() {
     let client = TestClient::new(Method::GET, "/test/headers")
         .header("Some-Custom-Header", "hello world")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.headers("hello world", None));
 
     let client = TestClient::new(Method::GET, "/test/headers")
         .header("Some-Custom-Header", "hello world")
         .header("Some-Optional-Header", "2")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.headers("hello world", Some(2)));
 }
 
 #[test]
 fn empty_request() {
-    let client =
-        TestClient::new(Method::POST, "/test/emptyRequest").header("Accept", "application/json");
+    let client = TestClient::new(Method::POST, "/test/emptyRequest")
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.empty_request());
 }
 
 #[test]
 fn unexpected_json_response() {
     let client = TestClient::new(Method::POST, "/test/emptyRequest")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#""hello world""#.to_string()));
     check!(client, client.empty_request());
 }
@@ -454,7 +553,7 @@ fn json_request() {
     let client = TestClient::new(Method::POST, "/test/jsonRequest")
         .header("Content-Type", "application/json")
         .header("Content-Length", "13")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .body(TestBody::Json(r#""hello world""#.to_string()));
     check!(client, client.json_request("hello world"));
 }
@@ -464,23 +563,42 @@ fn optional_json_request() {
     let client = TestClient::new(Method::POST, "/test/optionalJsonRequest")
         .header("Content-Type", "application/json")
         .header("Content-Length", "13")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .body(TestBody::Json(r#""hello world""#.to_string()));
     check!(client, client.optional_json_request(Some("hello world")));
 
     let client = TestClient::new(Method::POST, "/test/optionalJsonRequest")
         .header("Content-Type", "application/json")
         .header("Content-Length", "4")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .body(TestBody::Json("null".to_string()));
     check!(client, client.optional_json_request(None));
 }
 
+#[test]
+fn generated_smile_request() {
+    let body = smile::to_vec("hello world").unwrap();
+    let raw_client = TestClient::new(Method::POST, "/test/jsonRequest")
+        .header("Content-Type", "application/x-jackson-smile")
+        .header("Content-Length", &body.len().to_string())
+        .header("Accept", "application/x-jackson-smile, application/json")
+        .body(TestBody::Smile(body));
+
+    crate::smile_request_types::TestServiceClient::new(&raw_client)
+        .json_request("hello world")
+        .unwrap();
+    executor::block_on(
+        crate::smile_request_types::TestServiceAsyncClient::new(&raw_client)
+            .json_request("hello world"),
+    )
+    .unwrap();
+}
+
 #[test]
 fn streaming_request() {
     let client = TestClient::new(Method::POST, "/test/streamingRequest")
         .header("Content-Type", "application/octet-stream")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .body(TestBody::Streaming(vec![0, 1, 2, 3]));
     check!(
         client,
@@ -492,7 +610,7 @@ fn streaming_request() {
 fn streaming_alias_request() {
     let client = TestClient::new(Method::POST, "/test/streamingAliasRequest")
         .header("Content-Type", "application/octet-stream")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .body(TestBody::Streaming(vec![0, 1, 2, 3]));
     check!(
         client,
@@ -503,15 +621,32 @@ fn streaming_alias_request() {
 #[test]
 fn json_response() {
     let client = TestClient::new(Method::GET, "/test/jsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#""hello world""#.to_string()));
     check!(client, client.json_response(), "hello world");
 }
 
+#[test]
+fn smile_response() {
+    let client = TestClient::new(Method::GET, "/test/jsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json")
+        .response(TestBody::Smile(smile::to_vec("hello world").unwrap()));
+    check!(client, client.json_response(), "hello world");
+
+    let client = TestClient::new(Method::GET, "/test/listJsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json")
+        .response(TestBody::Smile(smile::to_vec(&["hello", "world"]).unwrap()));
+    check!(
+        client,
+        client.list_json_response(),
+        vec!["hello".to_string(), "world".to_string()]
+    );
+}
+
 #[test]
 fn optional_json_response() {
     let client = TestClient::new(Method::GET, "/test/optionalJsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#""hello world""#.to_string()));
     check!(
         client,
@@ -520,18 +655,18 @@ fn optional_json_response() {
     );
 
     let client = TestClient::new(Method::GET, "/test/optionalJsonResponse")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.optional_json_response(), None);
 }
 
 #[test]
 fn list_json_response() {
-    let client =
-        TestClient::new(Method::GET, "/test/listJsonResponse").header("Accept", "application/json");
+    let client = TestClient::new(Method::GET, "/test/listJsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.list_json_response(), Vec::<String>::new());
 
     let client = TestClient::new(Method::GET, "/test/listJsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#"["hello"]"#.to_string()));
     check!(
         client,
@@ -542,12 +677,12 @@ fn list_json_response() {
 
 #[test]
 fn set_json_response() {
-    let client =
-        TestClient::new(Method::GET, "/test/setJsonResponse").header("Accept", "application/json");
+    let client = TestClient::new(Method::GET, "/test/setJsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.set_json_response(), BTreeSet::new());
 
     let client = TestClient::new(Method::GET, "/test/setJsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#"["hello"]"#.to_string()));
     let mut set = BTreeSet::new();
     set.insert("hello".to_string());
@@ -556,12 +691,12 @@ fn set_json_response() {
 
 #[test]
 fn map_json_response() {
-    let client =
-        TestClient::new(Method::GET, "/test/mapJsonResponse").header("Accept", "application/json");
+    let client = TestClient::new(Method::GET, "/test/mapJsonResponse")
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(client, client.map_json_response(), BTreeMap::new());
 
     let client = TestClient::new(Method::GET, "/test/mapJsonResponse")
-        .header("Accept", "application/json")
+        .header("Accept", "application/x-jackson-smile, application/json")
         .response(TestBody::Json(r#"{"hello": "world"}"#.to_string()));
     let mut map = BTreeMap::new();
     map.insert("hello".to_string(), "world".to_string());
@@ -628,7 +763,7 @@ fn optional_streaming_alias_response() {
 fn header_auth() {
     let client = TestClient::new(Method::GET, "/test/headerAuth")
         .header("Authorization", "Bearer fizzbuzz")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(
         client,
         client.header_auth(&BearerToken::new("fizzbuzz").unwrap())
@@ -639,7 +774,7 @@ fn header_auth() {
 fn cookie_auth() {
     let client = TestClient::new(Method::GET, "/test/cookieAuth")
         .header("Cookie", "foobar=fizzbuzz")
-        .header("Accept", "application/json");
+        .header("Accept", "application/x-jackson-smile, application/json");
     check!(
         client,
         client.cookie_auth(&BearerToken::new("fizzbuzz").unwrap())
@@ -754,3 +889,84 @@ fn unversioned_custom_endpoint_config() {
 
     UnversionedCustomConfigClient::new(&client).foo().unwrap();
 }
+
+#[test]
+fn client_only_services() {
+    let client = TestClient::new(Method::POST, "/tiny/foo")
+        .header("Content-Type", "application/octet-stream")
+        .header("Accept", "application/octet-stream")
+        .body(TestBody::Streaming(vec![0, 1, 2, 3]))
+        .response(TestBody::Streaming(b"foobar".to_vec()));
+    let response = crate::client_types::TinyServiceClient::new(&client)
+        .foo(StreamingBody(&[0, 1, 2, 3][..]))
+        .unwrap();
+    assert_eq!(response, RemoteBody(b"foobar".to_vec()));
+
+    // server bindings and other services' clients shouldn't be generated
+    let root = include_str!(concat!(env!("OUT_DIR"), "/conjure-clients/mod.rs"));
+    assert!(root.contains("TinyServiceClient"));
+    assert!(!root.contains("TinyServiceEndpoints"));
+    assert!(!root.contains("TestService"));
+}
+
+#[test]
+fn list_response() {
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Json(r#"["foo", "bar"]"#.to_string()));
+    let response = ListServiceClient::new(&client)
+        .list()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(response, ["foo", "bar"]);
+
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Json(r#"["foo", 1]"#.to_string()));
+    let mut response = ListServiceClient::new(&client).list().unwrap();
+    assert_eq!(response.next().unwrap().unwrap(), "foo");
+    response.next().unwrap().unwrap_err();
+    assert!(response.next().is_none());
+
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Empty);
+    let mut response = ListServiceClient::new(&client).list().unwrap();
+    assert!(response.next().is_none());
+}
+
+#[test]
+fn async_list_response() {
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Json(r#"["foo", "bar"]"#.to_string()));
+    let response = executor::block_on(async {
+        AsyncListServiceClient::new(&client)
+            .list()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+    })
+    .unwrap();
+    assert_eq!(response, ["foo", "bar"]);
+
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Json(r#"["foo", 1]"#.to_string()));
+    executor::block_on(async {
+        let mut response = AsyncListServiceClient::new(&client).list().await.unwrap();
+        assert_eq!(response.next().await.unwrap().unwrap(), "foo");
+        response.next().await.unwrap().unwrap_err();
+        assert!(response.next().await.is_none());
+    });
+
+    let client = TestClient::new(Method::GET, "/test/list")
+        .header("Accept", "application/json")
+        .response(TestBody::Empty);
+    executor::block_on(async {
+        let mut response = AsyncListServiceClient::new(&client).list().await.unwrap();
+        assert!(response.next().await.is_none());
+    });
+}
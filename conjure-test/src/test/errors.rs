@@ -12,9 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use conjure_error::{ErrorCode, ErrorType};
+use conjure_error::{Error, ErrorCode, ErrorKind, ErrorType, NotFound, SerializableError};
+use conjure_http::client::{self, RemoteError};
+use conjure_http::server::{self, AsyncResponseBody, ResponseBody};
+use futures::executor;
+use http::{Response, StatusCode};
 use std::collections::BTreeMap;
+use std::time::Duration;
 
+use crate::test::RemoteBody;
 use crate::types::*;
 
 #[test]
@@ -41,3 +47,150 @@ fn error_serialization() {
     params.insert("unsafeFoo".to_string(), "false".to_string());
     assert_eq!(*encoded.parameters(), params);
 }
+
+#[test]
+fn service_error_response() {
+    let error = Error::service_safe("not found", NotFound::new());
+
+    let response = server::error_response::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()["Content-Type"], "application/json");
+    let body = match response.into_body() {
+        ResponseBody::Fixed(body) => body,
+        _ => panic!("expected a fixed body"),
+    };
+    let encoded = conjure_serde::json::client_from_slice::<SerializableError>(&body).unwrap();
+    assert_eq!(*encoded.error_code(), ErrorCode::NotFound);
+    assert_eq!(encoded.error_name(), "Default:NotFound");
+
+    let error = Error::internal_safe("oops");
+    let response = server::async_error_response::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    match response.into_body() {
+        AsyncResponseBody::Fixed(_) => {}
+        _ => panic!("expected a fixed body"),
+    }
+}
+
+#[test]
+fn qos_error_response() {
+    let error = Error::throttle_for_safe("slow down", Duration::from_millis(1500));
+    let response = server::error_response::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["Retry-After"], "2");
+
+    let error = Error::throttle_safe("slow down");
+    let response = server::error_response::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(!response.headers().contains_key("Retry-After"));
+
+    let error = Error::unavailable_safe("go away");
+    let response = server::async_error_response::<Vec<u8>>(&error);
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    match response.into_body() {
+        AsyncResponseBody::Empty => {}
+        _ => panic!("expected an empty body"),
+    }
+}
+
+fn error_response(status: StatusCode, body: &[u8]) -> Response<RemoteBody> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(RemoteBody(body.to_vec()))
+        .unwrap()
+}
+
+#[test]
+fn decode_service_error() {
+    let encoded = SerializableError::builder()
+        .error_code(ErrorCode::Internal)
+        .error_name("Test:SimpleError")
+        .error_instance_id(conjure_object::Uuid::nil())
+        .insert_parameters("foo", "hello")
+        .insert_parameters("bar", "15")
+        .insert_parameters("baz", "{}")
+        .insert_parameters("unsafeFoo", "true")
+        .build();
+    let body = conjure_serde::json::to_vec(&encoded).unwrap();
+
+    let error = client::decode_error(error_response(StatusCode::INTERNAL_SERVER_ERROR, &body));
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e, encoded),
+        _ => panic!("expected a service error"),
+    }
+    let cause = error.cause().downcast_ref::<RemoteError>().unwrap();
+    assert_eq!(cause.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(cause.error(), Some(&encoded));
+
+    let typed = client::downcast_error::<SimpleError>(&error).unwrap();
+    assert_eq!(typed.foo(), "hello");
+    assert_eq!(typed.bar(), 15);
+    assert!(typed.unsafe_foo());
+
+    assert!(client::downcast_error::<NotFound>(&error).is_none());
+
+    let error = executor::block_on(client::async_decode_error(error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &body,
+    )));
+    assert!(client::downcast_error::<SimpleError>(&error).is_some());
+}
+
+#[test]
+fn decode_invalid_error() {
+    let error = client::decode_error(error_response(StatusCode::BAD_GATEWAY, b"<html>"));
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::Internal),
+        _ => panic!("expected a service error"),
+    }
+    let cause = error.cause().downcast_ref::<RemoteError>().unwrap();
+    assert_eq!(cause.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(cause.error(), None);
+}
+
+#[test]
+fn decode_oversized_error() {
+    let encoded = SerializableError::builder()
+        .error_code(ErrorCode::Internal)
+        .error_name("Test:SimpleError")
+        .error_instance_id(conjure_object::Uuid::nil())
+        .build();
+    let mut body = conjure_serde::json::to_vec(&encoded).unwrap();
+    body.resize(2 * 1024 * 1024, b' ');
+
+    let error = client::decode_error(error_response(StatusCode::INTERNAL_SERVER_ERROR, &body));
+    let cause = error.cause().downcast_ref::<RemoteError>().unwrap();
+    assert_eq!(cause.error(), None);
+
+    let error = executor::block_on(client::async_decode_error(error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &body,
+    )));
+    let cause = error.cause().downcast_ref::<RemoteError>().unwrap();
+    assert_eq!(cause.error(), None);
+}
+
+#[test]
+fn decode_qos_error() {
+    let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, b"");
+    response
+        .headers_mut()
+        .insert("Retry-After", "3".parse().unwrap());
+    match client::decode_error(response).kind() {
+        ErrorKind::Throttle(e) => assert_eq!(e.duration(), Some(Duration::from_secs(3))),
+        _ => panic!("expected a throttle error"),
+    }
+
+    let response = error_response(StatusCode::TOO_MANY_REQUESTS, b"");
+    match client::decode_error(response).kind() {
+        ErrorKind::Throttle(e) => assert_eq!(e.duration(), None),
+        _ => panic!("expected a throttle error"),
+    }
+
+    let response = error_response(StatusCode::SERVICE_UNAVAILABLE, b"");
+    match executor::block_on(client::async_decode_error(response)).kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::test::RemoteBody;
+use conjure_error::{Error, ErrorCode, ErrorKind, PermissionDenied};
+use conjure_http::server::{
+    AsyncEndpoint, AsyncFilter, AsyncResponseBody, AsyncRouter, BoxAsyncEndpoint, ConjureRuntime,
+    Endpoint, Filter, FilteredEndpoint, FilteredService, ResponseBody, Router, Service,
+    StdResponseSerializer,
+};
+use conjure_http::SafeParams;
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use http::header::AUTHORIZATION;
+use http::{Extensions, Request, Response};
+use std::sync::{Arc, Mutex};
+
+#[conjure_endpoints]
+trait Gadgets {
+    #[endpoint(method = GET, path = "/gadgets", produces = StdResponseSerializer)]
+    fn list_gadgets(&self) -> Result<Vec<String>, Error>;
+}
+
+#[conjure_endpoints(name = "Gadgets")]
+trait AsyncGadgets {
+    #[endpoint(method = GET, path = "/gadgets", produces = StdResponseSerializer)]
+    async fn list_gadgets(&self) -> Result<Vec<String>, Error>;
+}
+
+struct GadgetsHandler;
+
+impl Gadgets for GadgetsHandler {
+    fn list_gadgets(&self) -> Result<Vec<String>, Error> {
+        Ok(vec!["gizmo".to_string()])
+    }
+}
+
+impl AsyncGadgets for GadgetsHandler {
+    async fn list_gadgets(&self) -> Result<Vec<String>, Error> {
+        Ok(vec!["gizmo".to_string()])
+    }
+}
+
+struct Auth;
+
+impl Auth {
+    fn check<I>(&self, req: &Request<I>) -> Result<(), Error> {
+        match req.headers().get(AUTHORIZATION) {
+            Some(value) if value == "Bearer secret" => Ok(()),
+            _ => Err(Error::service_safe(
+                "missing credentials",
+                PermissionDenied::new(),
+            )),
+        }
+    }
+}
+
+impl<I, O> Filter<I, O> for Auth {
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &dyn Endpoint<I, O>,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        self.check(&req)?;
+        next.handle(req, response_extensions)
+    }
+}
+
+impl<I, O> AsyncFilter<I, O> for Auth
+where
+    I: Send,
+{
+    async fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &BoxAsyncEndpoint<'static, I, O>,
+    ) -> Result<Response<AsyncResponseBody<O>>, Error> {
+        self.check(&req)?;
+        next.handle(req, response_extensions).await
+    }
+}
+
+struct Logger(Mutex<Vec<String>>);
+
+impl<I, O> Filter<I, O> for Logger {
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &dyn Endpoint<I, O>,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        let result = next.handle(req, response_extensions);
+
+        let mut safe_params = response_extensions
+            .remove::<SafeParams>()
+            .unwrap_or_default();
+        safe_params.insert("endpoint", &next.name());
+        response_extensions.insert(safe_params);
+
+        self.0.lock().unwrap().push(format!(
+            "{}.{} deprecated={:?} ok={}",
+            next.service_name(),
+            next.name(),
+            next.deprecated(),
+            result.is_ok(),
+        ));
+        result
+    }
+}
+
+fn request(authorization: Option<&'static str>) -> Request<RemoteBody> {
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.uri_mut() = "/gadgets".parse().unwrap();
+    if let Some(authorization) = authorization {
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, authorization.parse().unwrap());
+    }
+    request
+}
+
+fn assert_permission_denied(error: Error) {
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::PermissionDenied),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn filter_chain() {
+    let logger = Arc::new(Logger(Mutex::new(vec![])));
+    let router = Router::<RemoteBody, Vec<u8>>::builder()
+        .service(FilteredService::new(
+            FilteredService::new(GadgetsEndpoints::new(GadgetsHandler), Auth),
+            logger.clone(),
+        ))
+        .build()
+        .unwrap();
+
+    let mut extensions = Extensions::new();
+    let response = router
+        .handle(request(Some("Bearer secret")), &mut extensions)
+        .unwrap();
+    match response.into_body() {
+        ResponseBody::Fixed(bytes) => assert_eq!(bytes, r#"["gizmo"]"#),
+        _ => panic!("expected a fixed body"),
+    }
+    let safe_params = extensions.get::<SafeParams>().unwrap();
+    let names = safe_params.iter().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(names, ["endpoint"]);
+
+    let error = router
+        .handle(request(None), &mut Extensions::new())
+        .err()
+        .unwrap();
+    assert_permission_denied(error);
+
+    assert_eq!(
+        *logger.0.lock().unwrap(),
+        [
+            "Gadgets.list_gadgets deprecated=None ok=true",
+            "Gadgets.list_gadgets deprecated=None ok=false",
+        ],
+    );
+}
+
+#[test]
+fn async_filter() {
+    let router = AsyncRouter::<RemoteBody, Vec<u8>>::builder()
+        .service(FilteredService::new(
+            AsyncGadgetsEndpoints::new(GadgetsHandler),
+            Auth,
+        ))
+        .build()
+        .unwrap();
+
+    let response =
+        executor::block_on(router.handle(request(Some("Bearer secret")), &mut Extensions::new()))
+            .unwrap();
+    match response.into_body() {
+        AsyncResponseBody::Fixed(bytes) => assert_eq!(bytes, r#"["gizmo"]"#),
+        _ => panic!("expected a fixed body"),
+    }
+
+    let error = executor::block_on(router.handle(request(None), &mut Extensions::new()))
+        .err()
+        .unwrap();
+    assert_permission_denied(error);
+}
+
+#[test]
+fn filtered_endpoint() {
+    let runtime = Arc::new(ConjureRuntime::new());
+    let endpoint =
+        Service::<RemoteBody, Vec<u8>>::endpoints(&GadgetsEndpoints::new(GadgetsHandler), &runtime)
+            .pop()
+            .unwrap();
+    let endpoint = FilteredEndpoint::new(endpoint, Auth);
+
+    endpoint
+        .handle(request(Some("Bearer secret")), &mut Extensions::new())
+        .unwrap();
+    let error = endpoint
+        .handle(request(None), &mut Extensions::new())
+        .err()
+        .unwrap();
+    assert_permission_denied(error);
+}
@@ -0,0 +1,288 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use conjure_error::{Error, ErrorCode, ErrorKind, NotFound};
+use conjure_http::client::{
+    self, AsyncClient, AsyncRequestBody, AsyncService, Client, ConjureResponseDeserializer,
+    RequestBody, Service, SmileRequestSerializer,
+};
+use conjure_http::loopback::{AsyncLoopbackClient, LoopbackClient};
+use conjure_http::server::{
+    AsyncEndpoint, AsyncFilter, AsyncResponseBody, BoxAsyncEndpoint, Endpoint, Filter,
+    FilteredService, ResponseBody, StdResponseSerializer,
+};
+use conjure_macros::{conjure_client, conjure_endpoints, endpoint};
+use futures::executor;
+use http::{Extensions, Request, Response};
+use std::time::Duration;
+
+#[conjure_endpoints]
+trait Yaks {
+    #[endpoint(method = GET, path = "/yaks/{id}", produces = StdResponseSerializer)]
+    fn get_yak(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/yaks", produces = StdResponseSerializer)]
+    fn create_yak(&self, #[body] name: String) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/throttle")]
+    fn throttle(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/unavailable")]
+    fn unavailable(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints(name = "Yaks")]
+trait AsyncYaks {
+    #[endpoint(method = GET, path = "/yaks/{id}", produces = StdResponseSerializer)]
+    async fn get_yak(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/yaks", produces = StdResponseSerializer)]
+    async fn create_yak(&self, #[body] name: String) -> Result<String, Error>;
+}
+
+struct YaksHandler;
+
+impl Yaks for YaksHandler {
+    fn get_yak(&self, id: i32) -> Result<String, Error> {
+        if id < 0 {
+            return Err(Error::service_safe("no such yak", NotFound::new()));
+        }
+
+        Ok(format!("yak {id}"))
+    }
+
+    fn create_yak(&self, name: String) -> Result<String, Error> {
+        Ok(format!("created {name}"))
+    }
+
+    fn throttle(&self) -> Result<(), Error> {
+        Err(Error::throttle_for_safe(
+            "slow down",
+            Duration::from_secs(5),
+        ))
+    }
+
+    fn unavailable(&self) -> Result<(), Error> {
+        Err(Error::unavailable_safe("go away"))
+    }
+}
+
+impl AsyncYaks for YaksHandler {
+    async fn get_yak(&self, id: i32) -> Result<String, Error> {
+        Yaks::get_yak(self, id)
+    }
+
+    async fn create_yak(&self, name: String) -> Result<String, Error> {
+        Yaks::create_yak(self, name)
+    }
+}
+
+#[conjure_client(name = "Yaks")]
+trait YaksApi {
+    #[endpoint(method = GET, path = "/yaks/{id}", accept = ConjureResponseDeserializer)]
+    fn get_yak(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/yaks", accept = ConjureResponseDeserializer)]
+    fn create_yak(&self, #[body] name: &str) -> Result<String, Error>;
+
+    #[endpoint(
+        method = POST,
+        path = "/yaks",
+        name = "create_yak",
+        accept = ConjureResponseDeserializer
+    )]
+    fn create_yak_smile(
+        &self,
+        #[body(serializer = SmileRequestSerializer)] name: &str,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/throttle")]
+    fn throttle(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/unavailable")]
+    fn unavailable(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/yaks/{id}")]
+    fn misnamed(&self, #[path] id: i32) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/missing")]
+    fn missing(&self) -> Result<(), Error>;
+
+    #[endpoint(method = DELETE, path = "/yaks", name = "create_yak")]
+    fn delete_yaks(&self) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/other/{id}", name = "get_yak")]
+    fn moved(&self, #[path] id: i32) -> Result<(), Error>;
+}
+
+#[conjure_client(name = "Yaks")]
+trait AsyncYaksApi {
+    #[endpoint(method = GET, path = "/yaks/{id}", accept = ConjureResponseDeserializer)]
+    async fn get_yak(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = POST, path = "/yaks", accept = ConjureResponseDeserializer)]
+    async fn create_yak(&self, #[body] name: &str) -> Result<String, Error>;
+}
+
+fn client() -> YaksApiClient<LoopbackClient> {
+    YaksApiClient::new(LoopbackClient::new(YaksEndpoints::new(YaksHandler)).unwrap())
+}
+
+#[test]
+fn round_trip() {
+    let client = client();
+
+    assert_eq!(client.get_yak(1).unwrap(), "yak 1");
+    assert_eq!(client.create_yak("bob").unwrap(), "created bob");
+    assert_eq!(client.create_yak_smile("alice").unwrap(), "created alice");
+}
+
+#[test]
+fn service_error() {
+    let error = client().get_yak(-1).err().unwrap();
+
+    match error.kind() {
+        ErrorKind::Service(e) => {
+            assert_eq!(*e.error_code(), ErrorCode::NotFound);
+            assert_eq!(e.error_name(), "Default:NotFound");
+        }
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn qos_errors() {
+    let client = client();
+
+    match client.throttle().err().unwrap().kind() {
+        ErrorKind::Throttle(e) => assert_eq!(e.duration(), Some(Duration::from_secs(5))),
+        _ => panic!("expected a throttle error"),
+    }
+
+    match client.unavailable().err().unwrap().kind() {
+        ErrorKind::Unavailable(_) => {}
+        _ => panic!("expected an unavailable error"),
+    }
+}
+
+#[test]
+fn routing_errors() {
+    let client = client();
+
+    match client.missing().err().unwrap().kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::NotFound),
+        _ => panic!("expected a service error"),
+    }
+
+    match client.delete_yaks().err().unwrap().kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::Internal),
+        _ => panic!("expected a service error"),
+    }
+
+    match client.misnamed(1).err().unwrap().kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::NotFound),
+        _ => panic!("expected a service error"),
+    }
+
+    match client.moved(1).err().unwrap().kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::NotFound),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[test]
+fn async_round_trip() {
+    let client = AsyncYaksApiClient::new(
+        AsyncLoopbackClient::new(AsyncYaksEndpoints::new(YaksHandler)).unwrap(),
+    );
+
+    assert_eq!(executor::block_on(client.get_yak(1)).unwrap(), "yak 1");
+    assert_eq!(
+        executor::block_on(client.create_yak("bob")).unwrap(),
+        "created bob"
+    );
+
+    let error = executor::block_on(client.get_yak(-1)).err().unwrap();
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::NotFound),
+        _ => panic!("expected a service error"),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Handled(&'static str);
+
+struct Tagger;
+
+impl<I, O> Filter<I, O> for Tagger {
+    fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &dyn Endpoint<I, O>,
+    ) -> Result<Response<ResponseBody<O>>, Error> {
+        response_extensions.insert(Handled("blocking"));
+        next.handle(req, response_extensions)
+    }
+}
+
+impl<I, O> AsyncFilter<I, O> for Tagger
+where
+    I: Send,
+{
+    async fn handle(
+        &self,
+        req: Request<I>,
+        response_extensions: &mut Extensions,
+        next: &BoxAsyncEndpoint<'static, I, O>,
+    ) -> Result<Response<AsyncResponseBody<O>>, Error> {
+        response_extensions.insert(Handled("async"));
+        next.handle(req, response_extensions).await
+    }
+}
+
+fn get_yak_request<B>(body: B) -> Request<B> {
+    let mut request = Request::new(body);
+    *request.uri_mut() = "/yaks/1".parse().unwrap();
+    request
+        .extensions_mut()
+        .insert(client::Endpoint::new("Yaks", None, "get_yak", "/yaks/{id}"));
+    request
+}
+
+#[test]
+fn response_extensions() {
+    let client = LoopbackClient::new(FilteredService::new(
+        YaksEndpoints::new(YaksHandler),
+        Tagger,
+    ))
+    .unwrap();
+    let response = client.send(get_yak_request(RequestBody::Empty)).unwrap();
+    assert_eq!(
+        response.extensions().get::<Handled>(),
+        Some(&Handled("blocking")),
+    );
+
+    let client = AsyncLoopbackClient::new(FilteredService::new(
+        AsyncYaksEndpoints::new(YaksHandler),
+        Tagger,
+    ))
+    .unwrap();
+    let response =
+        executor::block_on(client.send(get_yak_request(AsyncRequestBody::Empty))).unwrap();
+    assert_eq!(
+        response.extensions().get::<Handled>(),
+        Some(&Handled("async")),
+    );
+}
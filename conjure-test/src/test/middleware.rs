@@ -0,0 +1,246 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use conjure_error::{Error, NotFound};
+use conjure_http::client::{
+    AsyncClient, AsyncRequestBody, AsyncService, Client, ConjureResponseDeserializer, Endpoint,
+    RequestBody, Service,
+};
+use conjure_http::loopback::{AsyncLoopbackClient, LoopbackClient};
+use conjure_http::middleware::{AsyncMiddleware, Middleware, MiddlewareBuilder};
+use conjure_http::server::StdResponseSerializer;
+use conjure_macros::{conjure_client, conjure_endpoints, endpoint};
+use futures::executor;
+use http::{HeaderValue, Request, Response};
+use std::sync::{Arc, Mutex};
+
+#[conjure_endpoints]
+trait Traces {
+    #[endpoint(method = GET, path = "/trace", produces = StdResponseSerializer)]
+    fn get_trace(&self, #[header(name = "X-Trace-Id")] trace_id: String) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/missing")]
+    fn missing(&self) -> Result<(), Error>;
+}
+
+#[conjure_endpoints(name = "Traces")]
+trait AsyncTraces {
+    #[endpoint(method = GET, path = "/trace", produces = StdResponseSerializer)]
+    async fn get_trace(
+        &self,
+        #[header(name = "X-Trace-Id")] trace_id: String,
+    ) -> Result<String, Error>;
+}
+
+struct TracesHandler;
+
+impl Traces for TracesHandler {
+    fn get_trace(&self, trace_id: String) -> Result<String, Error> {
+        Ok(trace_id)
+    }
+
+    fn missing(&self) -> Result<(), Error> {
+        Err(Error::service_safe("missing", NotFound::new()))
+    }
+}
+
+impl AsyncTraces for TracesHandler {
+    async fn get_trace(&self, trace_id: String) -> Result<String, Error> {
+        Ok(trace_id)
+    }
+}
+
+#[conjure_client(name = "Traces")]
+trait TracesApi {
+    #[endpoint(method = GET, path = "/trace", accept = ConjureResponseDeserializer)]
+    fn get_trace(&self) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/missing")]
+    fn missing(&self) -> Result<(), Error>;
+}
+
+#[conjure_client(name = "Traces")]
+trait AsyncTracesApi {
+    #[endpoint(method = GET, path = "/trace", accept = ConjureResponseDeserializer)]
+    async fn get_trace(&self) -> Result<String, Error>;
+}
+
+struct TraceId(&'static str);
+
+impl<C> Middleware<C> for TraceId
+where
+    C: Client,
+{
+    fn send(
+        &self,
+        mut req: Request<RequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> Result<Response<C::ResponseBody>, Error> {
+        req.headers_mut()
+            .insert("X-Trace-Id", HeaderValue::from_static(self.0));
+        next.send(req)
+    }
+}
+
+impl<C> AsyncMiddleware<C> for TraceId
+where
+    C: AsyncClient + Sync,
+{
+    async fn send(
+        &self,
+        mut req: Request<AsyncRequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> Result<Response<C::ResponseBody>, Error> {
+        req.headers_mut()
+            .insert("X-Trace-Id", HeaderValue::from_static(self.0));
+        next.send(req).await
+    }
+}
+
+#[derive(Clone)]
+struct Recorder {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Recorder {
+    fn before<B>(&self, req: &Request<B>) {
+        let endpoint = req.extensions().get::<Endpoint>().unwrap();
+        self.log.lock().unwrap().push(format!(
+            "{} request {}.{}",
+            self.name,
+            endpoint.service(),
+            endpoint.name()
+        ));
+    }
+
+    fn after<B>(&self, result: &Result<Response<B>, Error>) {
+        let outcome = match result {
+            Ok(response) => response.status().to_string(),
+            Err(error) => error.cause().to_string(),
+        };
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} response {}", self.name, outcome));
+    }
+}
+
+impl<C> Middleware<C> for Recorder
+where
+    C: Client,
+{
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> Result<Response<C::ResponseBody>, Error> {
+        self.before(&req);
+        let result = next.send(req);
+        self.after(&result);
+        result
+    }
+}
+
+impl<C> AsyncMiddleware<C> for Recorder
+where
+    C: AsyncClient + Sync,
+{
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, C::BodyWriter>>,
+        next: &C,
+    ) -> Result<Response<C::ResponseBody>, Error> {
+        self.before(&req);
+        let result = next.send(req).await;
+        self.after(&result);
+        result
+    }
+}
+
+fn recorders() -> (Recorder, Recorder, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(vec![]));
+    let outer = Recorder {
+        name: "outer",
+        log: log.clone(),
+    };
+    let inner = Recorder {
+        name: "inner",
+        log: log.clone(),
+    };
+    (outer, inner, log)
+}
+
+#[test]
+fn middleware_chain() {
+    let (outer, inner, log) = recorders();
+    let client = MiddlewareBuilder::new()
+        .layer(outer)
+        .layer(TraceId("abc"))
+        .layer(inner)
+        .build(LoopbackClient::new(TracesEndpoints::new(TracesHandler)).unwrap());
+    let client = TracesApiClient::new(client);
+
+    assert_eq!(client.get_trace().unwrap(), "abc");
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer request Traces.get_trace",
+            "inner request Traces.get_trace",
+            "inner response 200 OK",
+            "outer response 200 OK",
+        ],
+    );
+}
+
+#[test]
+fn middleware_error() {
+    let (outer, _, log) = recorders();
+    let client = TracesApiClient::new(
+        MiddlewareBuilder::new()
+            .layer(outer)
+            .build(LoopbackClient::new(TracesEndpoints::new(TracesHandler)).unwrap()),
+    );
+
+    client.missing().unwrap_err();
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer request Traces.missing",
+            "outer response remote error: 404 Not Found (Default:NotFound)",
+        ],
+    );
+}
+
+#[test]
+fn async_middleware_chain() {
+    let (outer, inner, log) = recorders();
+    let client = MiddlewareBuilder::new()
+        .layer(outer)
+        .layer(TraceId("abc"))
+        .layer(inner)
+        .build(AsyncLoopbackClient::new(AsyncTracesEndpoints::new(TracesHandler)).unwrap());
+    let client = AsyncTracesApiClient::new(client);
+
+    assert_eq!(executor::block_on(client.get_trace()).unwrap(), "abc");
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "outer request Traces.get_trace",
+            "inner request Traces.get_trace",
+            "inner response 200 OK",
+            "outer response 200 OK",
+        ],
+    );
+}
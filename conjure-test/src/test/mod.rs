@@ -20,8 +20,14 @@ use std::task::{Context, Poll};
 
 mod clients;
 mod errors;
+mod filters;
+mod loopback;
+mod middleware;
 mod objects;
+mod retries;
+mod routers;
 mod servers;
+mod validation;
 
 #[derive(Debug, PartialEq)]
 struct RemoteBody(Vec<u8>);
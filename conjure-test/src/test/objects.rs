@@ -16,8 +16,9 @@ use crate::types::*;
 use bytes::Bytes;
 use conjure_object::Any;
 use conjure_object::DoubleKey;
+use conjure_object::SafeLong;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use std::f64;
 use std::fmt::Debug;
@@ -98,6 +99,23 @@ fn empty_fields() {
     );
 }
 
+#[test]
+fn external_types() {
+    let object = ExternalObject::builder()
+        .value(1i64)
+        .optional(2)
+        .push_list(3)
+        .build();
+    let value: &i64 = object.value();
+    assert_eq!(*value, 1);
+    test_serde(&object, r#"{"value": 1, "optional": 2, "list": [3]}"#);
+
+    let object = crate::exhaustive_types::ExternalObject::builder()
+        .value(SafeLong::new(1).unwrap())
+        .build();
+    test_serde(&object, r#"{"value": 1}"#);
+}
+
 #[test]
 fn enums() {
     test_serde(&TestEnum::One, r#""ONE""#);
@@ -208,6 +226,15 @@ fn union_trailing_fields() {
     assert!(e.is_data());
 }
 
+// unions are deserialized as maps, so their values are reported anonymously like other map values
+#[test]
+fn union_error_path() {
+    let json = r#"{"type": "object", "object": {"foo": "hi"}}"#;
+    let mut de = conjure_serde::json::ClientDeserializer::from_str(json);
+    TestUnion::deserialize(&mut de).err().unwrap();
+    assert_eq!(de.error_path().unwrap(), "[*].foo");
+}
+
 // just make sure that things end up in the right modules
 #[test]
 fn subpackage() {
@@ -215,6 +242,16 @@ fn subpackage() {
     bar::baz::OtherSubpackageObject::new(foo::SubpackageObject::new(IntegerAlias(1)));
 }
 
+// types from extern packages and extern types should be reused rather than regenerated
+#[test]
+fn extern_packages() {
+    let object = crate::extern_types::bar::baz::OtherSubpackageObject::new(
+        foo::SubpackageObject::new(IntegerAlias(1)),
+    );
+    let _: &foo::SubpackageObject = object.foo();
+    test_serde(&object, r#"{"foo": {"foo": 1}}"#);
+}
+
 #[test]
 fn binary() {
     let json = r#"
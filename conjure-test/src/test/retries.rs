@@ -0,0 +1,287 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::test::RemoteBody;
+use bytes::Bytes;
+use conjure_error::{Error, ErrorKind};
+use conjure_http::client::{
+    self, AsyncClient, AsyncRequestBody, AsyncWriteBody, BoxAsyncWriteBody, Client, RequestBody,
+    WriteBody,
+};
+use conjure_http::retry::RetryingClient;
+use futures::executor;
+use http::{Method, Request, Response, StatusCode};
+use std::io::Write;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Fails the first requests with the provided errors, recording the body of each attempt.
+struct FlakyClient {
+    errors: Mutex<Vec<Error>>,
+    bodies: Mutex<Vec<Vec<u8>>>,
+}
+
+impl FlakyClient {
+    fn new(errors: impl IntoIterator<Item = Error>) -> Self {
+        let mut errors = errors.into_iter().collect::<Vec<_>>();
+        errors.reverse();
+        FlakyClient {
+            errors: Mutex::new(errors),
+            bodies: Mutex::new(vec![]),
+        }
+    }
+
+    fn attempts(&self) -> usize {
+        self.bodies.lock().unwrap().len()
+    }
+
+    fn finish(&self, body: Vec<u8>) -> Result<Response<RemoteBody>, Error> {
+        self.bodies.lock().unwrap().push(body);
+        match self.errors.lock().unwrap().pop() {
+            Some(error) => Err(error),
+            None => Ok(Response::new(RemoteBody(vec![]))),
+        }
+    }
+}
+
+impl Client for FlakyClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    fn send(
+        &self,
+        req: Request<RequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let body = match req.into_body() {
+            RequestBody::Empty => vec![],
+            RequestBody::Fixed(bytes) => bytes.to_vec(),
+            RequestBody::Streaming(mut writer) => {
+                let mut buf = vec![];
+                writer.write_body(&mut buf)?;
+                buf
+            }
+        };
+        self.finish(body)
+    }
+}
+
+impl AsyncClient for FlakyClient {
+    type BodyWriter = Vec<u8>;
+    type ResponseBody = RemoteBody;
+
+    async fn send(
+        &self,
+        req: Request<AsyncRequestBody<'_, Self::BodyWriter>>,
+    ) -> Result<Response<Self::ResponseBody>, Error> {
+        let body = match req.into_body() {
+            AsyncRequestBody::Empty => vec![],
+            AsyncRequestBody::Fixed(bytes) => bytes.to_vec(),
+            AsyncRequestBody::Streaming(mut writer) => {
+                let mut buf = vec![];
+                Pin::new(&mut writer).write_body(Pin::new(&mut buf)).await?;
+                buf
+            }
+        };
+        self.finish(body)
+    }
+}
+
+// Writes a counter so each attempt's body reflects how many times it has been reset.
+struct CountingBody {
+    resets: u32,
+    resettable: bool,
+}
+
+impl CountingBody {
+    fn new(resettable: bool) -> Self {
+        CountingBody {
+            resets: 0,
+            resettable,
+        }
+    }
+}
+
+impl WriteBody<Vec<u8>> for CountingBody {
+    fn write_body(&mut self, w: &mut Vec<u8>) -> Result<(), Error> {
+        write!(w, "body {}", self.resets).map_err(Error::internal_safe)
+    }
+
+    fn reset(&mut self) -> bool {
+        self.resets += 1;
+        self.resettable
+    }
+}
+
+impl AsyncWriteBody<Vec<u8>> for CountingBody {
+    async fn write_body(self: Pin<&mut Self>, mut w: Pin<&mut Vec<u8>>) -> Result<(), Error> {
+        WriteBody::write_body(self.get_mut(), &mut w)
+    }
+
+    async fn reset(self: Pin<&mut Self>) -> bool {
+        WriteBody::reset(self.get_mut())
+    }
+}
+
+fn retrying(client: FlakyClient) -> RetryingClient<FlakyClient> {
+    RetryingClient::builder(client)
+        .backoff_slot_size(Duration::from_millis(1))
+        .build()
+}
+
+fn request<B>(method: Method, body: B) -> Request<B> {
+    let mut request = Request::new(body);
+    *request.method_mut() = method;
+    request
+}
+
+fn remote_error(status: StatusCode) -> Error {
+    let mut response = Response::new(RemoteBody(vec![]));
+    *response.status_mut() = status;
+    client::decode_error(response)
+}
+
+#[test]
+fn retry_qos_errors() {
+    let client = retrying(FlakyClient::new([
+        Error::unavailable_safe("unavailable"),
+        Error::throttle_safe("throttle"),
+        remote_error(StatusCode::SERVICE_UNAVAILABLE),
+    ]));
+
+    let body = RequestBody::Fixed(Bytes::from_static(b"hello"));
+    Client::send(&client, request(Method::POST, body)).unwrap();
+
+    assert_eq!(
+        *client.get_ref().bodies.lock().unwrap(),
+        vec![b"hello".to_vec(); 4]
+    );
+}
+
+#[test]
+fn retry_idempotent_requests() {
+    let client = retrying(FlakyClient::new([Error::internal_safe("connection reset")]));
+    Client::send(&client, request(Method::GET, RequestBody::Empty)).unwrap();
+    assert_eq!(client.get_ref().attempts(), 2);
+
+    let client = retrying(FlakyClient::new([Error::internal_safe("connection reset")]));
+    let error = Client::send(&client, request(Method::POST, RequestBody::Empty)).unwrap_err();
+    assert_eq!(client.get_ref().attempts(), 1);
+    assert_eq!(error.cause().to_string(), "connection reset");
+}
+
+#[test]
+fn no_retry_remote_errors() {
+    let client = retrying(FlakyClient::new([remote_error(StatusCode::NOT_FOUND)]));
+
+    let error = Client::send(&client, request(Method::GET, RequestBody::Empty)).unwrap_err();
+    assert_eq!(client.get_ref().attempts(), 1);
+    assert!(matches!(error.kind(), ErrorKind::Service(_)));
+}
+
+#[test]
+fn max_num_retries() {
+    let client = RetryingClient::builder(FlakyClient::new(
+        (0..5).map(|_| Error::unavailable_safe("unavailable")),
+    ))
+    .max_num_retries(2)
+    .backoff_slot_size(Duration::from_millis(1))
+    .build();
+
+    let error = Client::send(&client, request(Method::GET, RequestBody::Empty)).unwrap_err();
+    assert_eq!(client.get_ref().attempts(), 3);
+    assert!(matches!(error.kind(), ErrorKind::Unavailable(_)));
+}
+
+#[test]
+fn reset_streaming_body() {
+    let client = retrying(FlakyClient::new([
+        Error::unavailable_safe("unavailable"),
+        Error::unavailable_safe("unavailable"),
+    ]));
+
+    let body = RequestBody::Streaming(Box::new(CountingBody::new(true)));
+    Client::send(&client, request(Method::POST, body)).unwrap();
+
+    assert_eq!(
+        *client.get_ref().bodies.lock().unwrap(),
+        vec![b"body 0".to_vec(), b"body 1".to_vec(), b"body 2".to_vec()]
+    );
+}
+
+#[test]
+fn non_resettable_streaming_body() {
+    let client = retrying(FlakyClient::new([Error::unavailable_safe("unavailable")]));
+
+    let body = RequestBody::Streaming(Box::new(CountingBody::new(false)));
+    let error = Client::send(&client, request(Method::POST, body)).unwrap_err();
+    assert_eq!(client.get_ref().attempts(), 1);
+    assert!(matches!(error.kind(), ErrorKind::Unavailable(_)));
+}
+
+#[test]
+fn async_retries() {
+    let delays = Arc::new(Mutex::new(vec![]));
+    let client = RetryingClient::builder(FlakyClient::new([
+        Error::throttle_for_safe("throttle", Duration::from_secs(5)),
+        Error::unavailable_safe("unavailable"),
+    ]))
+    .backoff_slot_size(Duration::from_millis(100))
+    .async_sleep({
+        let delays = delays.clone();
+        move |delay| {
+            delays.lock().unwrap().push(delay);
+            async {}
+        }
+    })
+    .build();
+
+    let body = AsyncRequestBody::Streaming(BoxAsyncWriteBody::new(CountingBody::new(true)));
+    executor::block_on(AsyncClient::send(&client, request(Method::POST, body))).unwrap();
+
+    assert_eq!(
+        *client.get_ref().bodies.lock().unwrap(),
+        vec![b"body 0".to_vec(), b"body 1".to_vec(), b"body 2".to_vec()]
+    );
+
+    let delays = delays.lock().unwrap();
+    assert_eq!(delays.len(), 2);
+    assert_eq!(delays[0], Duration::from_secs(5));
+    assert!(delays[1] <= Duration::from_millis(200));
+}
+
+#[test]
+fn throttle_duration_capped() {
+    let delays = Arc::new(Mutex::new(vec![]));
+    let client = RetryingClient::builder(FlakyClient::new([Error::throttle_for_safe(
+        "throttle",
+        Duration::from_secs(100_000_000),
+    )]))
+    .max_backoff(Duration::from_secs(1))
+    .async_sleep({
+        let delays = delays.clone();
+        move |delay| {
+            delays.lock().unwrap().push(delay);
+            async {}
+        }
+    })
+    .build();
+
+    executor::block_on(AsyncClient::send(
+        &client,
+        request(Method::GET, AsyncRequestBody::Empty),
+    ))
+    .unwrap();
+    assert_eq!(*delays.lock().unwrap(), [Duration::from_secs(1)]);
+}
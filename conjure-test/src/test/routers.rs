@@ -0,0 +1,280 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::test::RemoteBody;
+use conjure_error::{Error, ErrorCode, SerializableError};
+use conjure_http::server::{
+    AsyncResponseBody, AsyncRouter, FromStrSeqDecoder, ResponseBody, Route, Router,
+    StdResponseSerializer,
+};
+use conjure_macros::{conjure_endpoints, endpoint};
+use futures::executor;
+use http::header::ALLOW;
+use http::{Extensions, Method, Request, Response, StatusCode};
+
+#[conjure_endpoints]
+trait Widgets {
+    #[endpoint(method = GET, path = "/widgets/{id}", produces = StdResponseSerializer)]
+    fn get_widget(&self, #[path] id: String) -> Result<String, Error>;
+
+    #[endpoint(method = DELETE, path = "/widgets/{id}")]
+    fn delete_widget(&self, #[path] id: String) -> Result<(), Error>;
+
+    #[endpoint(method = GET, path = "/widgets/special", produces = StdResponseSerializer)]
+    fn special_widget(&self) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/numbers/{id:[0-9]+}", produces = StdResponseSerializer)]
+    fn get_number(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = r"/digits/{id:\d+}", produces = StdResponseSerializer)]
+    fn get_digits(&self, #[path] id: i32) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/files/{path:.+}", produces = StdResponseSerializer)]
+    fn get_file(
+        &self,
+        #[path(decoder = FromStrSeqDecoder<_>)] path: Vec<String>,
+    ) -> Result<String, Error>;
+
+    #[endpoint(method = GET, path = "/blobs/{path:.+/raw}", produces = StdResponseSerializer)]
+    fn get_raw_blob(
+        &self,
+        #[path(decoder = FromStrSeqDecoder<_>)] path: Vec<String>,
+    ) -> Result<String, Error>;
+}
+
+struct WidgetsHandler;
+
+impl Widgets for WidgetsHandler {
+    fn get_widget(&self, id: String) -> Result<String, Error> {
+        Ok(format!("widget {id}"))
+    }
+
+    fn delete_widget(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn special_widget(&self) -> Result<String, Error> {
+        Ok("special".to_string())
+    }
+
+    fn get_number(&self, id: i32) -> Result<String, Error> {
+        Ok(format!("number {id}"))
+    }
+
+    fn get_digits(&self, id: i32) -> Result<String, Error> {
+        Ok(format!("digits {id}"))
+    }
+
+    fn get_file(&self, path: Vec<String>) -> Result<String, Error> {
+        Ok(format!("file {}", path.join(" ")))
+    }
+
+    fn get_raw_blob(&self, path: Vec<String>) -> Result<String, Error> {
+        Ok(format!("blob {}", path.join(" ")))
+    }
+}
+
+#[conjure_endpoints]
+trait AsyncWidgets {
+    #[endpoint(method = GET, path = "/widgets/{id}", produces = StdResponseSerializer)]
+    async fn get_widget(&self, #[path] id: String) -> Result<String, Error>;
+
+    #[endpoint(method = DELETE, path = "/widgets/{id}")]
+    async fn delete_widget(&self, #[path] id: String) -> Result<(), Error>;
+}
+
+impl AsyncWidgets for WidgetsHandler {
+    async fn get_widget(&self, id: String) -> Result<String, Error> {
+        Ok(format!("widget {id}"))
+    }
+
+    async fn delete_widget(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[conjure_endpoints]
+trait ConflictingWidgets {
+    #[endpoint(method = GET, path = "/widgets/{name}")]
+    fn get_widget_by_name(&self, #[path] name: String) -> Result<(), Error>;
+}
+
+struct ConflictingWidgetsHandler;
+
+impl ConflictingWidgets for ConflictingWidgetsHandler {
+    fn get_widget_by_name(&self, _: String) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn router() -> Router<RemoteBody, Vec<u8>> {
+    Router::builder()
+        .service(WidgetsEndpoints::new(WidgetsHandler))
+        .build()
+        .unwrap()
+}
+
+fn handle(
+    router: &Router<RemoteBody, Vec<u8>>,
+    method: Method,
+    path: &str,
+) -> Result<Response<ResponseBody<Vec<u8>>>, Error> {
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.method_mut() = method;
+    *request.uri_mut() = path.parse().unwrap();
+
+    router.handle(request, &mut Extensions::new())
+}
+
+fn body_string(body: ResponseBody<Vec<u8>>) -> String {
+    match body {
+        ResponseBody::Empty => String::new(),
+        ResponseBody::Fixed(bytes) => String::from_utf8(bytes.to_vec()).unwrap(),
+        ResponseBody::Streaming(_) => panic!("unexpected streaming body"),
+    }
+}
+
+fn send(router: &Router<RemoteBody, Vec<u8>>, method: Method, path: &str) -> Result<String, Error> {
+    let response = handle(router, method, path)?;
+    assert!(response.status().is_success());
+    Ok(body_string(response.into_body()))
+}
+
+fn assert_not_found(router: &Router<RemoteBody, Vec<u8>>, method: Method, path: &str) {
+    let response = handle(router, method, path).unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = body_string(response.into_body());
+    let error = conjure_serde::json::client_from_str::<SerializableError>(&body).unwrap();
+    assert_eq!(*error.error_code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn literal_and_parameter_routes() {
+    let router = router();
+
+    assert_eq!(
+        send(&router, Method::GET, "/widgets/foo").unwrap(),
+        r#""widget foo""#
+    );
+    assert_eq!(
+        send(&router, Method::GET, "/widgets/hello%20world").unwrap(),
+        r#""widget hello world""#
+    );
+    assert_eq!(
+        send(&router, Method::GET, "/widgets/special").unwrap(),
+        r#""special""#
+    );
+    assert_eq!(send(&router, Method::DELETE, "/widgets/foo").unwrap(), "");
+}
+
+#[test]
+fn regex_routes() {
+    let router = router();
+
+    assert_eq!(
+        send(&router, Method::GET, "/numbers/42").unwrap(),
+        r#""number 42""#
+    );
+    assert_not_found(&router, Method::GET, "/numbers/foo");
+
+    assert_eq!(
+        send(&router, Method::GET, "/digits/42").unwrap(),
+        r#""digits 42""#
+    );
+    assert_not_found(&router, Method::GET, "/digits/foo");
+
+    assert_eq!(
+        send(&router, Method::GET, "/files/foo/bar/baz.txt").unwrap(),
+        r#""file foo bar baz.txt""#
+    );
+    assert_not_found(&router, Method::GET, "/files");
+
+    assert_eq!(
+        send(&router, Method::GET, "/blobs/foo/bar/raw").unwrap(),
+        r#""blob foo bar raw""#
+    );
+    assert_not_found(&router, Method::GET, "/blobs/foo/bar");
+}
+
+#[test]
+fn path_params() {
+    let router = router();
+
+    match router.route(&Method::GET, "/files/a/b") {
+        Route::Found {
+            endpoint,
+            path_params,
+        } => {
+            assert_eq!(endpoint.name(), "get_file");
+            assert_eq!(&path_params["path"], "a/b");
+        }
+        _ => panic!("expected a route"),
+    }
+}
+
+#[test]
+fn not_found() {
+    let router = router();
+
+    assert_not_found(&router, Method::GET, "/gadgets/foo");
+    assert_not_found(&router, Method::GET, "/widgets");
+    assert_not_found(&router, Method::GET, "/widgets/foo/bar");
+    assert_not_found(&router, Method::GET, "/widgets/");
+}
+
+#[test]
+fn method_not_allowed() {
+    let router = router();
+
+    let response = handle(&router, Method::POST, "/widgets/foo").unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get(ALLOW).unwrap(), "DELETE, GET");
+}
+
+#[test]
+fn conflicting_routes() {
+    let result = Router::<RemoteBody, Vec<u8>>::builder()
+        .service(WidgetsEndpoints::new(WidgetsHandler))
+        .service(ConflictingWidgetsEndpoints::new(ConflictingWidgetsHandler))
+        .build();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn async_router() {
+    let router = AsyncRouter::<RemoteBody, Vec<u8>>::builder()
+        .service(AsyncWidgetsEndpoints::new(WidgetsHandler))
+        .build()
+        .unwrap();
+
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.uri_mut() = "/widgets/foo".parse().unwrap();
+    let response = executor::block_on(router.handle(request, &mut Extensions::new())).unwrap();
+    match response.into_body() {
+        AsyncResponseBody::Fixed(bytes) => assert_eq!(bytes, r#""widget foo""#),
+        _ => panic!("expected a fixed body"),
+    }
+
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.method_mut() = Method::PUT;
+    *request.uri_mut() = "/widgets/foo".parse().unwrap();
+    let response = executor::block_on(router.handle(request, &mut Extensions::new())).unwrap();
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    let mut request = Request::new(RemoteBody(vec![]));
+    *request.uri_mut() = "/gadgets".parse().unwrap();
+    let response = executor::block_on(router.handle(request, &mut Extensions::new())).unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
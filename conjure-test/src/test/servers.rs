@@ -15,16 +15,21 @@
 
 use crate::test::RemoteBody;
 use crate::types::*;
+use bytes::Bytes;
 use conjure_error::{Error, ErrorCode, ErrorKind};
 use conjure_http::server::{
-    AsyncEndpoint, AsyncResponseBody, AsyncService, AsyncWriteBody, ConjureRuntime,
-    DeserializeRequest, Endpoint, EndpointMetadata, FromStrOptionDecoder, FromStrSeqDecoder,
-    RequestContext, ResponseBody, SerializeResponse, Service, StdResponseSerializer, WriteBody,
+    AsyncEndpoint, AsyncResponseBody, AsyncSerializeResponse, AsyncService, AsyncWriteBody,
+    ConjureRuntime, DeserializeRequest, Endpoint, EndpointMetadata, FromStrOptionDecoder,
+    FromStrSeqDecoder, IterResponseSerializer, RequestContext, ResponseBody, SerializeResponse,
+    Service, StdRequestDeserializer, StdResponseSerializer, StreamResponseSerializer,
+    UnknownFieldPolicy, WriteBody,
 };
 use conjure_http::{PathParams, SafeParams};
 use conjure_macros::{conjure_endpoints, endpoint};
 use conjure_object::{BearerToken, ResourceIdentifier};
-use futures::executor;
+use conjure_serde::smile;
+use futures::{executor, stream, Sink};
+use http::header::CONTENT_TYPE;
 use http::{Extensions, HeaderMap, Request, Response, Uri};
 use mockall::mock;
 use mockall::predicate::eq;
@@ -33,8 +38,13 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::io::Write;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use self::test_service::AsyncTestServiceEndpoints;
+use crate::validation_types::validation::{
+    AsyncValidationService, AsyncValidationServiceEndpoints, ValidationService,
+    ValidationServiceEndpoints, Wrapper,
+};
 
 macro_rules! test_service_handler {
     ($(
@@ -301,8 +311,10 @@ where
         );
         match (response, &self.response) {
             (Ok(response), Ok(expected)) => {
+                let smile = is_smile(response.headers());
                 let body = match response.into_body() {
                     ResponseBody::Empty => TestBody::Empty,
+                    ResponseBody::Fixed(bytes) if smile => TestBody::Smile(bytes.to_vec()),
                     ResponseBody::Fixed(bytes) => {
                         TestBody::Json(String::from_utf8(bytes.to_vec()).unwrap())
                     }
@@ -347,8 +359,10 @@ where
         );
         match (response, &self.response) {
             (Ok(response), Ok(expected)) => {
+                let smile = is_smile(response.headers());
                 let body = match response.into_body() {
                     AsyncResponseBody::Empty => TestBody::Empty,
+                    AsyncResponseBody::Fixed(bytes) if smile => TestBody::Smile(bytes.to_vec()),
                     AsyncResponseBody::Fixed(bytes) => {
                         TestBody::Json(String::from_utf8(bytes.to_vec()).unwrap())
                     }
@@ -391,9 +405,16 @@ impl AsyncWriteBody<Vec<u8>> for StreamingBody {
 enum TestBody {
     Empty,
     Json(String),
+    Smile(Vec<u8>),
     Streaming(Vec<u8>),
 }
 
+fn is_smile(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .is_some_and(|v| v == "application/x-jackson-smile")
+}
+
 #[test]
 fn query_params() {
     TestServiceHandler::new()
@@ -533,6 +554,36 @@ fn json_request() {
         .send("jsonRequest");
 }
 
+#[test]
+fn smile_request() {
+    TestServiceHandler::new()
+        .json_request(|body| {
+            assert_eq!(body, "hello world");
+            Ok(())
+        })
+        .call()
+        .body(&smile::to_vec("hello world").unwrap())
+        .header("Content-Type", "application/x-jackson-smile")
+        .send("jsonRequest");
+
+    TestServiceHandler::new()
+        .optional_json_request(|body| {
+            assert_eq!(body, Some("hello world".to_string()));
+            Ok(())
+        })
+        .call()
+        .header("Content-Type", "application/x-jackson-smile")
+        .body(&smile::to_vec("hello world").unwrap())
+        .send("optionalJsonRequest");
+
+    TestServiceHandler::new()
+        .call()
+        .body(br#""hello world""#)
+        .header("Content-Type", "text/plain")
+        .error(ErrorCode::InvalidArgument)
+        .send("jsonRequest");
+}
+
 #[test]
 fn optional_json_request() {
     TestServiceHandler::new()
@@ -630,6 +681,63 @@ fn json_response() {
         .send("jsonResponse");
 }
 
+#[test]
+fn smile_response() {
+    let smile = smile::to_vec("hello world").unwrap();
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "application/x-jackson-smile")
+        .response(TestBody::Smile(smile.clone()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "application/x-jackson-smile, application/json")
+        .response(TestBody::Smile(smile.clone()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "application/json, application/x-jackson-smile")
+        .response(TestBody::Json(r#""hello world""#.to_string()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "application/x-jackson-smile;q=0.5, */*")
+        .response(TestBody::Json(r#""hello world""#.to_string()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header("Accept", "application/x-jackson-smile;q=0.5, Application/*")
+        .response(TestBody::Json(r#""hello world""#.to_string()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .json_response(|| Ok("hello world".to_string()))
+        .call()
+        .header(
+            "Accept",
+            "application/json;q=0.1, application/x-jackson-smile;q=0.9",
+        )
+        .response(TestBody::Smile(smile.clone()))
+        .send("jsonResponse");
+
+    TestServiceHandler::new()
+        .list_json_response(|| Ok(vec!["hello".to_string(), "world".to_string()]))
+        .call()
+        .header("Accept", "application/x-jackson-smile")
+        .response(TestBody::Smile(smile::to_vec(&["hello", "world"]).unwrap()))
+        .send("listJsonResponse");
+}
+
 #[test]
 fn optional_json_response() {
     TestServiceHandler::new()
@@ -817,6 +925,218 @@ fn small_request_body() {
         .send("smallRequestBody");
 }
 
+struct ValidationServiceHandler;
+
+impl ValidationService for ValidationServiceHandler {
+    fn wrapper(&self, _: Wrapper) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn marked(&self, _: String, _: Option<String>, _: Vec<String>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl AsyncValidationService for ValidationServiceHandler {
+    async fn wrapper(&self, _: Wrapper) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn marked(&self, _: String, _: Option<String>, _: Vec<String>) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+fn validation_call() -> Call<(
+    ValidationServiceEndpoints<ValidationServiceHandler>,
+    AsyncValidationServiceEndpoints<ValidationServiceHandler>,
+)> {
+    Call::new((
+        ValidationServiceEndpoints::new(ValidationServiceHandler),
+        AsyncValidationServiceEndpoints::new(ValidationServiceHandler),
+    ))
+    .uri("/validation/wrapper")
+    .header("Content-Type", "application/json")
+}
+
+#[test]
+fn validated_request() {
+    validation_call()
+        .body(br#"{"colors":["RED"]}"#)
+        .send("wrapper");
+
+    validation_call()
+        .body(br#"{"colors":["BLUE"]}"#)
+        .error(ErrorCode::InvalidArgument)
+        .send("wrapper");
+}
+
+#[test]
+fn marker_constraints() {
+    validation_call()
+        .uri("/validation/marked?name=foo")
+        .header("Tag", "bar")
+        .body(br#"["a","b"]"#)
+        .send("marked");
+
+    validation_call()
+        .uri("/validation/marked?name=fooo")
+        .body(br#"[]"#)
+        .error(ErrorCode::InvalidArgument)
+        .send("marked");
+
+    validation_call()
+        .uri("/validation/marked?name=foo")
+        .header("Tag", "barr")
+        .body(br#"[]"#)
+        .error(ErrorCode::InvalidArgument)
+        .send("marked");
+
+    validation_call()
+        .uri("/validation/marked?name=foo")
+        .body(br#"["a","b","c"]"#)
+        .error(ErrorCode::InvalidArgument)
+        .send("marked");
+}
+
+#[test]
+fn request_error_path() {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+    let body = br#"{"colors":["RED",1]}"#;
+    let error = <StdRequestDeserializer as DeserializeRequest<Wrapper, _>>::deserialize(
+        &ConjureRuntime::new(),
+        &headers,
+        std::iter::once(Ok(Bytes::from_static(body))),
+    )
+    .err()
+    .unwrap();
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::InvalidArgument),
+        _ => panic!("expected service error"),
+    }
+    assert_eq!(error.safe_params()["path"].as_str(), Some("colors[1]"));
+
+    let body = br#"{"colors":[]"#;
+    let error = <StdRequestDeserializer as DeserializeRequest<Wrapper, _>>::deserialize(
+        &ConjureRuntime::new(),
+        &headers,
+        std::iter::once(Ok(Bytes::from_static(body))),
+    )
+    .err()
+    .unwrap();
+    assert!(error.safe_params().is_empty());
+}
+
+#[conjure_endpoints]
+trait UnknownFieldsService {
+    #[endpoint(method = POST, path = "/test/unknownFields", produces = StdResponseSerializer)]
+    fn unknown_fields(
+        &self,
+        #[context] context: RequestContext<'_>,
+        #[body] body: Wrapper,
+    ) -> Result<Vec<String>, Error>;
+}
+
+struct UnknownFieldsServiceHandler;
+
+impl UnknownFieldsService for UnknownFieldsServiceHandler {
+    fn unknown_fields(
+        &self,
+        context: RequestContext<'_>,
+        _: Wrapper,
+    ) -> Result<Vec<String>, Error> {
+        Ok(context.unknown_fields().to_vec())
+    }
+}
+
+fn send_unknown_fields(policy: UnknownFieldPolicy) -> Result<String, Error> {
+    let runtime = ConjureRuntime::builder()
+        .unknown_field_policy(policy)
+        .build();
+    let endpoint = Service::<RemoteBody, Vec<u8>>::endpoints(
+        &UnknownFieldsServiceEndpoints::new(UnknownFieldsServiceHandler),
+        &Arc::new(runtime),
+    )
+    .pop()
+    .unwrap();
+
+    let mut request = Request::new(RemoteBody(
+        br#"{"colors":["RED"],"bogus":1,"constrained":{"name":"a","count":1,"id":1,"other":2}}"#
+            .to_vec(),
+    ));
+    request
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json".parse().unwrap());
+
+    match endpoint
+        .handle(request, &mut Extensions::new())?
+        .into_body()
+    {
+        ResponseBody::Fixed(body) => Ok(String::from_utf8(body.to_vec()).unwrap()),
+        _ => panic!("expected fixed body"),
+    }
+}
+
+#[test]
+fn unknown_field_policy() {
+    let error = send_unknown_fields(UnknownFieldPolicy::Reject)
+        .err()
+        .unwrap();
+    match error.kind() {
+        ErrorKind::Service(e) => assert_eq!(*e.error_code(), ErrorCode::InvalidArgument),
+        _ => panic!("expected service error"),
+    }
+
+    assert_eq!(
+        send_unknown_fields(UnknownFieldPolicy::Ignore).unwrap(),
+        "[]"
+    );
+
+    assert_eq!(
+        send_unknown_fields(UnknownFieldPolicy::Collect).unwrap(),
+        r#"["bogus","constrained.other"]"#,
+    );
+}
+
+#[test]
+fn endpoint_metadata() {
+    let runtime = Arc::new(ConjureRuntime::new());
+
+    let endpoints = Service::<RemoteBody, Vec<u8>>::endpoints(
+        &TestServiceEndpoints::new(TestServiceHandler::new()),
+        &runtime,
+    );
+    let endpoint = endpoints
+        .iter()
+        .find(|e| e.name() == "contextNoArgs")
+        .unwrap();
+    assert_eq!(endpoint.tags(), ["server-request-context"]);
+    assert_eq!(
+        endpoint.markers(),
+        ["com.palantir.conjure.java.lib.internal.Incubating"]
+    );
+
+    let endpoint = endpoints.iter().find(|e| e.name() == "context").unwrap();
+    assert_eq!(endpoint.tags(), ["server-request-context"]);
+    assert!(endpoint.markers().is_empty());
+
+    let endpoints = AsyncService::<RemoteBody, Vec<u8>>::endpoints(
+        &AsyncTestServiceEndpoints::new(TestServiceHandler::new()),
+        &runtime,
+    );
+    let endpoint = endpoints
+        .iter()
+        .find(|e| e.name() == "contextNoArgs")
+        .unwrap();
+    assert_eq!(endpoint.tags(), ["server-request-context"]);
+    assert_eq!(
+        endpoint.markers(),
+        ["com.palantir.conjure.java.lib.internal.Incubating"]
+    );
+}
+
 #[conjure_endpoints]
 trait CustomService {
     #[endpoint(method = GET, path = "/test/queryParams")]
@@ -864,7 +1184,12 @@ trait CustomService {
         #[body(safe)] body: String,
     ) -> Result<(), Error>;
 
-    #[endpoint(method = GET, path = "/test/context")]
+    #[endpoint(
+        method = GET,
+        path = "/test/context",
+        tags = ["custom-tag"],
+        markers = ["com.example.Authenticated"]
+    )]
     fn context(&self, #[context] context: RequestContext<'_>) -> Result<(), Error>;
 }
 
@@ -1023,6 +1348,22 @@ fn custom_context() {
         .send_sync("context");
 }
 
+#[test]
+fn custom_endpoint_metadata() {
+    let endpoints = Service::<RemoteBody, Vec<u8>>::endpoints(
+        &CustomServiceEndpoints::new(MockCustomService::new()),
+        &Arc::new(ConjureRuntime::new()),
+    );
+
+    let endpoint = endpoints.iter().find(|e| e.name() == "context").unwrap();
+    assert_eq!(endpoint.tags(), ["custom-tag"]);
+    assert_eq!(endpoint.markers(), ["com.example.Authenticated"]);
+
+    let endpoint = endpoints.iter().find(|e| e.name() == "headers").unwrap();
+    assert!(endpoint.tags().is_empty());
+    assert!(endpoint.markers().is_empty());
+}
+
 #[conjure_endpoints]
 trait CustomStreamingService<#[request_body] I, #[response_writer] O>
 where
@@ -1136,3 +1477,114 @@ fn custom_config() {
     assert_eq!(endpoints[0].service_name(), "service_name");
     assert_eq!(endpoints[0].name(), "name");
 }
+
+fn write_streaming_body(response: Response<ResponseBody<Vec<u8>>>) -> Vec<u8> {
+    let ResponseBody::Streaming(body) = response.into_body() else {
+        panic!("expected streaming body");
+    };
+    let mut buf = vec![];
+    body.write_body(&mut buf).unwrap();
+    buf
+}
+
+#[derive(Default)]
+struct ChunkSink(Vec<u8>);
+
+impl Sink<Bytes> for ChunkSink {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Bytes) -> Result<(), Error> {
+        self.get_mut().0.extend_from_slice(&item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn async_write_streaming_body(response: Response<AsyncResponseBody<ChunkSink>>) -> Vec<u8> {
+    let AsyncResponseBody::Streaming(body) = response.into_body() else {
+        panic!("expected streaming body");
+    };
+    let mut sink = ChunkSink::default();
+    executor::block_on(body.write_body(Pin::new(&mut sink))).unwrap();
+    sink.0
+}
+
+#[test]
+fn iter_response() {
+    let runtime = ConjureRuntime::new();
+    let items = vec![1., f64::NAN, 3.];
+
+    let response = <IterResponseSerializer as SerializeResponse<_, Vec<u8>>>::serialize(
+        &runtime,
+        &HeaderMap::new(),
+        items.clone(),
+    )
+    .unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(write_streaming_body(response), br#"[1.0,"NaN",3.0]"#);
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Accept", "application/x-jackson-smile".parse().unwrap());
+    let response = <IterResponseSerializer as SerializeResponse<_, Vec<u8>>>::serialize(
+        &runtime,
+        &headers,
+        items.clone(),
+    )
+    .unwrap();
+    assert!(is_smile(response.headers()));
+    assert_eq!(
+        write_streaming_body(response),
+        smile::to_vec(&items).unwrap()
+    );
+}
+
+#[test]
+fn stream_response() {
+    let runtime = ConjureRuntime::new();
+    let items = (0..10_000).map(|i| i.to_string()).collect::<Vec<_>>();
+
+    let response = <StreamResponseSerializer as AsyncSerializeResponse<_, ChunkSink>>::serialize(
+        &runtime,
+        &HeaderMap::new(),
+        stream::iter(items.clone()),
+    )
+    .unwrap();
+    assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+    assert_eq!(
+        async_write_streaming_body(response),
+        conjure_serde::json::to_vec(&items).unwrap(),
+    );
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Accept", "application/x-jackson-smile".parse().unwrap());
+    let response = <StreamResponseSerializer as AsyncSerializeResponse<_, ChunkSink>>::serialize(
+        &runtime,
+        &headers,
+        stream::iter(items.clone()),
+    )
+    .unwrap();
+    assert!(is_smile(response.headers()));
+    assert_eq!(
+        async_write_streaming_body(response),
+        smile::to_vec(&items).unwrap(),
+    );
+
+    let response = <StreamResponseSerializer as AsyncSerializeResponse<_, ChunkSink>>::serialize(
+        &runtime,
+        &HeaderMap::new(),
+        stream::empty::<i32>(),
+    )
+    .unwrap();
+    assert_eq!(async_write_streaming_body(response), b"[]");
+}
@@ -0,0 +1,111 @@
+// Copyright 2024 Palantir Technologies, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::validation_types::validation::*;
+use conjure_object::Validate;
+use serde::de::DeserializeOwned;
+
+fn validate<T>(json: &str) -> Result<(), String>
+where
+    T: DeserializeOwned + Validate,
+{
+    conjure_serde::json::client_from_str::<T>(json)
+        .unwrap()
+        .validate()
+        .map_err(|e| e.to_string())
+}
+
+#[test]
+fn constraints() {
+    let valid = r#"{"name":"foo","nickname":"f","tags":["a","b"],"count":10,"id":1}"#;
+    assert_eq!(validate::<Constrained>(valid), Ok(()));
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"","count":0,"id":1}"#),
+        Err("name: length must be at least 1 but was 0".to_string()),
+    );
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"föööö","count":0,"id":1}"#),
+        Ok(()),
+    );
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"foo","nickname":"fooo","count":0,"id":1}"#),
+        Err("nickname: length must be at most 3 but was 4".to_string()),
+    );
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"foo","tags":["a","b","c"],"count":0,"id":1}"#),
+        Err("tags: size must be at most 2 but was 3".to_string()),
+    );
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"foo","count":11,"id":1}"#),
+        Err("count: value must be at most 10 but was 11".to_string()),
+    );
+    assert_eq!(
+        validate::<Constrained>(r#"{"name":"foo","count":0,"id":0}"#),
+        Err("id: value must be at least 1 but was 0".to_string()),
+    );
+    assert_eq!(
+        validate::<Percentage>("100.5"),
+        Err("value must be at most 100 but was 100.5".to_string()),
+    );
+    assert_eq!(
+        validate::<Percentage>(r#""NaN""#),
+        Err("value must be at least 0 but was NaN".to_string()),
+    );
+}
+
+#[test]
+fn unknown_variants() {
+    assert_eq!(validate::<Color>(r#""RED""#), Ok(()));
+    assert_eq!(
+        validate::<Color>(r#""BLUE""#),
+        Err("unknown variant `BLUE`".to_string()),
+    );
+
+    assert_eq!(
+        validate::<Choice>(r#"{"type":"color","color":"RED"}"#),
+        Ok(())
+    );
+    assert_eq!(
+        validate::<Choice>(r#"{"type":"color","color":"BLUE"}"#),
+        Err("color: unknown variant `BLUE`".to_string()),
+    );
+    assert_eq!(
+        validate::<Choice>(r#"{"type":"shape","shape":{}}"#),
+        Err("unknown variant `shape`".to_string()),
+    );
+}
+
+#[test]
+fn nested() {
+    assert_eq!(
+        validate::<Wrapper>(r#"{"colors":["RED","GREEN"],"percentage":50}"#),
+        Ok(()),
+    );
+    assert_eq!(
+        validate::<Wrapper>(r#"{"colors":["RED","BLUE"]}"#),
+        Err("colors[1]: unknown variant `BLUE`".to_string()),
+    );
+    assert_eq!(
+        validate::<Wrapper>(r#"{"choices":{"a":{"type":"color","color":"BLUE"}}}"#),
+        Err("choices[*].color: unknown variant `BLUE`".to_string()),
+    );
+    assert_eq!(
+        validate::<Wrapper>(r#"{"percentage":-1}"#),
+        Err("percentage: value must be at least 0 but was -1".to_string()),
+    );
+    assert_eq!(
+        validate::<Wrapper>(r#"{"constrained":{"name":"foobar","count":0,"id":1}}"#),
+        Err("constrained.name: length must be at most 5 but was 6".to_string()),
+    );
+}